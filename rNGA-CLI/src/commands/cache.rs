@@ -0,0 +1,31 @@
+//! Cache commands.
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use rnga::cache::CacheStorage;
+use rust_i18n::t;
+
+use crate::config::load_config;
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Remove every entry from the configured cache
+    Clear,
+}
+
+pub async fn handle(action: CacheAction) -> Result<()> {
+    match action {
+        CacheAction::Clear => clear().await,
+    }
+}
+
+async fn clear() -> Result<()> {
+    let cache = load_config()?
+        .cache()
+        .context("No cache configured. Run 'rnga config set cache_dir <path>' first.")?;
+
+    cache.clear().await;
+    println!("{}", t!("cache_cleared", path = cache.dir().display()));
+
+    Ok(())
+}
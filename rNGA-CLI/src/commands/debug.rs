@@ -0,0 +1,74 @@
+//! Debug commands.
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use crate::config::build_client;
+
+#[derive(Subcommand)]
+pub enum DebugAction {
+    /// Print the raw response body of an API endpoint
+    Fetch {
+        /// API path (e.g. thread.php)
+        api: String,
+        /// Query parameters as key=value
+        #[arg(value_parser = parse_param)]
+        params: Vec<(String, String)>,
+    },
+}
+
+pub async fn handle(action: DebugAction) -> Result<()> {
+    match action {
+        DebugAction::Fetch { api, params } => fetch(&api, &params).await,
+    }
+}
+
+async fn fetch(api: &str, params: &[(String, String)]) -> Result<()> {
+    let client = build_client()?;
+    let query: Vec<(&str, &str)> = params
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+
+    let body = client.fetch_raw(api, &query, &[]).await?;
+    println!("{}", body);
+
+    Ok(())
+}
+
+/// Parse a `key=value` argument.
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.to_owned(), v.to_owned()))
+        .ok_or_else(|| format!("expected key=value, got '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(subcommand)]
+        action: DebugAction,
+    }
+
+    fn parse(args: &[&str]) -> Result<DebugAction, clap::Error> {
+        let args = std::iter::once("debug").chain(args.iter().copied());
+        TestCli::try_parse_from(args).map(|cli| cli.action)
+    }
+
+    #[test]
+    fn test_parse_fetch() {
+        let DebugAction::Fetch { api, params } =
+            parse(&["fetch", "thread.php", "fid=7", "page=2"]).unwrap();
+        assert_eq!(api, "thread.php");
+        assert_eq!(
+            params,
+            vec![("fid".into(), "7".into()), ("page".into(), "2".into())]
+        );
+
+        assert!(parse(&["fetch", "thread.php", "fid"]).is_err());
+    }
+}
@@ -1,11 +1,14 @@
 //! Forum commands.
 
 use anyhow::Result;
-use clap::Subcommand;
+use clap::{ArgGroup, Subcommand};
+use colored::Colorize;
 use rust_i18n::t;
+use std::path::{Path, PathBuf};
 
-use crate::config::{build_authed_client, build_client};
+use crate::config::{build_authed_client, build_client, load_config_or_default};
 use crate::handlers::forum as handlers;
+use crate::handlers::topic::RecentTopicsOptions;
 use crate::output::{print_table, OutputFormat};
 
 #[derive(Subcommand)]
@@ -14,6 +17,9 @@ pub enum ForumAction {
     #[command(alias = "ls")]
     List,
 
+    /// Print categories with their forums nested beneath
+    Tree,
+
     /// Search forums by name
     Search {
         /// Search keyword
@@ -23,9 +29,50 @@ pub enum ForumAction {
     /// List favorite forums
     Favorites,
 
+    /// Print an RSS feed of the latest topics in a forum
+    Feed {
+        /// Forum ID, as `fid:123`, `stid:456` or a bare fid
+        id: String,
+        /// Treat ID as stid instead of fid
+        #[arg(short, long)]
+        stid: bool,
+    },
+
+    /// Export favorite forums as an OPML subscription list
+    ExportOpml {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Feed URL template, with {param} (fid or stid) and {id} placeholders
+        /// [default: no feed URL, outlines only link to the forum pages]
+        #[arg(long)]
+        feed_url: Option<String>,
+    },
+
+    /// Show recent topics from several forums, merged by last post time
+    RecentMulti {
+        /// Comma-separated forum IDs, e.g. 123,456,-7
+        #[arg(
+            long,
+            value_delimiter = ',',
+            allow_hyphen_values = true,
+            required = true
+        )]
+        forums: Vec<String>,
+        /// Time range: second, minute, hour, day, week, month, year
+        #[arg(short, long, default_value = "1h")]
+        range: String,
+        /// Include pinned/topped topics
+        #[arg(long)]
+        include_topped: bool,
+        /// Number of concurrent requests [default: 4, or `concurrency` from config]
+        #[arg(short = 'j', long)]
+        concurrency: Option<usize>,
+    },
+
     /// Add forum to favorites
     FavAdd {
-        /// Forum ID
+        /// Forum ID, as `fid:123`, `stid:456` or a bare fid
         id: String,
         /// Treat ID as stid instead of fid
         #[arg(short, long)]
@@ -34,21 +81,56 @@ pub enum ForumAction {
 
     /// Remove forum from favorites
     FavRemove {
-        /// Forum ID
+        /// Forum ID, as `fid:123`, `stid:456` or a bare fid
         id: String,
         /// Treat ID as stid instead of fid
         #[arg(short, long)]
         stid: bool,
     },
+
+    /// Block or unblock a subforum in a forum's topic list
+    #[command(group(ArgGroup::new("mode").required(true).args(["block", "unblock"])))]
+    Filter {
+        /// Forum ID
+        fid: String,
+        /// Subforum filter ID
+        subforum_filter_id: String,
+        /// Hide the subforum's topics
+        #[arg(long)]
+        block: bool,
+        /// Show the subforum's topics again
+        #[arg(long)]
+        unblock: bool,
+    },
 }
 
 pub async fn handle(action: ForumAction, format: OutputFormat, verbose: bool) -> Result<()> {
     match action {
         ForumAction::List => list_categories(format, verbose).await,
+        ForumAction::Tree => print_tree(format).await,
         ForumAction::Search { keyword } => search_forums(&keyword, format).await,
         ForumAction::Favorites => list_favorites(format).await,
+        ForumAction::Feed { id, stid } => print_feed(&id, stid).await,
+        ForumAction::ExportOpml { output, feed_url } => {
+            export_opml(output.as_deref(), feed_url.as_deref()).await
+        }
+        ForumAction::RecentMulti {
+            forums,
+            range,
+            include_topped,
+            concurrency,
+        } => {
+            let concurrency = load_config_or_default().concurrency_or(concurrency);
+            recent_multi(&forums, &range, include_topped, concurrency, format).await
+        }
         ForumAction::FavAdd { id, stid } => add_favorite(&id, stid).await,
         ForumAction::FavRemove { id, stid } => remove_favorite(&id, stid).await,
+        ForumAction::Filter {
+            fid,
+            subforum_filter_id,
+            block,
+            ..
+        } => set_filter(&fid, &subforum_filter_id, block).await,
     }
 }
 
@@ -71,6 +153,22 @@ async fn list_categories(format: OutputFormat, verbose: bool) -> Result<()> {
     Ok(())
 }
 
+async fn print_tree(format: OutputFormat) -> Result<()> {
+    let client = build_client()?;
+    let categories = handlers::list_categories(&client).await?;
+
+    match format {
+        OutputFormat::Plain | OutputFormat::Table => {
+            for line in handlers::tree_lines(&categories) {
+                line.print();
+            }
+        }
+        _ => print_table(categories, format),
+    }
+
+    Ok(())
+}
+
 async fn search_forums(keyword: &str, format: OutputFormat) -> Result<()> {
     let client = build_client()?;
     let forums = handlers::search_forums(&client, keyword).await?;
@@ -89,6 +187,70 @@ async fn list_favorites(format: OutputFormat) -> Result<()> {
     Ok(())
 }
 
+async fn print_feed(id: &str, is_stid: bool) -> Result<()> {
+    let client = build_client()?;
+    let rss = handlers::forum_feed(&client, id, is_stid).await?;
+
+    print!("{}", rss);
+    Ok(())
+}
+
+async fn export_opml(output: Option<&Path>, feed_url: Option<&str>) -> Result<()> {
+    let client = build_authed_client()?;
+    let opml = handlers::export_opml(&client, feed_url).await?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, opml)?;
+            println!("{}", t!("opml_written", path = path.display()));
+        }
+        None => print!("{}", opml),
+    }
+    Ok(())
+}
+
+async fn recent_multi(
+    forums: &[String],
+    range: &str,
+    include_topped: bool,
+    concurrency: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let client = build_client()?;
+
+    let options = RecentTopicsOptions {
+        is_stid: false,
+        range: range.to_string(),
+        order: "lastpost".to_string(),
+        with_posts: false,
+        concurrency,
+        include_topped,
+    };
+
+    let topics = handlers::recent_multi(&client, forums, options, concurrency).await?;
+
+    if matches!(format, OutputFormat::Plain) {
+        println!(
+            "{}",
+            t!(
+                "recent_content_header",
+                forum = forums.join(", ").bold(),
+                content_type = t!("recent_topics_type"),
+                range = range.cyan()
+            )
+        );
+        println!();
+        if topics.is_empty() {
+            println!("{}", t!("no_topics_in_range"));
+            return Ok(());
+        }
+    }
+
+    print_table(topics, format);
+
+    Ok(())
+}
+
 async fn add_favorite(id: &str, is_stid: bool) -> Result<()> {
     let client = build_authed_client()?;
     let result = handlers::add_favorite(&client, id, is_stid).await?;
@@ -104,3 +266,91 @@ async fn remove_favorite(id: &str, is_stid: bool) -> Result<()> {
     println!("{}", t!("removed_forum_from_favorites", id = result.id));
     Ok(())
 }
+
+async fn set_filter(fid: &str, subforum_filter_id: &str, block: bool) -> Result<()> {
+    let client = build_authed_client()?;
+    let result = handlers::set_subforum_filter(&client, fid, subforum_filter_id, block).await?;
+
+    if block {
+        println!(
+            "{}",
+            t!(
+                "blocked_subforum",
+                id = result.subforum_filter_id,
+                fid = result.fid
+            )
+        );
+    } else {
+        println!(
+            "{}",
+            t!(
+                "unblocked_subforum",
+                id = result.subforum_filter_id,
+                fid = result.fid
+            )
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(subcommand)]
+        action: ForumAction,
+    }
+
+    fn try_parse(args: &[&str]) -> clap::error::Result<ForumAction> {
+        let args = std::iter::once("forum").chain(args.iter().copied());
+        TestCli::try_parse_from(args).map(|cli| cli.action)
+    }
+
+    #[test]
+    fn test_parse_favorite_subcommands() {
+        assert!(matches!(
+            try_parse(&["favorites"]),
+            Ok(ForumAction::Favorites)
+        ));
+        assert!(matches!(
+            try_parse(&["fav-add", "310"]),
+            Ok(ForumAction::FavAdd { id, stid: false }) if id == "310"
+        ));
+        assert!(matches!(
+            try_parse(&["fav-remove", "12345", "--stid"]),
+            Ok(ForumAction::FavRemove { id, stid: true }) if id == "12345"
+        ));
+    }
+
+    #[test]
+    fn test_parse_recent_multi() {
+        assert!(matches!(
+            try_parse(&["recent-multi", "--forums", "123,456,-7", "--range", "2h"]),
+            Ok(ForumAction::RecentMulti { forums, range, .. })
+                if forums == ["123", "456", "-7"] && range == "2h"
+        ));
+        assert!(try_parse(&["recent-multi"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert!(matches!(
+            try_parse(&["filter", "310", "7", "--block"]),
+            Ok(ForumAction::Filter { fid, subforum_filter_id, block: true, unblock: false })
+                if fid == "310" && subforum_filter_id == "7"
+        ));
+        assert!(matches!(
+            try_parse(&["filter", "310", "7", "--unblock"]),
+            Ok(ForumAction::Filter {
+                block: false,
+                unblock: true,
+                ..
+            })
+        ));
+        assert!(try_parse(&["filter", "310", "7"]).is_err());
+        assert!(try_parse(&["filter", "310", "7", "--block", "--unblock"]).is_err());
+    }
+}
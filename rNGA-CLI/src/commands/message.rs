@@ -5,7 +5,7 @@ use clap::Subcommand;
 use colored::Colorize;
 use rust_i18n::t;
 
-use crate::config::build_authed_client;
+use crate::config::{build_authed_client, load_config_or_default};
 use crate::handlers::message as handlers;
 use crate::output::{print_table, OutputFormat};
 
@@ -19,6 +19,17 @@ pub enum MessageAction {
         page: u32,
     },
 
+    /// Search conversations by subject or participant
+    ///
+    /// NGA has no message search, so the whole inbox is fetched and filtered locally.
+    Search {
+        /// Keyword to look for
+        keyword: String,
+        /// Number of concurrent requests [default: 4, or `concurrency` from config]
+        #[arg(short = 'j', long)]
+        concurrency: Option<usize>,
+    },
+
     /// View messages in a conversation
     Read {
         /// Conversation/message ID
@@ -47,11 +58,25 @@ pub enum MessageAction {
         /// Reply content
         content: String,
     },
+
+    /// Delete a conversation
+    #[command(alias = "rm")]
+    Delete {
+        /// Conversation/message ID
+        mid: String,
+    },
 }
 
 pub async fn handle(action: MessageAction, format: OutputFormat, _verbose: bool) -> Result<()> {
     match action {
         MessageAction::List { page } => list_conversations(page, format).await,
+        MessageAction::Search {
+            keyword,
+            concurrency,
+        } => {
+            let concurrency = load_config_or_default().concurrency_or(concurrency);
+            search_conversations(&keyword, concurrency, format).await
+        }
         MessageAction::Read { mid, page } => read_conversation(&mid, page, format).await,
         MessageAction::Send {
             to,
@@ -59,6 +84,7 @@ pub async fn handle(action: MessageAction, format: OutputFormat, _verbose: bool)
             content,
         } => send_message(&to, &subject, &content).await,
         MessageAction::Reply { mid, content } => reply_message(&mid, &content).await,
+        MessageAction::Delete { mid } => delete_conversation(&mid).await,
     }
 }
 
@@ -77,6 +103,29 @@ async fn list_conversations(page: u32, format: OutputFormat) -> Result<()> {
     Ok(())
 }
 
+async fn search_conversations(
+    keyword: &str,
+    concurrency: usize,
+    format: OutputFormat,
+) -> Result<()> {
+    let client = build_authed_client()?;
+    let conversations = handlers::search_conversations(&client, keyword, concurrency).await?;
+
+    if matches!(format, OutputFormat::Plain) {
+        println!(
+            "{}\n",
+            t!(
+                "message_search_results",
+                keyword = keyword,
+                count = conversations.len()
+            )
+        );
+    }
+
+    print_table(conversations, format);
+    Ok(())
+}
+
 async fn read_conversation(mid: &str, page: u32, format: OutputFormat) -> Result<()> {
     let client = build_authed_client()?;
     let result = handlers::read_conversation(&client, mid, page).await?;
@@ -112,3 +161,56 @@ async fn reply_message(mid: &str, content: &str) -> Result<()> {
     println!("{}", t!("reply_sent"));
     Ok(())
 }
+
+async fn delete_conversation(mid: &str) -> Result<()> {
+    let client = build_authed_client()?;
+    let result = handlers::delete_conversation(&client, mid).await?;
+
+    println!("{}", t!("conversation_deleted", mid = result.mid));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(subcommand)]
+        action: MessageAction,
+    }
+
+    fn parse(args: &[&str]) -> MessageAction {
+        let args = std::iter::once("message").chain(args.iter().copied());
+        TestCli::try_parse_from(args).unwrap().action
+    }
+
+    #[test]
+    fn test_parse_message_subcommands() {
+        assert!(matches!(parse(&["ls"]), MessageAction::List { page: 1 }));
+        assert!(matches!(
+            parse(&["read", "42", "-p", "3"]),
+            MessageAction::Read { mid, page: 3 } if mid == "42"
+        ));
+        assert!(matches!(
+            parse(&["send", "--to", "alice", "--subject", "Hi", "hello"]),
+            MessageAction::Send { to, subject, content }
+                if to == "alice" && subject == "Hi" && content == "hello"
+        ));
+        assert!(matches!(
+            parse(&["reply", "42", "thanks"]),
+            MessageAction::Reply { mid, content } if mid == "42" && content == "thanks"
+        ));
+        assert!(matches!(
+            parse(&["rm", "42"]),
+            MessageAction::Delete { mid } if mid == "42"
+        ));
+    }
+
+    #[test]
+    fn test_send_requires_recipient() {
+        let result = TestCli::try_parse_from(["message", "send", "--subject", "Hi", "hello"]);
+        assert!(result.is_err());
+    }
+}
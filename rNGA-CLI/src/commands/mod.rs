@@ -1,5 +1,7 @@
 //! Command implementations.
 
+pub mod cache;
+pub mod debug;
 pub mod forum;
 pub mod message;
 pub mod notification;
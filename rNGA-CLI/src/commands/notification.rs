@@ -23,6 +23,12 @@ pub enum NotificationAction {
         /// Page number
         #[arg(short, long, default_value = "1")]
         page: u32,
+        /// Only show unread notifications
+        #[arg(short, long)]
+        unread: bool,
+        /// Only show notifications about this topic
+        #[arg(short, long)]
+        topic: Option<String>,
     },
 
     /// Mark notification as read
@@ -46,7 +52,12 @@ pub async fn handle(
 ) -> Result<()> {
     match action {
         NotificationAction::Counts => show_counts(format).await,
-        NotificationAction::List { kind, page } => list_notifications(&kind, page, format).await,
+        NotificationAction::List {
+            kind,
+            page,
+            unread,
+            topic,
+        } => list_notifications(&kind, page, unread, topic.as_deref(), format).await,
         NotificationAction::Read { id } => mark_read(&id).await,
         NotificationAction::ReadAll { kind } => mark_all_read(&kind).await,
     }
@@ -58,23 +69,27 @@ async fn show_counts(format: OutputFormat) -> Result<()> {
 
     print_table(counts.to_rows(), format);
 
-    if matches!(format, OutputFormat::Plain) {
-        if counts.total > 0 {
-            println!(
-                "\n{}",
-                t!("total_unread", count = counts.total)
-                    .to_string()
-                    .yellow()
-            );
-        }
+    if matches!(format, OutputFormat::Plain) && counts.total > 0 {
+        println!(
+            "\n{}",
+            t!("total_unread", count = counts.total)
+                .to_string()
+                .yellow()
+        );
     }
 
     Ok(())
 }
 
-async fn list_notifications(kind: &str, page: u32, format: OutputFormat) -> Result<()> {
+async fn list_notifications(
+    kind: &str,
+    page: u32,
+    unread_only: bool,
+    topic: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
     let client = build_authed_client()?;
-    let result = handlers::list_notifications(&client, kind, page).await?;
+    let result = handlers::list_notifications(&client, kind, page, unread_only, topic).await?;
 
     if matches!(format, OutputFormat::Plain) {
         println!(
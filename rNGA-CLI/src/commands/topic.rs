@@ -1,24 +1,27 @@
 //! Topic commands.
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Subcommand;
 use colored::Colorize;
 use rust_i18n::t;
 use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
-use crate::config::{build_authed_client, build_client};
+use crate::config::{build_authed_client, build_client, load_config_or_default};
 use crate::handlers::topic::{
     self as handlers, ListTopicsOptions, ReadTopicOptions, RecentTopicsOptions, SearchTopicsOptions,
 };
-use crate::output::{format_relative_time, format_time, print_table, OutputFormat};
+use crate::output::{format_relative_time, format_time, print_table, to_json_lines, OutputFormat};
+use crate::pager::{Pager, PagerCommand, PagerStep};
 
 #[derive(Subcommand)]
 pub enum TopicAction {
     /// List topics in a forum
     #[command(alias = "ls")]
     List {
-        /// Forum ID
-        forum_id: String,
+        /// Forum ID, as `fid:123`, `stid:456` or a bare fid [default: `default_forum` from config]
+        forum_id: Option<String>,
         /// Treat ID as stid instead of fid
         #[arg(short, long)]
         stid: bool,
@@ -31,9 +34,9 @@ pub enum TopicAction {
         /// Number of pages to fetch
         #[arg(short = 'n', long, default_value = "1")]
         pages: u32,
-        /// Number of concurrent requests
-        #[arg(short = 'j', long, default_value = "4")]
-        concurrency: usize,
+        /// Number of concurrent requests [default: 4, or `concurrency` from config]
+        #[arg(short = 'j', long)]
+        concurrency: Option<usize>,
     },
 
     /// View topic details and posts
@@ -50,17 +53,30 @@ pub enum TopicAction {
         /// Fetch all pages
         #[arg(long)]
         all: bool,
-        /// Time range filter (e.g., 1h, 30m, 1d)
-        #[arg(short = 'r', long)]
+        /// Only show posts newer than this (e.g., 1h, 30m, 1d); pages are
+        /// walked backward from the last one until older posts are reached
+        #[arg(short = 'r', long, visible_alias = "since", value_parser = parse_range)]
         range: Option<String>,
-        /// Number of concurrent requests
-        #[arg(short = 'j', long, default_value = "4")]
-        concurrency: usize,
+        /// Number of concurrent requests [default: 4, or `concurrency` from config]
+        #[arg(short = 'j', long)]
+        concurrency: Option<usize>,
+        /// Nest replies under the posts they quote (plain output)
+        #[arg(long)]
+        tree: bool,
+        /// Page through the topic interactively (plain output on a terminal)
+        #[arg(short, long, conflicts_with_all = ["all", "range", "images"])]
+        interactive: bool,
+        /// List the image URLs of each post instead of the posts
+        #[arg(long)]
+        images: bool,
+        /// Save the images into this directory, named by post floor
+        #[arg(long, value_name = "DIR", requires = "images")]
+        download: Option<PathBuf>,
     },
 
     /// Search topics in a forum
     Search {
-        /// Forum ID
+        /// Forum ID, as `fid:123`, `stid:456` or a bare fid
         forum_id: String,
         /// Search keyword
         keyword: String,
@@ -106,10 +122,22 @@ pub enum TopicAction {
         folder: Option<String>,
     },
 
+    /// Print or open the web URL of a topic
+    Open {
+        /// Topic ID
+        topic_id: String,
+        /// Post ID to jump to
+        #[arg(long)]
+        post: Option<String>,
+        /// Open the URL in the default browser instead of printing it
+        #[arg(long)]
+        launch: bool,
+    },
+
     /// List recent topics/posts in a forum
     Recent {
-        /// Forum ID
-        forum_id: String,
+        /// Forum ID, as `fid:123`, `stid:456` or a bare fid [default: `default_forum` from config]
+        forum_id: Option<String>,
         /// Treat ID as stid instead of fid
         #[arg(short, long)]
         stid: bool,
@@ -122,13 +150,18 @@ pub enum TopicAction {
         /// Show individual posts/replies
         #[arg(long)]
         with_posts: bool,
-        /// Number of concurrent requests
-        #[arg(short = 'j', long, default_value = "4")]
-        concurrency: usize,
+        /// Include pinned/topped topics
+        #[arg(long)]
+        include_topped: bool,
+        /// Number of concurrent requests [default: 4, or `concurrency` from config]
+        #[arg(short = 'j', long)]
+        concurrency: Option<usize>,
     },
 }
 
 pub async fn handle(action: TopicAction, format: OutputFormat, verbose: bool) -> Result<()> {
+    let cfg = load_config_or_default();
+
     match action {
         TopicAction::List {
             forum_id,
@@ -137,7 +170,11 @@ pub async fn handle(action: TopicAction, format: OutputFormat, verbose: bool) ->
             order,
             pages,
             concurrency,
-        } => list_topics(&forum_id, stid, page, pages, &order, concurrency, format).await,
+        } => {
+            let forum_id = cfg.forum_or(forum_id)?;
+            let concurrency = cfg.concurrency_or(concurrency);
+            list_topics(&forum_id, stid, page, pages, &order, concurrency, format).await
+        }
         TopicAction::Read {
             topic_id,
             page,
@@ -145,7 +182,23 @@ pub async fn handle(action: TopicAction, format: OutputFormat, verbose: bool) ->
             all,
             range,
             concurrency,
-        } => read_topic(&topic_id, page, author, all, range, concurrency, format, verbose).await,
+            tree,
+            interactive,
+            images,
+            download,
+        } => {
+            let options = read_options(page, author, all, range, cfg.concurrency_or(concurrency));
+            if images {
+                read_images(&topic_id, options, download.as_deref(), format).await
+            } else if interactive
+                && matches!(format, OutputFormat::Plain)
+                && std::io::stdout().is_terminal()
+            {
+                page_topic(&topic_id, options.page, options.author, tree).await
+            } else {
+                read_topic(&topic_id, options, tree, format, verbose).await
+            }
+        }
         TopicAction::Search {
             forum_id,
             keyword,
@@ -157,21 +210,28 @@ pub async fn handle(action: TopicAction, format: OutputFormat, verbose: bool) ->
         TopicAction::Favorites { folder, page } => list_favorites(folder, page, format).await,
         TopicAction::FavAdd { topic_id, folder } => add_favorite(&topic_id, folder).await,
         TopicAction::FavRemove { topic_id, folder } => remove_favorite(&topic_id, folder).await,
+        TopicAction::Open {
+            topic_id,
+            post,
+            launch,
+        } => open_topic(&topic_id, post.as_deref(), launch),
         TopicAction::Recent {
             forum_id,
             stid,
             range,
             order,
             with_posts,
+            include_topped,
             concurrency,
         } => {
             recent_topics(
-                &forum_id,
+                &cfg.forum_or(forum_id)?,
                 stid,
                 &range,
                 &order,
                 with_posts,
-                concurrency,
+                include_topped,
+                cfg.concurrency_or(concurrency),
                 format,
             )
             .await
@@ -232,65 +292,179 @@ async fn list_topics(
     Ok(())
 }
 
-async fn read_topic(
-    topic_id: &str,
+/// Handler options for the `read` arguments.
+fn read_options(
     page: u32,
     author: Option<String>,
     fetch_all: bool,
     range: Option<String>,
     concurrency: usize,
-    format: OutputFormat,
-    _verbose: bool,
-) -> Result<()> {
-    let client = build_client()?;
-
-    let options = ReadTopicOptions {
+) -> ReadTopicOptions {
+    ReadTopicOptions {
         page,
         author,
         fetch_all,
         concurrency,
         range,
-    };
+        progress: false,
+    }
+}
+
+async fn read_topic(
+    topic_id: &str,
+    mut options: ReadTopicOptions,
+    tree: bool,
+    format: OutputFormat,
+    _verbose: bool,
+) -> Result<()> {
+    let client = build_client()?;
+    let fetch_all = options.fetch_all;
+    options.progress = matches!(format, OutputFormat::Plain) && std::io::stdout().is_terminal();
 
     let result = handlers::read_topic(&client, topic_id, options).await?;
 
     if matches!(format, OutputFormat::Plain) {
+        print_topic_header(&result, fetch_all);
+    }
+
+    print_posts(result.posts, tree, format);
+    Ok(())
+}
+
+async fn read_images(
+    topic_id: &str,
+    options: ReadTopicOptions,
+    download: Option<&Path>,
+    format: OutputFormat,
+) -> Result<()> {
+    let client = build_client()?;
+    let concurrency = options.concurrency;
+
+    let result = handlers::read_topic(&client, topic_id, options).await?;
+    let images = handlers::post_images(&result.posts);
+
+    if images.is_empty() {
+        if matches!(format, OutputFormat::Plain) {
+            println!("{}", t!("no_images"));
+        }
+        return Ok(());
+    }
+
+    let Some(dir) = download else {
+        print_table(images, format);
+        return Ok(());
+    };
+
+    let downloads = handlers::download_images(&client, &images, dir, concurrency).await?;
+    for (url, error) in &downloads.failed {
+        eprintln!(
+            "{}",
+            t!("image_download_failed", url = url, error = error).yellow()
+        );
+    }
+    println!(
+        "{}",
+        t!(
+            "images_downloaded",
+            count = downloads.saved.len(),
+            dir = dir.display()
+        )
+    );
+    Ok(())
+}
+
+/// Print a topic's forum, subject, author and page position.
+fn print_topic_header(result: &handlers::TopicDetailsResult, fetch_all: bool) {
+    println!(
+        "{} {}",
+        format!("[{}]", result.forum_name).dimmed(),
+        result.subject.bold()
+    );
+    if !result.tags.is_empty() {
+        println!("{}", t!("topic_tags", tags = result.tags.join(", ").cyan()));
+    }
+    println!(
+        "{}",
+        t!(
+            "topic_by_author",
+            author = result.author.green(),
+            date = format_time(result.post_date),
+            replies = result.replies
+        )
+    );
+    if fetch_all && result.total_pages > 1 {
         println!(
-            "{} {}",
-            format!("[{}]", result.forum_name).dimmed(),
-            result.subject.bold()
+            "{}\n",
+            t!("topic_fetched_all_pages", total = result.total_pages)
         );
-        if !result.tags.is_empty() {
-            println!("{}", t!("topic_tags", tags = result.tags.join(", ").cyan()));
-        }
+    } else {
         println!(
-            "{}",
+            "{}\n",
             t!(
-                "topic_by_author",
-                author = result.author.green(),
-                date = format_time(result.post_date),
-                replies = result.replies
+                "topic_page_info",
+                page = result.page,
+                total = result.total_pages
             )
         );
-        if fetch_all && result.total_pages > 1 {
-            println!(
-                "{}\n",
-                t!("topic_fetched_all_pages", total = result.total_pages)
-            );
-        } else {
-            println!(
-                "{}\n",
-                t!(
-                    "topic_page_info",
-                    page = result.page,
-                    total = result.total_pages
-                )
-            );
+    }
+}
+
+/// Print posts, nested by quote when `tree` is set and output is plain.
+fn print_posts(posts: Vec<handlers::PostInfo>, tree: bool, format: OutputFormat) {
+    if tree && matches!(format, OutputFormat::Plain) {
+        for node in handlers::build_post_tree(posts) {
+            node.plain_print_tree(0);
         }
+        return;
     }
 
-    print_table(result.posts, format);
-    Ok(())
+    print_table(posts, format);
+}
+
+/// Show a topic one page at a time, reading navigation commands from stdin.
+async fn page_topic(topic_id: &str, page: u32, author: Option<String>, tree: bool) -> Result<()> {
+    let client = build_client()?;
+    let mut pager = Pager::new(page, page);
+    let mut step = PagerStep::Show(pager.page());
+
+    loop {
+        if let PagerStep::Show(page) = step {
+            let options = ReadTopicOptions {
+                page,
+                author: author.clone(),
+                ..Default::default()
+            };
+            let result = handlers::read_topic(&client, topic_id, options).await?;
+            pager.set_total_pages(result.total_pages);
+
+            print_topic_header(&result, false);
+            print_posts(result.posts, tree, OutputFormat::Plain);
+        }
+
+        let prompt = t!(
+            "pager_prompt",
+            page = pager.page(),
+            total = pager.total_pages()
+        );
+        print!("{} ", prompt.dimmed());
+        std::io::stdout().flush()?;
+
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        step = match PagerCommand::parse(&line) {
+            Some(command) => pager.apply(command),
+            None => {
+                println!("{}", t!("pager_help"));
+                PagerStep::Stay
+            }
+        };
+        if step == PagerStep::Quit {
+            return Ok(());
+        }
+    }
 }
 
 async fn search_topics(
@@ -369,12 +543,65 @@ async fn remove_favorite(topic_id: &str, folder: Option<String>) -> Result<()> {
     Ok(())
 }
 
+fn open_topic(topic_id: &str, post: Option<&str>, launch: bool) -> Result<()> {
+    let client = build_client()?;
+    let url = topic_url(client.base_url(), topic_id, post);
+
+    if launch {
+        println!("{}", t!("opening_in_browser", url = url));
+        launch_browser(&url)
+    } else {
+        println!("{}", url);
+        Ok(())
+    }
+}
+
+/// Validate a time range argument such as `2h` or `day`.
+fn parse_range(s: &str) -> Result<String, String> {
+    handlers::parse_time_range(s)
+        .map(|_| s.to_owned())
+        .ok_or_else(|| format!("invalid time range '{}'", s))
+}
+
+/// Web URL of a topic on the site at `base_url`, anchored at `post` when given.
+fn topic_url(base_url: &str, topic_id: &str, post: Option<&str>) -> String {
+    let url = rnga::Topic::with_id(topic_id).url(base_url);
+    match post {
+        Some(pid) => format!("{}#pid{}Anchor", url, pid),
+        None => url,
+    }
+}
+
+/// Open `url` with the platform's default handler.
+fn launch_browser(url: &str) -> Result<()> {
+    use std::process::Command;
+
+    // `cmd /C start` would split the URL at `&`, so go through explorer,
+    // which takes it as one argument.
+    let mut command = if cfg!(target_os = "windows") {
+        Command::new("explorer")
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let status = command.arg(url).status()?;
+    // explorer exits with 1 even when it opened the URL.
+    if !status.success() && !cfg!(target_os = "windows") {
+        bail!("Browser launcher exited with {}", status);
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn recent_topics(
     forum_id: &str,
     is_stid: bool,
     range: &str,
     order: &str,
     with_posts: bool,
+    include_topped: bool,
     concurrency: usize,
     format: OutputFormat,
 ) -> Result<()> {
@@ -386,6 +613,7 @@ async fn recent_topics(
         order: order.to_string(),
         with_posts,
         concurrency,
+        include_topped,
     };
 
     let result = handlers::recent_topics(&client, forum_id, options).await?;
@@ -441,6 +669,11 @@ async fn recent_topics(
         OutputFormat::Json => {
             println!("{}", serde_json::to_string_pretty(&result.posts)?);
         }
+        OutputFormat::JsonLines => {
+            if !result.posts.is_empty() {
+                println!("{}", to_json_lines(&result.posts));
+            }
+        }
         OutputFormat::Toon => {
             let json_value = serde_json::to_value(&result.posts)?;
             println!("{}", toon_format::encode_default(&json_value).unwrap_or_default());
@@ -462,7 +695,7 @@ async fn recent_topics(
                     (topic_id.clone(), max_time)
                 })
                 .collect();
-            topic_order.sort_by(|a, b| b.1.cmp(&a.1));
+            topic_order.sort_by_key(|t| std::cmp::Reverse(t.1));
 
             for (topic_id, _) in topic_order {
                 let first_post = result
@@ -482,9 +715,9 @@ async fn recent_topics(
 
                 for post in topic_posts {
                     let floor_display = if post.post_type == "comment" {
-                        format!("{}", post.floor).magenta()
+                        post.floor.to_string().magenta()
                     } else {
-                        format!("{}", post.floor).yellow()
+                        post.floor.to_string().yellow()
                     };
 
                     println!(
@@ -520,3 +753,71 @@ async fn recent_topics(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEFAULT_CONCURRENCY;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(subcommand)]
+        action: TopicAction,
+    }
+
+    fn parse(args: &[&str]) -> Result<TopicAction, clap::Error> {
+        let args = std::iter::once("topic").chain(args.iter().copied());
+        TestCli::try_parse_from(args).map(|cli| cli.action)
+    }
+
+    /// `range` of the handler options built from a `read` command line.
+    fn read_range(args: &[&str]) -> Option<String> {
+        match parse(args).unwrap() {
+            TopicAction::Read {
+                page,
+                author,
+                all,
+                range,
+                ..
+            } => read_options(page, author, all, range, DEFAULT_CONCURRENCY).range,
+            _ => panic!("expected read"),
+        }
+    }
+
+    #[test]
+    fn test_parse_read_since() {
+        assert_eq!(
+            read_range(&["read", "1", "--since", "2h"]).as_deref(),
+            Some("2h")
+        );
+        assert_eq!(
+            read_range(&["read", "1", "-r", "1d"]).as_deref(),
+            Some("1d")
+        );
+        assert_eq!(read_range(&["read", "1"]), None);
+
+        assert!(parse(&["read", "1", "--since", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_read_interactive_conflicts() {
+        for flag in ["--all", "--images"] {
+            assert!(parse(&["read", "1", "-i", flag]).is_err());
+        }
+        assert!(parse(&["read", "1", "-i", "--since", "2h"]).is_err());
+        assert!(parse(&["read", "1", "-i", "--author", "42"]).is_ok());
+    }
+
+    #[test]
+    fn test_topic_url() {
+        assert_eq!(
+            topic_url("https://nga.178.com/", "12345678", None),
+            "https://nga.178.com/read.php?tid=12345678"
+        );
+        assert_eq!(
+            topic_url("https://bbs.nga.cn/", "12345678", Some("87654321")),
+            "https://bbs.nga.cn/read.php?tid=12345678#pid87654321Anchor"
+        );
+    }
+}
@@ -29,6 +29,9 @@ pub enum UserAction {
     Search {
         /// Search keyword
         keyword: String,
+        /// Page number
+        #[arg(short, long, default_value = "1")]
+        page: u32,
     },
 
     /// View user's topics
@@ -47,6 +50,9 @@ pub enum UserAction {
         /// Page number
         #[arg(short, long, default_value = "1")]
         page: u32,
+        /// Only show posts in this forum ID
+        #[arg(long)]
+        forum: Option<String>,
     },
 }
 
@@ -55,9 +61,13 @@ pub async fn handle(action: UserAction, format: OutputFormat, _verbose: bool) ->
         UserAction::Get { user_id } => get_user_by_id(&user_id, format).await,
         UserAction::Name { username } => get_user_by_name(&username, format).await,
         UserAction::Me => get_me(format).await,
-        UserAction::Search { keyword } => search_users(&keyword, format).await,
+        UserAction::Search { keyword, page } => search_users(&keyword, page, format).await,
         UserAction::Topics { user_id, page } => user_topics(&user_id, page, format).await,
-        UserAction::Posts { user_id, page } => user_posts(&user_id, page, format).await,
+        UserAction::Posts {
+            user_id,
+            page,
+            forum,
+        } => user_posts(&user_id, page, forum.as_deref(), format).await,
     }
 }
 
@@ -85,18 +95,29 @@ async fn get_me(format: OutputFormat) -> Result<()> {
     Ok(())
 }
 
-async fn search_users(keyword: &str, format: OutputFormat) -> Result<()> {
+async fn search_users(keyword: &str, page: u32, format: OutputFormat) -> Result<()> {
     let client = build_client()?;
-    let results = handlers::search_users(&client, keyword).await?;
+    let result = handlers::search_users(&client, keyword, page).await?;
 
-    if results.is_empty() {
+    if result.users.is_empty() {
         if matches!(format, OutputFormat::Plain) {
             println!("{}", t!("no_users_found"));
         }
         return Ok(());
     }
 
-    print_table(results, format);
+    if matches!(format, OutputFormat::Plain) && result.total_pages > 1 {
+        println!(
+            "{}\n",
+            t!(
+                "topic_page_info",
+                page = result.page,
+                total = result.total_pages
+            )
+        );
+    }
+
+    print_table(result.users, format);
     Ok(())
 }
 
@@ -120,9 +141,14 @@ async fn user_topics(user_id: &str, page: u32, format: OutputFormat) -> Result<(
     Ok(())
 }
 
-async fn user_posts(user_id: &str, page: u32, format: OutputFormat) -> Result<()> {
+async fn user_posts(
+    user_id: &str,
+    page: u32,
+    forum: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
     let client = build_client()?;
-    let result = handlers::user_posts(&client, user_id, page).await?;
+    let result = handlers::user_posts(&client, user_id, page, forum).await?;
 
     if matches!(format, OutputFormat::Plain) {
         println!(
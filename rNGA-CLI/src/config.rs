@@ -1,21 +1,245 @@
 //! Configuration management for NGA CLI.
 
-use anyhow::{Context, Result};
-use rnga::NGAClient;
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use colored::Colorize;
+use rnga::cache::FileCache;
+use rnga::{Device, NGAClient, NGAClientBuilder, RequestObserver};
+use rust_i18n::t;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use crate::output::{OutputFormat, TimeStyle};
+
+/// Number of concurrent requests when neither flag nor config sets one.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Keys accepted by `config set` and `config get`.
+pub const CONFIG_KEYS: &[&str] = &[
+    "default_forum",
+    "concurrency",
+    "format",
+    "lang",
+    "timezone",
+    "time_format",
+    "cache_dir",
+    "offline",
+];
+
+/// Profile name that refers to the top-level `auth` credentials.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Profile selected with the global `--profile` flag.
+static PROFILE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// Whether raw response bodies are printed, set by the global `--raw` flag.
+static RAW_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Whether requests are served from cache only, set by the global `--offline` flag.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Device to send requests as, set by the global `--device` flag.
+static DEVICE: OnceLock<Device> = OnceLock::new();
 
 /// CLI configuration.
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Config {
-    /// Authentication credentials.
+    /// Authentication credentials of the default profile.
     pub auth: Option<AuthConfig>,
+    /// Profile used when `--profile` is not given.
+    pub active_profile: Option<String>,
+    /// Forum ID used when a command's forum argument is omitted.
+    pub default_forum: Option<String>,
+    /// Default number of concurrent requests.
+    pub concurrency: Option<usize>,
+    /// Default output format.
+    pub format: Option<String>,
+    /// Default output language.
+    pub lang: Option<String>,
+    /// IANA timezone for timestamps, instead of the local one.
+    pub timezone: Option<String>,
+    /// strftime format for timestamps.
+    pub time_format: Option<String>,
+    /// Directory of the response cache; no cache when unset.
+    pub cache_dir: Option<String>,
+    /// Serve reads from the cache only, never touching the network.
+    pub offline: Option<bool>,
+    /// Named account profiles.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, AuthConfig>,
+}
+
+impl Config {
+    /// Set a default by key, validating the value.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "default_forum" => self.default_forum = Some(value.to_owned()),
+            "concurrency" => {
+                let n: usize = value
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .with_context(|| format!("Invalid concurrency: {}", value))?;
+                self.concurrency = Some(n);
+            }
+            "format" => {
+                OutputFormat::from_str(value, true)
+                    .map_err(|_| anyhow::anyhow!("Invalid format: {}", value))?;
+                self.format = Some(value.to_owned());
+            }
+            "lang" => self.lang = Some(value.to_owned()),
+            "timezone" => {
+                TimeStyle::new(Some(value), None)?;
+                self.timezone = Some(value.to_owned());
+            }
+            "time_format" => {
+                TimeStyle::new(None, Some(value))?;
+                self.time_format = Some(value.to_owned());
+            }
+            "cache_dir" => self.cache_dir = Some(value.to_owned()),
+            "offline" => {
+                let offline: bool = value
+                    .parse()
+                    .ok()
+                    .with_context(|| format!("Invalid offline value: {}", value))?;
+                self.offline = Some(offline);
+            }
+            _ => bail!(
+                "Unknown config key '{}', expected one of: {}",
+                key,
+                CONFIG_KEYS.join(", ")
+            ),
+        }
+        Ok(())
+    }
+
+    /// Get a default by key.
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(match key {
+            "default_forum" => self.default_forum.clone(),
+            "concurrency" => self.concurrency.map(|n| n.to_string()),
+            "format" => self.format.clone(),
+            "lang" => self.lang.clone(),
+            "timezone" => self.timezone.clone(),
+            "time_format" => self.time_format.clone(),
+            "cache_dir" => self.cache_dir.clone(),
+            "offline" => self.offline.map(|b| b.to_string()),
+            _ => bail!(
+                "Unknown config key '{}', expected one of: {}",
+                key,
+                CONFIG_KEYS.join(", ")
+            ),
+        })
+    }
+
+    /// Resolve the output format from a flag, falling back to the default.
+    pub fn format_or(&self, flag: Option<OutputFormat>) -> OutputFormat {
+        flag.or_else(|| {
+            self.format
+                .as_deref()
+                .and_then(|f| OutputFormat::from_str(f, true).ok())
+        })
+        .unwrap_or_default()
+    }
+
+    /// Resolve the language from a flag, falling back to the default.
+    pub fn lang_or(&self, flag: Option<String>) -> String {
+        flag.or_else(|| self.lang.clone())
+            .unwrap_or_else(|| "en".to_owned())
+    }
+
+    /// Resolve the timestamp style from flags, falling back to the defaults.
+    pub fn time_style_or(
+        &self,
+        timezone: Option<String>,
+        time_format: Option<String>,
+    ) -> Result<TimeStyle> {
+        TimeStyle::new(
+            timezone.as_deref().or(self.timezone.as_deref()),
+            time_format.as_deref().or(self.time_format.as_deref()),
+        )
+    }
+
+    /// Resolve concurrency from a flag, falling back to the default.
+    pub fn concurrency_or(&self, flag: Option<usize>) -> usize {
+        flag.or(self.concurrency).unwrap_or(DEFAULT_CONCURRENCY)
+    }
+
+    /// Resolve a forum ID from an argument, falling back to the default.
+    pub fn forum_or(&self, arg: Option<String>) -> Result<String> {
+        arg.or_else(|| self.default_forum.clone())
+            .context("No forum given. Pass a forum ID or run 'rnga config set default_forum <id>'.")
+    }
+
+    /// The configured response cache, if any.
+    pub fn cache(&self) -> Option<FileCache> {
+        self.cache_dir.as_deref().map(FileCache::new)
+    }
+
+    /// Resolve the profile name from a flag, falling back to the active one.
+    pub fn profile_or<'a>(&'a self, flag: Option<&'a str>) -> &'a str {
+        flag.or(self.active_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Get the credentials stored under a profile.
+    pub fn profile(&self, name: &str) -> Option<&AuthConfig> {
+        if name == DEFAULT_PROFILE {
+            self.auth.as_ref()
+        } else {
+            self.profiles.get(name)
+        }
+    }
+
+    /// Store credentials under a profile, replacing any existing ones.
+    pub fn set_profile(&mut self, name: &str, auth: AuthConfig) {
+        if name == DEFAULT_PROFILE {
+            self.auth = Some(auth);
+        } else {
+            self.profiles.insert(name.to_owned(), auth);
+        }
+    }
+
+    /// Remove a profile, deactivating it if it was active.
+    pub fn remove_profile(&mut self, name: &str) -> Option<AuthConfig> {
+        if self.active_profile.as_deref() == Some(name) {
+            self.active_profile = None;
+        }
+        if name == DEFAULT_PROFILE {
+            self.auth.take()
+        } else {
+            self.profiles.remove(name)
+        }
+    }
+
+    /// Make a stored profile the active one.
+    pub fn use_profile(&mut self, name: &str) -> Result<()> {
+        if self.profile(name).is_none() {
+            bail!("Unknown profile '{}'", name);
+        }
+        self.active_profile = (name != DEFAULT_PROFILE).then(|| name.to_owned());
+        Ok(())
+    }
+
+    /// Credentials of the selected profile, if logged in.
+    ///
+    /// Errors if a named profile was selected but does not exist.
+    pub fn selected_auth(&self, flag: Option<&str>) -> Result<Option<&AuthConfig>> {
+        let name = self.profile_or(flag);
+        match self.profile(name) {
+            None if name != DEFAULT_PROFILE => bail!("Unknown profile '{}'", name),
+            auth => Ok(auth),
+        }
+    }
 }
 
 /// Authentication configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AuthConfig {
     /// Access token.
     pub token: String,
@@ -23,6 +247,61 @@ pub struct AuthConfig {
     pub uid: String,
 }
 
+/// Select a profile for the rest of the process.
+pub fn set_profile_override(name: String) {
+    let _ = PROFILE_OVERRIDE.set(name);
+}
+
+/// Profile selected with the global `--profile` flag, if any.
+pub fn profile_override() -> Option<&'static str> {
+    PROFILE_OVERRIDE.get().map(String::as_str)
+}
+
+/// Print raw response bodies to stderr for clients built afterwards.
+pub fn set_raw_output(enabled: bool) {
+    RAW_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Serve reads from the cache only for clients built afterwards.
+pub fn set_offline(enabled: bool) {
+    OFFLINE.store(enabled, Ordering::Relaxed);
+}
+
+/// Send requests with this device's User-Agent for clients built afterwards.
+pub fn set_device(device: Device) {
+    let _ = DEVICE.set(device);
+}
+
+/// Observer that prints each decoded response body to stderr.
+struct RawBodyPrinter;
+
+impl RequestObserver for RawBodyPrinter {
+    fn on_response(&self, api: &str, status: u16, body: &str) {
+        eprintln!("<- {} {}\n{}", api, status, body);
+    }
+}
+
+/// Client builder with CLI-wide options and the configured cache applied.
+///
+/// With a cache, reads fall back to expired entries on network errors.
+fn client_builder(config: &Config) -> NGAClientBuilder {
+    let mut builder = NGAClient::builder();
+    if let Some(cache) = config.cache() {
+        builder = builder.cache(Arc::new(cache)).cache_fallback(true);
+    }
+    if OFFLINE.load(Ordering::Relaxed) || config.offline == Some(true) {
+        builder = builder.offline(true);
+    }
+    if let Some(&device) = DEVICE.get() {
+        builder = builder.device(device);
+    }
+    if RAW_OUTPUT.load(Ordering::Relaxed) {
+        builder.observer(Arc::new(RawBodyPrinter))
+    } else {
+        builder
+    }
+}
+
 /// Get the configuration file path.
 pub fn config_path() -> Result<PathBuf> {
     let exe_path = env::current_exe().context("Could not determine executable path")?;
@@ -35,23 +314,42 @@ pub fn config_path() -> Result<PathBuf> {
 
 /// Load configuration from file.
 pub fn load_config() -> Result<Config> {
-    let path = config_path()?;
+    load_config_from(&config_path()?)
+}
+
+/// Load configuration from file, warning and falling back to the defaults
+/// when it cannot be read.
+pub fn load_config_or_default() -> Config {
+    load_config().unwrap_or_else(|e| {
+        eprintln!(
+            "{}",
+            t!("config_load_failed", error = format!("{:#}", e)).yellow()
+        );
+        Config::default()
+    })
+}
 
+/// Load configuration from a specific file.
+pub fn load_config_from(path: &Path) -> Result<Config> {
     if !path.exists() {
         return Ok(Config::default());
     }
 
-    let content = fs::read_to_string(&path).context("Failed to read config file")?;
+    let content = fs::read_to_string(path).context("Failed to read config file")?;
 
     toml::from_str(&content).context("Failed to parse config file")
 }
 
 /// Save configuration to file.
 pub fn save_config(config: &Config) -> Result<()> {
-    let path = config_path()?;
+    save_config_to(config, &config_path()?)
+}
+
+/// Save configuration to a specific file.
+pub fn save_config_to(config: &Config, path: &Path) -> Result<()> {
     let content = toml::to_string_pretty(config).context("Failed to serialize config")?;
 
-    fs::write(&path, content).context("Failed to write config file")?;
+    fs::write(path, content).context("Failed to write config file")?;
 
     Ok(())
 }
@@ -60,9 +358,9 @@ pub fn save_config(config: &Config) -> Result<()> {
 pub fn build_client() -> Result<NGAClient> {
     let config = load_config()?;
 
-    let mut builder = NGAClient::builder();
+    let mut builder = client_builder(&config);
 
-    if let Some(auth) = config.auth {
+    if let Some(auth) = config.selected_auth(profile_override())? {
         builder = builder.auth(&auth.token, &auth.uid);
     }
 
@@ -74,11 +372,174 @@ pub fn build_authed_client() -> Result<NGAClient> {
     let config = load_config()?;
 
     let auth = config
-        .auth
+        .selected_auth(profile_override())?
         .context("Authentication required. Run 'rnga auth login' first.")?;
 
-    NGAClient::builder()
+    client_builder(&config)
         .auth(&auth.token, &auth.uid)
         .build()
         .context("Failed to build NGA client")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("rnga-test-{}-{}.toml", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_set_get_defaults() {
+        let mut config = Config::default();
+        config.set("default_forum", "310").unwrap();
+        config.set("concurrency", "8").unwrap();
+        config.set("format", "json").unwrap();
+        config.set("lang", "zh-CN").unwrap();
+
+        assert_eq!(config.get("default_forum").unwrap().as_deref(), Some("310"));
+        assert_eq!(config.get("concurrency").unwrap().as_deref(), Some("8"));
+        assert!(matches!(config.format_or(None), OutputFormat::Json));
+        assert!(matches!(
+            config.format_or(Some(OutputFormat::Table)),
+            OutputFormat::Table
+        ));
+        assert_eq!(config.lang_or(None), "zh-CN");
+        assert_eq!(config.concurrency_or(None), 8);
+        assert_eq!(config.concurrency_or(Some(2)), 2);
+        assert_eq!(config.forum_or(None).unwrap(), "310");
+
+        config.set("timezone", "Asia/Tokyo").unwrap();
+        config.set("time_format", "%H:%M").unwrap();
+        assert_eq!(
+            config.get("timezone").unwrap().as_deref(),
+            Some("Asia/Tokyo")
+        );
+        let style = config.time_style_or(Some("UTC".into()), None).unwrap();
+        assert_eq!(style, TimeStyle::new(Some("UTC"), Some("%H:%M")).unwrap());
+
+        assert!(config.set("concurrency", "0").is_err());
+        assert!(config.set("timezone", "Nowhere/Else").is_err());
+        assert!(config.set("format", "xml").is_err());
+        assert!(config.set("colour", "red").is_err());
+        assert!(config.get("colour").is_err());
+    }
+
+    #[test]
+    fn test_cache_dir() {
+        let mut config = Config::default();
+        assert!(config.cache().is_none());
+
+        config.set("cache_dir", "/tmp/rnga-cache").unwrap();
+        assert_eq!(config.cache().unwrap().dir(), Path::new("/tmp/rnga-cache"));
+    }
+
+    #[test]
+    fn test_offline_key() {
+        let mut config = Config::default();
+        config.set("offline", "true").unwrap();
+        assert_eq!(config.offline, Some(true));
+        assert_eq!(config.get("offline").unwrap().as_deref(), Some("true"));
+        assert!(config.set("offline", "maybe").is_err());
+    }
+
+    #[test]
+    fn test_unset_defaults() {
+        let config = Config::default();
+        assert!(matches!(config.format_or(None), OutputFormat::Plain));
+        assert_eq!(config.lang_or(None), "en");
+        assert_eq!(
+            config.time_style_or(None, None).unwrap(),
+            TimeStyle::default()
+        );
+        assert_eq!(config.concurrency_or(None), DEFAULT_CONCURRENCY);
+        assert!(config.forum_or(None).is_err());
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let path = temp_config_path("round-trip");
+        let mut config = Config {
+            auth: Some(AuthConfig {
+                token: "token".into(),
+                uid: "42".into(),
+            }),
+            ..Config::default()
+        };
+        config.set("default_forum", "-7").unwrap();
+        config.set("concurrency", "6").unwrap();
+
+        save_config_to(&config, &path).unwrap();
+        let loaded = load_config_from(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, config);
+    }
+
+    #[test]
+    fn test_load_missing_and_legacy_files() {
+        let path = temp_config_path("legacy");
+        assert_eq!(load_config_from(&path).unwrap(), Config::default());
+
+        fs::write(&path, "[auth]\ntoken = \"t\"\nuid = \"1\"\n").unwrap();
+        let loaded = load_config_from(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.auth.map(|a| a.uid).as_deref(), Some("1"));
+        assert_eq!(loaded.concurrency, None);
+        assert!(loaded.profiles.is_empty());
+    }
+
+    fn auth(uid: &str) -> AuthConfig {
+        AuthConfig {
+            token: format!("token-{}", uid),
+            uid: uid.into(),
+        }
+    }
+
+    #[test]
+    fn test_add_and_switch_profiles() {
+        let mut config = Config::default();
+        config.set_profile(DEFAULT_PROFILE, auth("1"));
+        config.set_profile("alt", auth("2"));
+
+        assert_eq!(config.auth, Some(auth("1")));
+        assert_eq!(config.selected_auth(None).unwrap(), Some(&auth("1")));
+        assert_eq!(config.selected_auth(Some("alt")).unwrap(), Some(&auth("2")));
+        assert!(config.selected_auth(Some("missing")).is_err());
+
+        config.use_profile("alt").unwrap();
+        assert_eq!(config.active_profile.as_deref(), Some("alt"));
+        assert_eq!(config.selected_auth(None).unwrap(), Some(&auth("2")));
+        assert_eq!(
+            config.selected_auth(Some(DEFAULT_PROFILE)).unwrap(),
+            Some(&auth("1"))
+        );
+        assert!(config.use_profile("missing").is_err());
+
+        config.use_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(config.active_profile, None);
+
+        config.use_profile("alt").unwrap();
+        assert_eq!(config.remove_profile("alt"), Some(auth("2")));
+        assert_eq!(config.active_profile, None);
+        assert_eq!(config.selected_auth(None).unwrap(), Some(&auth("1")));
+    }
+
+    #[test]
+    fn test_load_profiles() {
+        let path = temp_config_path("profiles");
+        let mut config = Config::default();
+        config.set_profile("main", auth("1"));
+        config.set_profile("alt", auth("2"));
+        config.use_profile("alt").unwrap();
+
+        save_config_to(&config, &path).unwrap();
+        let loaded = load_config_from(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, config);
+        assert_eq!(loaded.auth, None);
+        assert_eq!(loaded.selected_auth(None).unwrap(), Some(&auth("2")));
+    }
+}
@@ -2,12 +2,14 @@
 
 use anyhow::Result;
 use colored::Colorize;
+use futures::stream::{self, StreamExt, TryStreamExt};
 use rnga::models::*;
 use rnga::NGAClient;
 use rust_i18n::t;
 use serde::Serialize;
 
-use crate::output::{PlainPrint, TableRow};
+use super::topic::{recent_topics, RecentResult, RecentTopicsOptions, TopicInfo};
+use crate::output::{format_relative_time, PlainPrint, TableRow};
 
 /// Forum information.
 #[derive(Debug, Clone, Serialize)]
@@ -49,6 +51,42 @@ impl PlainPrint for ForumInfo {
     }
 }
 
+/// A recent topic tagged with the forum it was found in.
+#[derive(Debug, Clone, Serialize)]
+pub struct ForumTopicInfo {
+    pub forum: String,
+    #[serde(flatten)]
+    pub topic: TopicInfo,
+}
+
+impl TableRow for ForumTopicInfo {
+    fn headers() -> Vec<&'static str> {
+        vec!["Forum", "ID", "Subject", "Author", "Replies", "Last Post"]
+    }
+    fn row(&self) -> Vec<String> {
+        let mut row = vec![self.forum.clone()];
+        row.extend(self.topic.row());
+        row
+    }
+}
+
+impl PlainPrint for ForumTopicInfo {
+    fn plain_print(&self) {
+        println!(
+            "{} {} {}",
+            format!("[{}]", self.forum).magenta(),
+            t!("topic_label", id = &self.topic.id).to_string().cyan(),
+            self.topic.subject.bold()
+        );
+        println!(
+            "   {} | {} | {}",
+            t!("by_label", author = self.topic.author.green()),
+            format_relative_time(self.topic.last_post_date).dimmed(),
+            t!("replies_label", count = self.topic.replies)
+        );
+    }
+}
+
 /// Category with its forums.
 #[derive(Debug, Clone, Serialize)]
 pub struct CategoryInfo {
@@ -88,6 +126,55 @@ impl PlainPrint for CategoryInfo {
     }
 }
 
+/// Indentation of forums beneath their category in the tree view.
+const TREE_INDENT: &str = "  ";
+
+/// One line of the forum tree view.
+#[derive(Debug)]
+pub enum TreeLine<'a> {
+    /// Category header.
+    Category(&'a CategoryInfo),
+    /// Forum beneath a category.
+    Forum(&'a ForumInfo),
+    /// Placeholder for a category without forums.
+    Empty,
+}
+
+impl TreeLine<'_> {
+    /// Nesting depth of the line.
+    pub fn depth(&self) -> usize {
+        match self {
+            TreeLine::Category(_) => 0,
+            TreeLine::Forum(_) | TreeLine::Empty => 1,
+        }
+    }
+
+    /// Print the line, indented by its depth.
+    pub fn print(&self) {
+        let indent = TREE_INDENT.repeat(self.depth());
+        match self {
+            TreeLine::Category(category) => category.plain_print(),
+            TreeLine::Forum(forum) => {
+                println!("{}[{}] {}", indent, forum.id.cyan(), forum.name.bold())
+            }
+            TreeLine::Empty => println!("{}{}", indent, t!("no_results").to_string().dimmed()),
+        }
+    }
+}
+
+/// Flatten categories into tree lines, each forum beneath its category.
+pub fn tree_lines(categories: &[CategoryInfo]) -> Vec<TreeLine<'_>> {
+    let mut lines = Vec::new();
+    for category in categories {
+        lines.push(TreeLine::Category(category));
+        if category.forums.is_empty() {
+            lines.push(TreeLine::Empty);
+        }
+        lines.extend(category.forums.iter().map(TreeLine::Forum));
+    }
+    lines
+}
+
 /// Result of favorite modification.
 #[derive(Debug, Clone, Serialize)]
 pub struct FavoriteModifyResult {
@@ -95,6 +182,14 @@ pub struct FavoriteModifyResult {
     pub action: String,
 }
 
+/// Result of a subforum filter change.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubforumFilterResult {
+    pub fid: String,
+    pub subforum_filter_id: String,
+    pub action: String,
+}
+
 /// List all forum categories.
 pub async fn list_categories(client: &NGAClient) -> Result<Vec<CategoryInfo>> {
     let categories = client.forums().list().await?;
@@ -113,17 +208,99 @@ pub async fn list_favorites(client: &NGAClient) -> Result<Vec<ForumInfo>> {
     Ok(forums.iter().map(ForumInfo::from).collect())
 }
 
+/// Build an RSS feed of the first page of a forum.
+pub async fn forum_feed(client: &NGAClient, id: &str, is_stid: bool) -> Result<String> {
+    let forum_id = super::forum_id(id, is_stid)?;
+    let forum_url = format!(
+        "{}thread.php?{}={}",
+        client.base_url(),
+        forum_id.param_name(),
+        forum_id.id()
+    );
+
+    let result = client.topics().list(forum_id).send().await?;
+    Ok(result.to_rss(client.base_url(), &forum_url))
+}
+
+/// Build an OPML subscription list of the favorite forums.
+///
+/// `feed_url` is a template for each forum's RSS URL, with `{param}` replaced
+/// by `fid` or `stid` and `{id}` by the forum ID. Without it, outlines only
+/// link to the forum pages.
+pub async fn export_opml(client: &NGAClient, feed_url: Option<&str>) -> Result<String> {
+    let forums = client.forums().favorites().await?;
+    Ok(rnga::feed::forums_to_opml(
+        "NGA",
+        client.base_url(),
+        &forums,
+        |forum| feed_url.and_then(|template| feed_url_for(forum, template)),
+    ))
+}
+
+/// Expand a feed URL template for a forum with an ID.
+fn feed_url_for(forum: &Forum, template: &str) -> Option<String> {
+    let id = forum.id.as_ref()?;
+    Some(
+        template
+            .replace("{param}", id.param_name())
+            .replace("{id}", id.id()),
+    )
+}
+
+/// Recent topics across several forums, newest activity first.
+///
+/// Each forum is scanned with `recent_topics`, up to `concurrency` at a time.
+/// Those scans fetch their pages one by one, so at most `concurrency`
+/// requests are in flight overall.
+pub async fn recent_multi(
+    client: &NGAClient,
+    forum_ids: &[String],
+    options: RecentTopicsOptions,
+    concurrency: usize,
+) -> Result<Vec<ForumTopicInfo>> {
+    let options = RecentTopicsOptions {
+        concurrency: 1,
+        ..options
+    };
+    let results: Vec<(String, RecentResult)> = stream::iter(forum_ids)
+        .map(|id| {
+            let options = options.clone();
+            async move {
+                let result = recent_topics(client, id, options).await?;
+                Ok::<_, anyhow::Error>((id.clone(), result))
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    Ok(merge_recent(results))
+}
+
+/// Merge per-forum results, tagging each topic with its forum's name (or ID
+/// if the name is unknown) and sorting by last post time, newest first.
+fn merge_recent(results: Vec<(String, RecentResult)>) -> Vec<ForumTopicInfo> {
+    let mut topics: Vec<ForumTopicInfo> = results
+        .into_iter()
+        .flat_map(|(id, result)| {
+            let forum = result.forum_name.unwrap_or(id);
+            result.topics.into_iter().map(move |topic| ForumTopicInfo {
+                forum: forum.clone(),
+                topic,
+            })
+        })
+        .collect();
+    topics.sort_by_key(|t| std::cmp::Reverse(t.topic.last_post_date));
+    topics
+}
+
 /// Add forum to favorites.
 pub async fn add_favorite(
     client: &NGAClient,
     id: &str,
     is_stid: bool,
 ) -> Result<FavoriteModifyResult> {
-    let forum_id = if is_stid {
-        ForumIdKind::stid(id)
-    } else {
-        ForumIdKind::fid(id)
-    };
+    let forum_id = super::forum_id(id, is_stid)?;
 
     client
         .forums()
@@ -142,11 +319,7 @@ pub async fn remove_favorite(
     id: &str,
     is_stid: bool,
 ) -> Result<FavoriteModifyResult> {
-    let forum_id = if is_stid {
-        ForumIdKind::stid(id)
-    } else {
-        ForumIdKind::fid(id)
-    };
+    let forum_id = super::forum_id(id, is_stid)?;
 
     client
         .forums()
@@ -158,3 +331,131 @@ pub async fn remove_favorite(
         action: "removed".to_string(),
     })
 }
+
+/// Block or unblock a subforum in a forum's topic list.
+pub async fn set_subforum_filter(
+    client: &NGAClient,
+    fid: &str,
+    subforum_filter_id: &str,
+    block: bool,
+) -> Result<SubforumFilterResult> {
+    let op = if block {
+        SubforumFilterOp::Block
+    } else {
+        SubforumFilterOp::Show
+    };
+
+    client
+        .forums()
+        .set_subforum_filter(fid, subforum_filter_id, op)
+        .await?;
+
+    Ok(SubforumFilterResult {
+        fid: fid.to_string(),
+        subforum_filter_id: subforum_filter_id.to_string(),
+        action: if block { "blocked" } else { "unblocked" }.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(name: &str, forum_ids: &[&str]) -> CategoryInfo {
+        let forums: Vec<ForumInfo> = forum_ids
+            .iter()
+            .map(|id| ForumInfo {
+                id: id.to_string(),
+                name: format!("Forum {}", id),
+                info: String::new(),
+            })
+            .collect();
+        CategoryInfo {
+            name: name.into(),
+            forum_count: forums.len(),
+            forums,
+        }
+    }
+
+    fn recent(forum_name: Option<&str>, topics: &[(&str, i64)]) -> RecentResult {
+        RecentResult {
+            forum_name: forum_name.map(String::from),
+            range_display: "2 hours".into(),
+            topics: topics
+                .iter()
+                .map(|(id, last_post_date)| TopicInfo {
+                    id: id.to_string(),
+                    subject: format!("Topic {}", id),
+                    tags: Vec::new(),
+                    author: "author".into(),
+                    author_id: "1".into(),
+                    replies: 0,
+                    post_date: 0,
+                    last_post_date: *last_post_date,
+                })
+                .collect(),
+            posts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge_recent() {
+        let merged = merge_recent(vec![
+            (
+                "123".into(),
+                recent(Some("Games"), &[("1", 300), ("2", 100)]),
+            ),
+            ("-7".into(), recent(None, &[("3", 400), ("4", 200)])),
+        ]);
+
+        let order: Vec<(&str, &str)> = merged
+            .iter()
+            .map(|t| (t.forum.as_str(), t.topic.id.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![("-7", "3"), ("Games", "1"), ("-7", "4"), ("Games", "2")]
+        );
+    }
+
+    #[test]
+    fn test_feed_url_for() {
+        let forum = Forum::minimal(ForumIdKind::stid("1234"), "Board");
+        assert_eq!(
+            feed_url_for(&forum, "https://feeds.example.com/{param}/{id}.xml").as_deref(),
+            Some("https://feeds.example.com/stid/1234.xml")
+        );
+        assert_eq!(
+            feed_url_for(&Forum::default(), "https://feeds.example.com/{id}.xml"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tree_lines() {
+        let categories = vec![category("Games", &["7", "310"]), category("Empty", &[])];
+        let lines = tree_lines(&categories);
+
+        let shape: Vec<(usize, String)> = lines
+            .iter()
+            .map(|line| {
+                let label = match line {
+                    TreeLine::Category(c) => c.name.clone(),
+                    TreeLine::Forum(f) => f.id.clone(),
+                    TreeLine::Empty => "-".into(),
+                };
+                (line.depth(), label)
+            })
+            .collect();
+        assert_eq!(
+            shape,
+            vec![
+                (0, "Games".into()),
+                (1, "7".into()),
+                (1, "310".into()),
+                (0, "Empty".into()),
+                (1, "-".into()),
+            ]
+        );
+    }
+}
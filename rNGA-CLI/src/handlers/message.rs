@@ -2,7 +2,7 @@
 
 use anyhow::Result;
 use colored::Colorize;
-use rnga::NGAClient;
+use rnga::{NGAClient, ShortMessage};
 use rust_i18n::t;
 use serde::Serialize;
 
@@ -19,6 +19,19 @@ pub struct MessageInfo {
     pub is_unread: bool,
 }
 
+impl From<&ShortMessage> for MessageInfo {
+    fn from(m: &ShortMessage) -> Self {
+        Self {
+            id: m.id.clone(),
+            other_user: m.other_username.clone(),
+            other_uid: m.other_user_id.to_string(),
+            subject: m.subject.clone(),
+            last_time: m.last_time,
+            is_unread: m.is_unread,
+        }
+    }
+}
+
 impl TableRow for MessageInfo {
     fn headers() -> Vec<&'static str> {
         vec!["ID", "With", "Subject", "Last", "Unread"]
@@ -133,27 +146,33 @@ pub struct ReplyMessageResult {
     pub success: bool,
 }
 
+/// Delete message result.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteMessageResult {
+    pub mid: String,
+    pub success: bool,
+}
+
 /// List message conversations.
 pub async fn list_conversations(client: &NGAClient, page: u32) -> Result<MessageListResult> {
     let result = client.messages().list(page).await?;
     Ok(MessageListResult {
         page,
         total_pages: result.total_pages,
-        conversations: result
-            .conversations
-            .iter()
-            .map(|m| MessageInfo {
-                id: m.id.clone(),
-                other_user: m.other_username.clone(),
-                other_uid: m.other_user_id.to_string(),
-                subject: m.subject.clone(),
-                last_time: m.last_time,
-                is_unread: m.is_unread,
-            })
-            .collect(),
+        conversations: result.conversations.iter().map(MessageInfo::from).collect(),
     })
 }
 
+/// Search conversations by subject or participant, filtering the whole inbox locally.
+pub async fn search_conversations(
+    client: &NGAClient,
+    keyword: &str,
+    concurrency: usize,
+) -> Result<Vec<MessageInfo>> {
+    let conversations = client.messages().search_local(keyword, concurrency).await?;
+    Ok(conversations.iter().map(MessageInfo::from).collect())
+}
+
 /// Read a conversation.
 pub async fn read_conversation(
     client: &NGAClient,
@@ -183,7 +202,7 @@ pub async fn read_conversation(
                 },
                 from_uid: p.from_user_id.to_string(),
                 is_mine: p.is_mine,
-                content: p.content.to_plain_text(),
+                content: p.content.to_plain_text_expanded(),
                 time: p.time,
             })
             .collect(),
@@ -225,3 +244,13 @@ pub async fn reply_message(
         success: true,
     })
 }
+
+/// Delete a conversation.
+pub async fn delete_conversation(client: &NGAClient, mid: &str) -> Result<DeleteMessageResult> {
+    client.messages().delete(mid).await?;
+
+    Ok(DeleteMessageResult {
+        mid: mid.to_string(),
+        success: true,
+    })
+}
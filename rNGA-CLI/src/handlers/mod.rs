@@ -6,3 +6,31 @@ pub mod notification;
 pub mod post;
 pub mod topic;
 pub mod user;
+
+use anyhow::Result;
+use rnga::models::ForumIdKind;
+
+/// Resolve a forum ID argument, which may be `fid:123`, `stid:456` or a bare
+/// number. A bare number is taken as a stid when the `--stid` flag is set.
+pub fn forum_id(id: &str, is_stid: bool) -> Result<ForumIdKind> {
+    if is_stid && !id.contains(':') {
+        return Ok(format!("stid:{}", id).parse()?);
+    }
+    Ok(id.parse()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forum_id() {
+        assert_eq!(forum_id("310", false).unwrap(), ForumIdKind::fid("310"));
+        assert_eq!(forum_id("310", true).unwrap(), ForumIdKind::stid("310"));
+        assert_eq!(
+            forum_id("stid:456", false).unwrap(),
+            ForumIdKind::stid("456")
+        );
+        assert!(forum_id("forum", false).is_err());
+    }
+}
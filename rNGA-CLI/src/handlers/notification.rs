@@ -185,14 +185,19 @@ pub async fn list_notifications(
     client: &NGAClient,
     kind: &str,
     page: u32,
+    unread_only: bool,
+    topic: Option<&str>,
 ) -> Result<NotificationListResult> {
     let noti_type = parse_notification_type(kind);
-    let result = client
+    let mut builder = client
         .notifications()
         .list(noti_type)
         .page(page)
-        .send()
-        .await?;
+        .unread_only(unread_only);
+    if let Some(topic) = topic {
+        builder = builder.topic(topic);
+    }
+    let result = builder.send().await?;
 
     Ok(NotificationListResult {
         kind: format!("{:?}", noti_type),
@@ -25,16 +25,27 @@ pub struct LightPostInfo {
     pub author_id: String,
     pub content: String,
     pub score: i32,
+    pub up: Option<i32>,
+    pub down: Option<i32>,
     pub post_date: i64,
 }
 
+impl LightPostInfo {
+    /// Score as "+up / -down" when vote counts are known.
+    fn vote_text(&self) -> Option<String> {
+        Some(format!("+{} / -{}", self.up?, self.down?))
+    }
+}
+
 impl From<&LightPost> for LightPostInfo {
     fn from(p: &LightPost) -> Self {
         Self {
             author: p.author.name.display().to_string(),
             author_id: p.author.id.to_string(),
-            content: p.content.to_plain_text(),
+            content: p.content.to_plain_text_expanded(),
             score: p.score,
+            up: p.vote.map(|v| v.up),
+            down: p.vote.map(|v| v.down),
             post_date: p.post_date,
         }
     }
@@ -48,7 +59,7 @@ impl TableRow for LightPostInfo {
         vec![
             self.author.clone(),
             self.content.clone(),
-            self.score.to_string(),
+            self.vote_text().unwrap_or_else(|| self.score.to_string()),
             format_relative_time(self.post_date),
         ]
     }
@@ -61,7 +72,9 @@ impl PlainPrint for LightPostInfo {
             self.author.green(),
             t!("uid_label", id = &self.author_id).to_string().dimmed(),
             format_relative_time(self.post_date).dimmed(),
-            if self.score != 0 {
+            if let Some(votes) = self.vote_text() {
+                format!(" ({})", votes).yellow().to_string()
+            } else if self.score != 0 {
                 format!(" (+{})", self.score).yellow().to_string()
             } else {
                 String::new()
@@ -3,14 +3,18 @@
 use anyhow::Result;
 use colored::Colorize;
 use futures::stream::{self, StreamExt};
+use indicatif::{ProgressBar, ProgressStyle};
 use rnga::models::*;
+use rnga::parser::bbcode::DEFAULT_MAX_DEPTH;
 use rnga::NGAClient;
 use rust_i18n::t;
 use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Semaphore;
 
-use crate::output::{format_relative_time, PlainPrint, TableRow};
+use crate::output::{format_relative_time, format_time, AnsiRender, PlainPrint, TableRow};
 
 /// Topic information.
 #[derive(Debug, Clone, Serialize)]
@@ -81,27 +85,69 @@ pub struct PostInfo {
     pub author: String,
     pub author_id: String,
     pub content: String,
+    #[serde(skip)]
+    pub rich_content: String,
+    #[serde(skip)]
+    pub markdown: String,
+    /// Preview of the content shown in table cells.
+    #[serde(skip)]
+    pub summary: String,
     pub score: i32,
     pub post_date: i64,
     pub comment_count: i32,
+    pub device: Option<String>,
+    pub quoted_post_ids: Vec<String>,
+    /// Whether the topic's original poster wrote this post.
+    pub is_op: bool,
+    /// Absolute URLs of the post's images.
+    #[serde(skip)]
+    pub image_urls: Vec<String>,
 }
 
-impl From<&Post> for PostInfo {
-    fn from(p: &Post) -> Self {
+impl PostInfo {
+    /// Convert a post, marking it if the author of `topic` wrote it.
+    pub fn new(p: &Post, topic: &Topic) -> Self {
         Self {
             floor: p.floor,
             post_id: p.id.to_string(),
             topic_id: p.topic_id.to_string(),
-            author: p.author.name.display().to_string(),
+            author: p
+                .author
+                .anon_label()
+                .unwrap_or_else(|| p.author.name.display().to_string()),
             author_id: p.author.id.to_string(),
-            content: p.content.to_plain_text(),
+            content: p.content.to_plain_text_expanded(),
+            rich_content: p.content.to_ansi(),
+            markdown: p.content.to_markdown(),
+            summary: p.content.summary(TABLE_SUMMARY_CHARS),
             score: p.score,
             post_date: p.post_date,
             comment_count: p.comment_count,
+            device: p.device_label().map(str::to_string),
+            quoted_post_ids: p
+                .content
+                .quoted_post_ids()
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            is_op: p.is_op(topic),
+            image_urls: p.all_image_urls(),
+        }
+    }
+
+    /// Author name, followed by a badge on posts by the original poster.
+    fn author_label(&self) -> String {
+        if self.is_op {
+            format!("{} [{}]", self.author, t!("op_badge"))
+        } else {
+            self.author.clone()
         }
     }
 }
 
+/// Maximum length of post content in table cells.
+const TABLE_SUMMARY_CHARS: usize = 200;
+
 impl TableRow for PostInfo {
     fn headers() -> Vec<&'static str> {
         vec!["#", "Author", "Content", "Score", "Time"]
@@ -109,8 +155,8 @@ impl TableRow for PostInfo {
     fn row(&self) -> Vec<String> {
         vec![
             self.floor.to_string(),
-            self.author.clone(),
-            self.content.clone(),
+            self.author_label(),
+            self.summary.clone(),
             self.score.to_string(),
             format_relative_time(self.post_date),
         ]
@@ -119,10 +165,25 @@ impl TableRow for PostInfo {
 
 impl PlainPrint for PostInfo {
     fn plain_print(&self) {
+        self.plain_print_indented(0);
+    }
+}
+
+impl PostInfo {
+    /// Print as plain text, indented by `depth` levels.
+    pub fn plain_print_indented(&self, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let device = match &self.device {
+            Some(device) => format!(" {}", t!("device_label", device = device))
+                .dimmed()
+                .to_string(),
+            None => String::new(),
+        };
         println!(
-            "{} {} {} {}{}",
+            "{}{} {} {} {}{}{}",
+            indent,
             format!("#{}", self.floor).yellow(),
-            self.author.green(),
+            self.author_label().green(),
             t!("uid_label", id = &self.author_id).to_string().dimmed(),
             format_relative_time(self.post_date).dimmed(),
             if self.score != 0 {
@@ -131,17 +192,96 @@ impl PlainPrint for PostInfo {
                     .to_string()
             } else {
                 String::new()
-            }
+            },
+            device
         );
-        for line in self.content.lines() {
+        for line in self.rich_content.lines() {
             if !line.trim().is_empty() {
-                println!("     {}", line);
+                println!("{}     {}", indent, line);
             }
         }
         println!();
     }
 }
 
+/// A post with the replies that quote it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PostNode {
+    pub post: PostInfo,
+    pub replies: Vec<PostNode>,
+}
+
+impl PostNode {
+    /// Print this node and its replies as an indented tree.
+    pub fn plain_print_tree(&self, depth: usize) {
+        self.post.plain_print_indented(depth);
+        for reply in &self.replies {
+            reply.plain_print_tree(depth + 1);
+        }
+    }
+}
+
+/// Nest posts under the first earlier post they quote.
+///
+/// Posts that quote nothing, or only posts outside the list, stay at the top level.
+/// The tree is at most `DEFAULT_MAX_DEPTH` levels deep; replies below that are
+/// added as siblings of the post they quote.
+pub fn build_post_tree(posts: Vec<PostInfo>) -> Vec<PostNode> {
+    let index: HashMap<&str, usize> = posts
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.post_id.as_str(), i))
+        .collect();
+
+    let mut parents: Vec<Option<usize>> = posts
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            p.quoted_post_ids
+                .iter()
+                .filter_map(|id| index.get(id.as_str()).copied())
+                .find(|&parent| parent < i)
+        })
+        .collect();
+
+    // Parents come before their replies, so their depth is already final.
+    let mut depths = vec![0; posts.len()];
+    for i in 0..posts.len() {
+        if let Some(parent) = parents[i] {
+            if depths[parent] + 1 >= DEFAULT_MAX_DEPTH {
+                parents[i] = parents[parent];
+            }
+            depths[i] = parents[i].map_or(0, |p| depths[p] + 1);
+        }
+    }
+
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); posts.len()];
+    let mut roots = Vec::new();
+    for (i, parent) in parents.iter().enumerate() {
+        match parent {
+            Some(parent) => children[*parent].push(i),
+            None => roots.push(i),
+        }
+    }
+
+    let mut slots: Vec<Option<PostInfo>> = posts.into_iter().map(Some).collect();
+    roots
+        .into_iter()
+        .map(|i| assemble_node(i, &children, &mut slots))
+        .collect()
+}
+
+fn assemble_node(i: usize, children: &[Vec<usize>], slots: &mut [Option<PostInfo>]) -> PostNode {
+    let replies = children[i]
+        .iter()
+        .map(|&c| assemble_node(c, children, slots))
+        .collect();
+    PostNode {
+        post: slots[i].take().expect("each post has a single parent"),
+        replies,
+    }
+}
+
 /// Topic list result.
 #[derive(Debug, Clone, Serialize)]
 pub struct TopicListResult {
@@ -167,6 +307,34 @@ pub struct TopicDetailsResult {
     pub posts: Vec<PostInfo>,
 }
 
+impl TopicDetailsResult {
+    /// Render the fetched posts as one Markdown document, with a header
+    /// per floor naming the author and post time.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# {}\n\n{} · {} · {} · {}/{}\n",
+            self.subject,
+            self.forum_name,
+            self.author,
+            format_time(self.post_date),
+            self.page,
+            self.total_pages
+        );
+        for post in &self.posts {
+            let op = if post.is_op { " (OP)" } else { "" };
+            out.push_str(&format!(
+                "\n## #{} {}{} · {}\n\n{}\n",
+                post.floor,
+                post.author,
+                op,
+                format_time(post.post_date),
+                post.markdown.trim()
+            ));
+        }
+        out
+    }
+}
+
 /// Topic search result.
 #[derive(Debug, Clone, Serialize)]
 pub struct TopicSearchResult {
@@ -273,6 +441,8 @@ pub struct ReadTopicOptions {
     pub fetch_all: bool,
     pub concurrency: usize,
     pub range: Option<String>,
+    /// Show a progress bar while fetching pages.
+    pub progress: bool,
 }
 
 /// Options for searching topics.
@@ -291,6 +461,7 @@ pub struct RecentTopicsOptions {
     pub order: String,
     pub with_posts: bool,
     pub concurrency: usize,
+    pub include_topped: bool,
 }
 
 fn parse_order(order: &str) -> TopicOrder {
@@ -363,11 +534,7 @@ pub async fn list_topics(
     forum_id: &str,
     options: ListTopicsOptions,
 ) -> Result<TopicListResult> {
-    let id = if options.is_stid {
-        ForumIdKind::stid(forum_id)
-    } else {
-        ForumIdKind::fid(forum_id)
-    };
+    let id = super::forum_id(forum_id, options.is_stid)?;
 
     let order_by = parse_order(&options.order);
     let start_page = options.start_page.max(1);
@@ -478,6 +645,7 @@ pub async fn read_topic(
     let replies = topic.replies;
     let post_date = topic.post_date;
     let total_pages = first_result.total_pages;
+    let op = topic.clone();
 
     if !options.fetch_all && total_pages == 1 {
         let posts: Vec<PostInfo> = if let Some(cutoff) = cutoff_time {
@@ -485,10 +653,14 @@ pub async fn read_topic(
                 .posts
                 .iter()
                 .filter(|p| p.post_date >= cutoff)
-                .map(PostInfo::from)
+                .map(|p| PostInfo::new(p, &op))
                 .collect()
         } else {
-            first_result.posts.iter().map(PostInfo::from).collect()
+            first_result
+                .posts
+                .iter()
+                .map(|p| PostInfo::new(p, &op))
+                .collect()
         };
 
         return Ok(TopicDetailsResult {
@@ -528,14 +700,17 @@ pub async fn read_topic(
             post_date,
             page,
             total_pages,
-            posts: page_result.posts.iter().map(PostInfo::from).collect(),
+            posts: page_result
+                .posts
+                .iter()
+                .map(|p| PostInfo::new(p, &op))
+                .collect(),
         });
     }
 
-    let mut all_posts: Vec<Post>;
+    let mut pages: Vec<Vec<Post>> = Vec::new();
 
-    if cutoff_time.is_some() {
-        all_posts = Vec::new();
+    if let Some(cutoff) = cutoff_time {
         let mut current_page = total_pages;
 
         loop {
@@ -549,13 +724,13 @@ pub async fn read_topic(
                 builder.send().await?
             };
 
-            let mut found_any_recent = false;
-            for post in details.posts {
-                if post.post_date >= cutoff_time.unwrap() {
-                    found_any_recent = true;
-                    all_posts.push(post);
-                }
-            }
+            let recent: Vec<Post> = details
+                .posts
+                .into_iter()
+                .filter(|post| post.post_date >= cutoff)
+                .collect();
+            let found_any_recent = !recent.is_empty();
+            pages.push(recent);
 
             if !found_any_recent || current_page == 1 {
                 break;
@@ -571,6 +746,15 @@ pub async fn read_topic(
 
         let pages_to_fetch: Vec<u32> = (2..=total_pages).collect();
 
+        let bar = options
+            .progress
+            .then(|| page_progress_bar(pages_to_fetch.len() as u64));
+        let mut progress = FetchProgress::new(pages_to_fetch.len() as u64, |done, _| {
+            if let Some(bar) = &bar {
+                bar.set_position(done);
+            }
+        });
+
         let fetch_results: Vec<_> = stream::iter(pages_to_fetch)
             .map(|p| {
                 let sem = semaphore.clone();
@@ -587,22 +771,27 @@ pub async fn read_topic(
                 }
             })
             .buffer_unordered(concurrency)
+            .inspect(|_| progress.complete())
             .collect()
             .await;
 
-        all_posts = first_result.posts;
+        if let Some(bar) = bar {
+            bar.finish_and_clear();
+        }
+
+        pages.push(first_result.posts);
 
         let mut sorted_results: Vec<_> = fetch_results.into_iter().collect();
         sorted_results.sort_by_key(|(p, _)| *p);
 
         for (_page_num, result) in sorted_results {
             if let Ok(page_result) = result {
-                all_posts.extend(page_result.posts);
+                pages.push(page_result.posts);
             }
         }
     }
 
-    all_posts.sort_by_key(|p| p.floor);
+    let all_posts = merge_pages(pages);
 
     Ok(TopicDetailsResult {
         forum_name,
@@ -614,10 +803,176 @@ pub async fn read_topic(
         post_date,
         page: 1,
         total_pages,
-        posts: all_posts.iter().map(PostInfo::from).collect(),
+        posts: all_posts.iter().map(|p| PostInfo::new(p, &op)).collect(),
     })
 }
 
+/// Image URLs of one post.
+#[derive(Debug, Clone, Serialize)]
+pub struct PostImages {
+    pub floor: i32,
+    pub post_id: String,
+    pub urls: Vec<String>,
+}
+
+impl TableRow for PostImages {
+    fn headers() -> Vec<&'static str> {
+        vec!["#", "Images"]
+    }
+    fn row(&self) -> Vec<String> {
+        vec![self.floor.to_string(), self.urls.join("\n")]
+    }
+}
+
+impl PlainPrint for PostImages {
+    fn plain_print(&self) {
+        println!("{}", format!("#{}", self.floor).yellow());
+        for url in &self.urls {
+            println!("  {}", url);
+        }
+    }
+}
+
+/// Image URLs grouped by post, skipping posts without images.
+pub fn post_images(posts: &[PostInfo]) -> Vec<PostImages> {
+    posts
+        .iter()
+        .filter(|p| !p.image_urls.is_empty())
+        .map(|p| PostImages {
+            floor: p.floor,
+            post_id: p.post_id.clone(),
+            urls: p.image_urls.clone(),
+        })
+        .collect()
+}
+
+/// File name for the `index`-th (from 1) image of the post at `floor`, as
+/// `{floor}-{index}.{ext}`.
+///
+/// The extension comes from the URL, then the content type, then `jpg`.
+pub fn image_file_name(floor: i32, index: usize, url: &str, content_type: Option<&str>) -> String {
+    let from_url = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|path| path.rsplit('/').next())
+        .and_then(|file| file.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_lowercase())
+        .filter(|ext| {
+            (1..=5).contains(&ext.len()) && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+    let from_type = || {
+        content_type
+            .and_then(|ct| ct.strip_prefix("image/"))
+            .map(|subtype| match subtype {
+                "jpeg" => "jpg".to_string(),
+                "svg+xml" => "svg".to_string(),
+                other => other.to_string(),
+            })
+    };
+    let ext = from_url
+        .or_else(from_type)
+        .unwrap_or_else(|| "jpg".to_string());
+    format!("{}-{}.{}", floor, index, ext)
+}
+
+/// Outcome of downloading a topic's images.
+#[derive(Debug, Default)]
+pub struct ImageDownloads {
+    pub saved: Vec<PathBuf>,
+    /// URLs that failed, with the error.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Download every image into `dir`, up to `concurrency` at a time.
+///
+/// A failed image is recorded and doesn't stop the others.
+pub async fn download_images(
+    client: &NGAClient,
+    images: &[PostImages],
+    dir: &Path,
+    concurrency: usize,
+) -> Result<ImageDownloads> {
+    std::fs::create_dir_all(dir)?;
+
+    let jobs = images.iter().flat_map(|post| {
+        post.urls
+            .iter()
+            .enumerate()
+            .map(move |(i, url)| (post.floor, i + 1, url))
+    });
+    let results: Vec<(&String, Result<PathBuf>)> = stream::iter(jobs)
+        .map(|(floor, index, url)| async move {
+            let result = async {
+                let image = client.posts().fetch_image(url).await?;
+                let name = image_file_name(floor, index, url, image.content_type.as_deref());
+                let path = dir.join(name);
+                std::fs::write(&path, &image.bytes)?;
+                Ok(path)
+            }
+            .await;
+            (url, result)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut downloads = ImageDownloads::default();
+    for (url, result) in results {
+        match result {
+            Ok(path) => downloads.saved.push(path),
+            Err(e) => downloads.failed.push((url.clone(), e.to_string())),
+        }
+    }
+    downloads.saved.sort();
+    Ok(downloads)
+}
+
+/// Merge posts from several pages in floor order.
+///
+/// Pages can overlap at their boundaries, notably in author-filtered views, so
+/// a post seen on an earlier page is dropped when it shows up again.
+fn merge_pages(pages: Vec<Vec<Post>>) -> Vec<Post> {
+    let mut seen = HashSet::new();
+    let mut posts: Vec<Post> = pages
+        .into_iter()
+        .flatten()
+        .filter(|p| seen.insert(p.id.clone()))
+        .collect();
+    posts.sort_by_key(|p| p.floor);
+    posts
+}
+
+/// Counts completed page fetches and reports each step to a hook.
+struct FetchProgress<F: FnMut(u64, u64)> {
+    done: u64,
+    total: u64,
+    on_update: F,
+}
+
+impl<F: FnMut(u64, u64)> FetchProgress<F> {
+    fn new(total: u64, on_update: F) -> Self {
+        Self {
+            done: 0,
+            total,
+            on_update,
+        }
+    }
+
+    /// Record one completed fetch.
+    fn complete(&mut self) {
+        self.done = (self.done + 1).min(self.total);
+        (self.on_update)(self.done, self.total);
+    }
+}
+
+fn page_progress_bar(total: u64) -> ProgressBar {
+    let bar = ProgressBar::new(total);
+    if let Ok(style) = ProgressStyle::with_template("{bar:40.cyan/blue} {pos}/{len} pages") {
+        bar.set_style(style);
+    }
+    bar
+}
+
 /// Search topics in a forum.
 pub async fn search_topics(
     client: &NGAClient,
@@ -625,11 +980,7 @@ pub async fn search_topics(
     keyword: &str,
     options: SearchTopicsOptions,
 ) -> Result<TopicSearchResult> {
-    let id = if options.is_stid {
-        ForumIdKind::stid(forum_id)
-    } else {
-        ForumIdKind::fid(forum_id)
-    };
+    let id = super::forum_id(forum_id, options.is_stid)?;
 
     let result = client
         .topics()
@@ -725,11 +1076,7 @@ pub async fn recent_topics(
 
     let cutoff_time = now - time_range_seconds;
 
-    let id = if options.is_stid {
-        ForumIdKind::stid(forum_id)
-    } else {
-        ForumIdKind::fid(forum_id)
-    };
+    let id = super::forum_id(forum_id, options.is_stid)?;
 
     let order_by = parse_order(&options.order);
 
@@ -750,27 +1097,11 @@ pub async fn recent_topics(
             forum_name = result.forum.as_ref().map(|f| f.name.clone());
         }
 
-        let mut found_any_recent = false;
-        let mut all_older_than_cutoff = true;
-
-        for topic in result.topics {
-            let relevant_time = match order_by {
-                TopicOrder::PostDate => topic.post_date,
-                _ => topic.last_post_date,
-            };
-
-            if relevant_time >= cutoff_time {
-                all_recent_topics.push(topic);
-                found_any_recent = true;
-                all_older_than_cutoff = false;
-            }
-        }
-
-        if all_older_than_cutoff || current_page >= result.total_pages {
-            break;
-        }
+        let (recent, found_any_recent) =
+            select_recent_topics(result.topics, cutoff_time, order_by, options.include_topped);
+        all_recent_topics.extend(recent);
 
-        if !found_any_recent {
+        if !found_any_recent || current_page >= result.total_pages {
             break;
         }
 
@@ -835,7 +1166,7 @@ pub async fn recent_topics(
         }
     }
 
-    all_posts.sort_by(|a, b| b.post_date.cmp(&a.post_date));
+    all_posts.sort_by_key(|p| std::cmp::Reverse(p.post_date));
 
     Ok(RecentResult {
         forum_name,
@@ -845,6 +1176,41 @@ pub async fn recent_topics(
     })
 }
 
+/// Select topics whose activity falls after `cutoff_time`.
+///
+/// Also returns whether any non-topped topic was recent, which decides whether
+/// the next page is worth fetching. Topped topics stay at the top of every page
+/// regardless of activity, so they never keep the paging loop alive.
+fn select_recent_topics(
+    topics: Vec<Topic>,
+    cutoff_time: i64,
+    order_by: TopicOrder,
+    include_topped: bool,
+) -> (Vec<Topic>, bool) {
+    let mut recent = Vec::new();
+    let mut found_any_recent = false;
+
+    for topic in topics {
+        if topic.is_topped && !include_topped {
+            continue;
+        }
+
+        let relevant_time = match order_by {
+            TopicOrder::PostDate => topic.post_date,
+            _ => topic.last_post_date,
+        };
+
+        if relevant_time >= cutoff_time {
+            if !topic.is_topped {
+                found_any_recent = true;
+            }
+            recent.push(topic);
+        }
+    }
+
+    (recent, found_any_recent)
+}
+
 async fn fetch_topic_posts(
     client: &NGAClient,
     topic: &Topic,
@@ -890,9 +1256,11 @@ async fn fetch_topic_posts(
                     "post".to_string(),
                     post.id.to_string(),
                     format!("#{}", post.floor),
-                    post.author.name.display().to_string(),
+                    post.author
+                        .anon_label()
+                        .unwrap_or_else(|| post.author.name.display().to_string()),
                     post.author.id.to_string(),
-                    post.content.to_plain_text(),
+                    post.content.to_plain_text_expanded(),
                     post.post_date,
                     post.score,
                 ));
@@ -934,7 +1302,7 @@ async fn fetch_topic_posts(
                             format!("#{} comment", post.floor),
                             comment.author.name.display().to_string(),
                             comment.author.id.to_string(),
-                            comment.content.to_plain_text(),
+                            comment.content.to_plain_text_expanded(),
                             comment.post_date,
                             comment.score,
                         ));
@@ -952,3 +1320,230 @@ async fn fetch_topic_posts(
 
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topic(id: &str, last_post_date: i64, is_topped: bool) -> Topic {
+        Topic {
+            last_post_date,
+            is_topped,
+            ..Topic::with_id(id)
+        }
+    }
+
+    fn raw_post(id: &str, floor: i32) -> Post {
+        Post {
+            id: PostId::new(id),
+            floor,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_post_images() {
+        let mut first = post(1, &[]);
+        first.image_urls = vec!["https://img/a.jpg".into(), "https://img/b.png".into()];
+        let mut third = post(3, &[]);
+        third.image_urls = vec!["https://img/c".into()];
+
+        let images = post_images(&[post(0, &[]), first, post(2, &[]), third]);
+        let grouped: Vec<(i32, &str, usize)> = images
+            .iter()
+            .map(|p| (p.floor, p.post_id.as_str(), p.urls.len()))
+            .collect();
+        assert_eq!(grouped, vec![(1, "p1", 2), (3, "p3", 1)]);
+    }
+
+    #[test]
+    fn test_image_file_name() {
+        let name = |url, content_type| image_file_name(7, 2, url, content_type);
+        assert_eq!(name("https://img/mon_202401/a.PNG", None), "7-2.png");
+        assert_eq!(name("https://img/a.webp?x=1.5#frag", None), "7-2.webp");
+        assert_eq!(name("https://cdn/img?id=1", Some("image/jpeg")), "7-2.jpg");
+        assert_eq!(name("https://cdn/img", Some("image/gif")), "7-2.gif");
+        assert_eq!(name("https://cdn/img", None), "7-2.jpg");
+    }
+
+    #[test]
+    fn test_merge_pages_dedupes_overlap() {
+        let pages = vec![
+            vec![raw_post("10", 0), raw_post("11", 1), raw_post("12", 2)],
+            vec![raw_post("15", 5), raw_post("12", 2), raw_post("13", 3)],
+            vec![raw_post("13", 3), raw_post("16", 6)],
+        ];
+
+        let floors: Vec<(String, i32)> = merge_pages(pages)
+            .into_iter()
+            .map(|p| (p.id.to_string(), p.floor))
+            .collect();
+        assert_eq!(
+            floors,
+            vec![
+                ("10".to_string(), 0),
+                ("11".to_string(), 1),
+                ("12".to_string(), 2),
+                ("13".to_string(), 3),
+                ("15".to_string(), 5),
+                ("16".to_string(), 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_recent_topics_skips_topped() {
+        let topics = vec![
+            topic("1", 2000, true),
+            topic("2", 1500, false),
+            topic("3", 500, false),
+        ];
+
+        let (recent, found) = select_recent_topics(topics, 1000, TopicOrder::LastPost, false);
+        let ids: Vec<_> = recent.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["2"]);
+        assert!(found);
+    }
+
+    #[test]
+    fn test_select_recent_topics_include_topped() {
+        let topics = vec![topic("1", 2000, true), topic("2", 1500, false)];
+
+        let (recent, _) = select_recent_topics(topics, 1000, TopicOrder::LastPost, true);
+        assert_eq!(recent.len(), 2);
+    }
+
+    #[test]
+    fn test_select_recent_topics_topped_does_not_continue_paging() {
+        let topics = vec![topic("1", 2000, true), topic("2", 500, false)];
+
+        let (recent, found) = select_recent_topics(topics, 1000, TopicOrder::LastPost, true);
+        assert_eq!(recent.len(), 1);
+        assert!(!found);
+    }
+
+    fn post(floor: i32, quotes: &[&str]) -> PostInfo {
+        PostInfo {
+            floor,
+            post_id: format!("p{}", floor),
+            topic_id: "1".into(),
+            author: String::new(),
+            author_id: String::new(),
+            content: String::new(),
+            rich_content: String::new(),
+            markdown: String::new(),
+            summary: String::new(),
+            score: 0,
+            post_date: 0,
+            comment_count: 0,
+            device: None,
+            quoted_post_ids: quotes.iter().map(|q| q.to_string()).collect(),
+            is_op: false,
+            image_urls: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_topic_markdown() {
+        let mut op = post(0, &[]);
+        op.author = "alice".into();
+        op.is_op = true;
+        op.markdown = "**Hello**\n".into();
+        let mut reply = post(1, &[]);
+        reply.author = "bob".into();
+        reply.markdown = "> Hello\n\nHi".into();
+
+        let result = TopicDetailsResult {
+            forum_name: "Forum".into(),
+            subject: "Greetings".into(),
+            tags: Vec::new(),
+            author: "alice".into(),
+            author_id: "1".into(),
+            replies: 1,
+            post_date: 0,
+            page: 1,
+            total_pages: 1,
+            posts: vec![op, reply],
+        };
+        assert_eq!(
+            result.to_markdown(),
+            "# Greetings\n\nForum · alice · - · 1/1\n\
+\n## #0 alice (OP) · -\n\n**Hello**\n\
+\n## #1 bob · -\n\n> Hello\n\nHi\n"
+        );
+    }
+
+    fn shape(nodes: &[PostNode]) -> Vec<(i32, Vec<(i32, usize)>)> {
+        nodes
+            .iter()
+            .map(|n| {
+                let replies = n
+                    .replies
+                    .iter()
+                    .map(|r| (r.post.floor, r.replies.len()))
+                    .collect();
+                (n.post.floor, replies)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build_post_tree() {
+        let posts = vec![
+            post(0, &[]),
+            post(1, &["p0"]),
+            post(2, &["p1"]),
+            post(3, &["missing"]),
+            post(4, &["missing", "p0"]),
+            post(5, &["p6"]),
+            post(6, &[]),
+        ];
+
+        let tree = build_post_tree(posts);
+        assert_eq!(
+            shape(&tree),
+            vec![
+                (0, vec![(1, 1), (4, 0)]),
+                (3, vec![]),
+                (5, vec![]),
+                (6, vec![]),
+            ]
+        );
+        assert_eq!(tree[0].replies[0].replies[0].post.floor, 2);
+    }
+
+    #[test]
+    fn test_build_post_tree_depth_limit() {
+        let ids: Vec<String> = (0..100).map(|i| format!("p{}", i)).collect();
+        let posts = (0..100)
+            .map(|i| match i {
+                0 => post(0, &[]),
+                _ => post(i, &[ids[i as usize - 1].as_str()]),
+            })
+            .collect();
+
+        fn depth(node: &PostNode) -> usize {
+            1 + node.replies.iter().map(depth).max().unwrap_or(0)
+        }
+
+        let tree = build_post_tree(posts);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(depth(&tree[0]), DEFAULT_MAX_DEPTH);
+
+        fn count(node: &PostNode) -> usize {
+            1 + node.replies.iter().map(count).sum::<usize>()
+        }
+        assert_eq!(count(&tree[0]), 100);
+    }
+
+    #[test]
+    fn test_fetch_progress() {
+        let mut updates = Vec::new();
+        let mut progress = FetchProgress::new(2, |done, total| updates.push((done, total)));
+        progress.complete();
+        progress.complete();
+        progress.complete();
+
+        assert_eq!(updates, vec![(1, 2), (2, 2), (2, 2)]);
+    }
+}
@@ -85,6 +85,15 @@ impl PlainPrint for UserSearchInfo {
     }
 }
 
+/// A page of user search results.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserSearchPageResult {
+    pub keyword: String,
+    pub page: u32,
+    pub total_pages: u32,
+    pub users: Vec<UserSearchInfo>,
+}
+
 /// User's topics result.
 #[derive(Debug, Clone, Serialize)]
 pub struct UserTopicsResult {
@@ -197,15 +206,30 @@ pub async fn get_me(client: &NGAClient) -> Result<UserInfo> {
 }
 
 /// Search users by keyword.
-pub async fn search_users(client: &NGAClient, keyword: &str) -> Result<Vec<UserSearchInfo>> {
-    let results = client.users().search(keyword).await?;
-    Ok(results
-        .iter()
-        .map(|u| UserSearchInfo {
-            id: u.id.to_string(),
-            name: u.name.clone(),
-        })
-        .collect())
+pub async fn search_users(
+    client: &NGAClient,
+    keyword: &str,
+    page: u32,
+) -> Result<UserSearchPageResult> {
+    let result = client
+        .users()
+        .search_paged(keyword)
+        .page(page)
+        .send()
+        .await?;
+    Ok(UserSearchPageResult {
+        keyword: keyword.to_string(),
+        page: result.page,
+        total_pages: result.total_pages,
+        users: result
+            .users
+            .iter()
+            .map(|u| UserSearchInfo {
+                id: u.id.to_string(),
+                name: u.name.clone(),
+            })
+            .collect(),
+    })
 }
 
 /// Get topics posted by a user.
@@ -229,9 +253,18 @@ pub async fn user_topics(client: &NGAClient, user_id: &str, page: u32) -> Result
     })
 }
 
-/// Get posts by a user.
-pub async fn user_posts(client: &NGAClient, user_id: &str, page: u32) -> Result<UserPostsResult> {
-    let result = client.posts().by_user(user_id, page).await?;
+/// Get posts by a user, optionally limited to one forum.
+pub async fn user_posts(
+    client: &NGAClient,
+    user_id: &str,
+    page: u32,
+    forum: Option<&str>,
+) -> Result<UserPostsResult> {
+    let mut builder = client.posts().by_user(user_id).page(page);
+    if let Some(fid) = forum {
+        builder = builder.forum(fid);
+    }
+    let result = builder.send().await?;
     Ok(UserPostsResult {
         user_id: user_id.to_string(),
         page,
@@ -5,10 +5,11 @@ mod config;
 mod handlers;
 mod mcp;
 mod output;
+mod pager;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use commands::{forum, message, notification, post, topic, user};
+use commands::{cache, debug, forum, message, notification, post, topic, user};
 use rust_i18n::t;
 
 rust_i18n::i18n!("src/locales", fallback = "en");
@@ -19,17 +20,49 @@ rust_i18n::i18n!("src/locales", fallback = "en");
 #[command(version, about, long_about = None)]
 #[command(propagate_version = true)]
 struct Cli {
-    /// Output format
-    #[arg(short, long, global = true, default_value = "plain")]
-    format: output::OutputFormat,
+    /// Output format [default: plain, or `format` from config]
+    #[arg(short, long, global = true)]
+    format: Option<output::OutputFormat>,
 
     /// Show verbose output
     #[arg(short, long, global = true)]
     verbose: bool,
 
-    /// Language for output (en, zh-CN)
-    #[arg(short, long, global = true, default_value = "en")]
-    lang: String,
+    /// Language for output (en, zh-CN) [default: en, or `lang` from config]
+    #[arg(short, long, global = true)]
+    lang: Option<String>,
+
+    /// Disable colored output
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Account profile to use [default: the active profile]
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Device whose User-Agent is sent (apple, android, desktop, windows-phone)
+    #[arg(long, global = true)]
+    device: Option<rnga::Device>,
+
+    /// Serve reads from the cache only, never touching the network
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Print raw response bodies to stderr before parsing
+    #[arg(long, global = true)]
+    raw: bool,
+
+    /// Only show these table columns, by header (e.g. id,subject)
+    #[arg(long, global = true, value_delimiter = ',')]
+    columns: Vec<String>,
+
+    /// IANA timezone for timestamps (e.g. Asia/Shanghai) [default: local]
+    #[arg(long, global = true)]
+    timezone: Option<String>,
+
+    /// strftime format for timestamps [default: %Y-%m-%d %H:%M]
+    #[arg(long, global = true)]
+    time_format: Option<String>,
 
     #[command(subcommand)]
     command: Option<Commands>,
@@ -89,13 +122,44 @@ enum Commands {
         action: message::MessageAction,
     },
 
-    /// Show current configuration
-    Config,
+    /// Manage the response cache
+    Cache {
+        #[command(subcommand)]
+        action: cache::CacheAction,
+    },
+
+    /// Debugging helpers
+    Debug {
+        #[command(subcommand)]
+        action: debug::DebugAction,
+    },
+
+    /// Show or change configuration
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Set a default (default_forum, concurrency, format, lang, timezone, time_format, cache_dir, offline)
+    Set {
+        /// Config key
+        key: String,
+        /// Value to store
+        value: String,
+    },
+    /// Print a default
+    Get {
+        /// Config key
+        key: String,
+    },
 }
 
 #[derive(Subcommand)]
 enum AuthAction {
-    /// Login with token and uid
+    /// Login with token and uid (stored under `--profile` if given)
     Login {
         /// Access token
         #[arg(short, long)]
@@ -108,13 +172,36 @@ enum AuthAction {
     Logout,
     /// Show current auth status
     Status,
+    /// Switch the active profile
+    Use {
+        /// Profile name
+        profile: String,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let cfg = config::load_config_or_default();
+    let format = cfg.format_or(cli.format);
+
+    rust_i18n::set_locale(&cfg.lang_or(cli.lang));
 
-    rust_i18n::set_locale(&cli.lang);
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
+
+    config::set_raw_output(cli.raw);
+    config::set_offline(cli.offline);
+    if let Some(device) = cli.device {
+        config::set_device(device);
+    }
+    output::set_table_columns(cli.columns);
+    output::set_time_style(cfg.time_style_or(cli.timezone, cli.time_format)?);
+
+    if let Some(profile) = cli.profile {
+        config::set_profile_override(profile);
+    }
 
     if cli.mcp {
         tracing_subscriber::fmt()
@@ -134,56 +221,95 @@ async fn main() -> Result<()> {
 
     match command {
         Commands::Auth { action } => handle_auth(action).await,
-        Commands::Forum { action } => forum::handle(action, cli.format, cli.verbose).await,
-        Commands::Topic { action } => topic::handle(action, cli.format, cli.verbose).await,
-        Commands::Post { action } => post::handle(action, cli.format, cli.verbose).await,
-        Commands::User { action } => user::handle(action, cli.format, cli.verbose).await,
+        Commands::Forum { action } => forum::handle(action, format, cli.verbose).await,
+        Commands::Topic { action } => topic::handle(action, format, cli.verbose).await,
+        Commands::Post { action } => post::handle(action, format, cli.verbose).await,
+        Commands::User { action } => user::handle(action, format, cli.verbose).await,
         Commands::Notification { action } => {
-            notification::handle(action, cli.format, cli.verbose).await
+            notification::handle(action, format, cli.verbose).await
         }
-        Commands::Message { action } => message::handle(action, cli.format, cli.verbose).await,
-        Commands::Config => {
+        Commands::Message { action } => message::handle(action, format, cli.verbose).await,
+        Commands::Cache { action } => cache::handle(action).await,
+        Commands::Debug { action } => debug::handle(action).await,
+        Commands::Config { action } => handle_config(action).await,
+    }
+}
+
+async fn handle_config(action: Option<ConfigAction>) -> Result<()> {
+    match action {
+        None => {
             let cfg = config::load_config()?;
             println!(
                 "{}",
                 t!("config_file", path = config::config_path()?.display())
             );
-            println!("{}", t!("authenticated", status = cfg.auth.is_some()));
-            if let Some(auth) = &cfg.auth {
+            let auth = cfg.selected_auth(config::profile_override())?;
+            println!("{}", t!("authenticated", status = auth.is_some()));
+            if let Some(auth) = auth {
                 println!("{}", t!("user_id", uid = &auth.uid));
             }
+            for key in config::CONFIG_KEYS {
+                if let Some(value) = cfg.get(key)? {
+                    println!("{} = {}", key, value);
+                }
+            }
+            Ok(())
+        }
+        Some(ConfigAction::Set { key, value }) => {
+            let mut cfg = config::load_config()?;
+            cfg.set(&key, &value)?;
+            config::save_config(&cfg)?;
+            println!("{}", t!("config_value_set", key = &key, value = &value));
+            Ok(())
+        }
+        Some(ConfigAction::Get { key }) => {
+            let cfg = config::load_config()?;
+            match cfg.get(&key)? {
+                Some(value) => println!("{}", value),
+                None => println!("{}", t!("config_value_unset", key = &key)),
+            }
             Ok(())
         }
     }
 }
 
 async fn handle_auth(action: AuthAction) -> Result<()> {
+    let mut cfg = config::load_config()?;
+    let profile = cfg.profile_or(config::profile_override()).to_owned();
+
     match action {
         AuthAction::Login { token, uid } => {
-            let mut cfg = config::load_config()?;
-            cfg.auth = Some(config::AuthConfig {
-                token: token.clone(),
-                uid: uid.clone(),
-            });
+            cfg.set_profile(
+                &profile,
+                config::AuthConfig {
+                    token,
+                    uid: uid.clone(),
+                },
+            );
             config::save_config(&cfg)?;
             println!("{}", t!("logged_in_as", uid = &uid));
             Ok(())
         }
         AuthAction::Logout => {
-            let mut cfg = config::load_config()?;
-            cfg.auth = None;
+            cfg.remove_profile(&profile);
             config::save_config(&cfg)?;
             println!("{}", t!("logged_out"));
             Ok(())
         }
         AuthAction::Status => {
-            let cfg = config::load_config()?;
-            if let Some(auth) = &cfg.auth {
+            if let Some(auth) = cfg.selected_auth(config::profile_override())? {
                 println!("{}", t!("logged_in_as", uid = &auth.uid));
+                println!("{}", t!("active_profile", profile = &profile));
             } else {
                 println!("{}", t!("not_logged_in"));
             }
             Ok(())
         }
+        AuthAction::Use { profile } => {
+            cfg.use_profile(&profile)?;
+            config::save_config(&cfg)?;
+            println!("{}", t!("switched_profile", profile = &profile));
+            Ok(())
+        }
     }
 }
@@ -3,10 +3,14 @@
 use rmcp::{
     handler::server::{tool::ToolRouter, wrapper::Parameters},
     model::*,
-    tool, tool_handler, tool_router, ErrorData as McpError, ServerHandler,
+    service::RequestContext,
+    tool, tool_handler, tool_router, ErrorData as McpError, RoleServer, ServerHandler,
 };
+use rnga::models::{Forum, ForumIdKind, Topic};
+use rnga::NGAClient;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use crate::config;
 use crate::handlers::{forum, post, topic, user};
@@ -40,6 +44,118 @@ impl NGAMCPServer {
     }
 }
 
+/// URI of a favorite forum or topic exposed as an MCP resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ResourceUri {
+    /// `nga://forum/{fid}` or `nga://forum/stid/{stid}`
+    Forum(ForumIdKind),
+    /// `nga://topic/{tid}`
+    Topic(String),
+}
+
+impl ResourceUri {
+    fn parse(uri: &str) -> Option<Self> {
+        let path = uri.strip_prefix("nga://")?;
+        let (kind, id) = path.split_once('/')?;
+        let uri = match (kind, id.split_once('/')) {
+            ("forum", Some(("stid", stid))) => Self::Forum(ForumIdKind::stid(stid)),
+            ("forum", None) => Self::Forum(ForumIdKind::fid(id)),
+            ("topic", None) => Self::Topic(id.to_owned()),
+            _ => return None,
+        };
+        Some(uri)
+    }
+}
+
+impl fmt::Display for ResourceUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Forum(ForumIdKind::Fid(fid)) => write!(f, "nga://forum/{}", fid),
+            Self::Forum(ForumIdKind::Stid(stid)) => write!(f, "nga://forum/stid/{}", stid),
+            Self::Topic(tid) => write!(f, "nga://topic/{}", tid),
+        }
+    }
+}
+
+fn forum_resource(forum: &Forum) -> Option<Resource> {
+    let uri = ResourceUri::Forum(forum.id.clone()?);
+    let mut resource = RawResource::new(uri.to_string(), &forum.name);
+    resource.description = Some(forum.info.clone()).filter(|info| !info.is_empty());
+    resource.mime_type = Some("text/plain".into());
+    Some(resource.no_annotation())
+}
+
+fn topic_resource(topic: &Topic) -> Resource {
+    let uri = ResourceUri::Topic(topic.id.to_string());
+    let mut resource = RawResource::new(uri.to_string(), &topic.subject.content);
+    resource.description = Some(topic.author.name.display().to_string());
+    resource.mime_type = Some("text/markdown".into());
+    resource.no_annotation()
+}
+
+/// The logged-in user's favorite forums, then the first page of favorite topics.
+///
+/// Empty when not logged in, since clients list resources on connect.
+async fn favorite_resources(client: &NGAClient) -> anyhow::Result<Vec<Resource>> {
+    match fetch_favorite_resources(client).await {
+        Err(rnga::Error::AuthRequired) => Ok(Vec::new()),
+        result => Ok(result?),
+    }
+}
+
+/// Fetch both favorite lists and map them to resources.
+async fn fetch_favorite_resources(client: &NGAClient) -> rnga::Result<Vec<Resource>> {
+    let forums = client.forums().favorites().await?;
+    let topics = client.topics().favorites().send().await?;
+
+    Ok(forums
+        .iter()
+        .filter_map(forum_resource)
+        .chain(topics.topics.iter().map(topic_resource))
+        .collect())
+}
+
+/// Resolve a resource: a forum's first topic page as TOON, a topic's first
+/// page as Markdown.
+async fn read_favorite(client: &NGAClient, uri: &str) -> Result<ResourceContents, McpError> {
+    let internal = |e: anyhow::Error| McpError::internal_error(e.to_string(), None);
+
+    let text = match ResourceUri::parse(uri) {
+        Some(ResourceUri::Forum(forum_id)) => {
+            let options = topic::ListTopicsOptions {
+                is_stid: matches!(forum_id, ForumIdKind::Stid(_)),
+                start_page: 1,
+                num_pages: 1,
+                order: default_order(),
+                concurrency: 1,
+            };
+            let result = topic::list_topics(client, forum_id.id(), options)
+                .await
+                .map_err(internal)?;
+            NGAMCPServer::to_toon(&result)?
+        }
+        Some(ResourceUri::Topic(topic_id)) => {
+            let options = topic::ReadTopicOptions {
+                page: 1,
+                concurrency: 1,
+                ..Default::default()
+            };
+            topic::read_topic(client, &topic_id, options)
+                .await
+                .map_err(internal)?
+                .to_markdown()
+        }
+        None => {
+            return Err(McpError::resource_not_found(
+                format!("Unknown resource: {}", uri),
+                None,
+            ))
+        }
+    };
+
+    Ok(ResourceContents::text(text, uri))
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct KeywordParam {
     /// Search keyword
@@ -80,6 +196,20 @@ pub struct TopicReadParams {
     pub range: Option<String>,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TopicMarkdownParams {
+    /// Topic ID
+    pub topic_id: String,
+    /// Page number (default: 1)
+    #[serde(default = "default_page")]
+    pub page: u32,
+    /// Filter by author ID
+    pub author: Option<String>,
+    /// Fetch all pages
+    #[serde(default)]
+    pub all: bool,
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct TopicSearchParams {
     /// Forum ID (fid)
@@ -202,6 +332,7 @@ impl NGAMCPServer {
             fetch_all: params.0.all,
             concurrency: 4,
             range: params.0.range,
+            progress: false,
         };
         let result = topic::read_topic(&client, &params.0.topic_id, options)
             .await
@@ -209,6 +340,28 @@ impl NGAMCPServer {
         Self::ok(Self::to_toon(&result)?)
     }
 
+    #[tool(
+        description = "Read a topic rendered as Markdown, one section per floor, for summarizing"
+    )]
+    async fn topic_markdown(
+        &self,
+        params: Parameters<TopicMarkdownParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let client = Self::build_client()?;
+        let options = topic::ReadTopicOptions {
+            page: params.0.page,
+            author: params.0.author,
+            fetch_all: params.0.all,
+            concurrency: 4,
+            range: None,
+            progress: false,
+        };
+        let result = topic::read_topic(&client, &params.0.topic_id, options)
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Self::ok(result.to_markdown())
+    }
+
     #[tool(description = "Search topics in a forum by keyword")]
     async fn topic_search(
         &self,
@@ -238,6 +391,7 @@ impl NGAMCPServer {
             order: "lastpost".to_string(),
             with_posts: params.0.with_posts,
             concurrency: 4,
+            include_topped: false,
         };
         let result = topic::recent_topics(&client, &params.0.forum_id, options)
             .await
@@ -301,10 +455,10 @@ impl NGAMCPServer {
         params: Parameters<KeywordParam>,
     ) -> Result<CallToolResult, McpError> {
         let client = Self::build_client()?;
-        let results = user::search_users(&client, &params.0.keyword)
+        let results = user::search_users(&client, &params.0.keyword, 1)
             .await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        Self::ok(Self::to_toon(&results)?)
+        Self::ok(Self::to_toon(&results.users)?)
     }
 }
 
@@ -315,6 +469,7 @@ impl ServerHandler for NGAMCPServer {
             protocol_version: Default::default(),
             capabilities: ServerCapabilities {
                 tools: Some(ToolsCapability { list_changed: None }),
+                resources: Some(ResourcesCapability::default()),
                 ..Default::default()
             },
             server_info: Implementation {
@@ -327,6 +482,30 @@ impl ServerHandler for NGAMCPServer {
             ..Default::default()
         }
     }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let client = Self::build_client()?;
+        let resources = favorite_resources(&client)
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(ListResourcesResult::with_all_items(resources))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let client = Self::build_client()?;
+        let contents = read_favorite(&client, &request.uri).await?;
+        Ok(ReadResourceResult {
+            contents: vec![contents],
+        })
+    }
 }
 
 /// Run the MCP Server.
@@ -343,3 +522,70 @@ pub async fn run_server() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rnga::testing::serve_bodies;
+
+    /// Logged-in client for a local server answering with `bodies` in order.
+    fn serving_client(bodies: Vec<&'static str>) -> NGAClient {
+        NGAClient::builder()
+            .base_url(serve_bodies(bodies))
+            .auth("token", "1")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_resource_uri() {
+        for uri in ["nga://forum/310", "nga://forum/stid/1234", "nga://topic/42"] {
+            assert_eq!(ResourceUri::parse(uri).unwrap().to_string(), uri);
+        }
+        assert_eq!(
+            ResourceUri::parse("nga://forum/-7"),
+            Some(ResourceUri::Forum(ForumIdKind::fid("-7")))
+        );
+        assert!(ResourceUri::parse("nga://user/1").is_none());
+        assert!(ResourceUri::parse("https://forum/310").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_favorite_resources() {
+        let client = serving_client(vec![
+            "<root><data><item>\
+<item><fid>310</fid><name>精英议会</name><info>Raiding</info></item>\
+<item><stid>1234</stid><name>Guild</name></item>\
+</item></data></root>",
+            "<root><__T><item><tid>42</tid><fid>310</fid><subject>Patch notes</subject>\
+<author>alice</author><authorid>1</authorid></item></__T></root>",
+        ]);
+
+        let resources = favorite_resources(&client).await.unwrap();
+        let listed: Vec<(&str, &str)> = resources
+            .iter()
+            .map(|r| (r.uri.as_str(), r.name.as_str()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("nga://forum/310", "精英议会"),
+                ("nga://forum/stid/1234", "Guild"),
+                ("nga://topic/42", "Patch notes"),
+            ]
+        );
+        assert_eq!(resources[0].description.as_deref(), Some("Raiding"));
+        assert_eq!(resources[1].description, None);
+    }
+
+    #[tokio::test]
+    async fn test_favorite_resources_logged_out() {
+        let client = NGAClient::builder()
+            .base_url("http://127.0.0.1:9/")
+            .build()
+            .unwrap();
+
+        let resources = favorite_resources(&client).await.unwrap();
+        assert!(resources.is_empty());
+    }
+}
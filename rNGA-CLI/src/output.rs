@@ -1,10 +1,25 @@
 //! Output formatting.
 
+use anyhow::{bail, Result};
+use chrono::format::{Item, StrftimeItems};
 use chrono::{Local, TimeZone};
+use chrono_tz::Tz;
 use clap::ValueEnum;
+use colored::Color;
 use comfy_table::{presets::UTF8_FULL_CONDENSED, ContentArrangement, Table};
+use rnga::models::{PostContent, Span, SpanKind};
 use rust_i18n::t;
 use serde::Serialize;
+use std::sync::OnceLock;
+
+/// Table columns selected with the global `--columns` flag.
+static TABLE_COLUMNS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Timestamp style selected with `--timezone` and `--time-format`.
+static TIME_STYLE: OnceLock<TimeStyle> = OnceLock::new();
+
+/// Format of absolute timestamps when none is configured.
+pub const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
 
 /// Output format options.
 #[derive(Debug, Clone, Copy, Default, ValueEnum)]
@@ -13,6 +28,8 @@ pub enum OutputFormat {
     Table,
     /// JSON format
     Json,
+    /// Newline-delimited JSON, one object per line
+    JsonLines,
     /// TOON format
     Toon,
     /// Plain text format
@@ -32,6 +49,43 @@ pub trait TableRow {
     fn headers() -> Vec<&'static str>;
     /// Get row data as strings.
     fn row(&self) -> Vec<String>;
+
+    /// Indices of the columns whose headers match `columns`, ignoring case,
+    /// in the order given. Unknown names are skipped.
+    fn column_indices(columns: &[&str]) -> Vec<usize> {
+        let headers = Self::headers();
+        columns
+            .iter()
+            .filter_map(|c| {
+                headers
+                    .iter()
+                    .position(|h| h.eq_ignore_ascii_case(c.trim()))
+            })
+            .collect()
+    }
+
+    /// Headers of the columns at `indices`.
+    fn selected_headers(indices: &[usize]) -> Vec<&'static str> {
+        let headers = Self::headers();
+        indices
+            .iter()
+            .filter_map(|&i| headers.get(i).copied())
+            .collect()
+    }
+
+    /// Cells of this row at `indices`.
+    fn selected_row(&self, indices: &[usize]) -> Vec<String> {
+        let mut row: Vec<Option<String>> = self.row().into_iter().map(Some).collect();
+        indices
+            .iter()
+            .filter_map(|&i| row.get_mut(i).and_then(Option::take))
+            .collect()
+    }
+}
+
+/// Limit table output to the named columns, for tables printed afterwards.
+pub fn set_table_columns(columns: Vec<String>) {
+    let _ = TABLE_COLUMNS.set(columns);
 }
 
 /// Print items in plain text format.
@@ -45,29 +99,194 @@ pub fn print_plain<T: PlainPrint>(items: &[T]) {
     }
 }
 
-/// Format a Unix timestamp for display.
-pub fn format_time(timestamp: i64) -> String {
-    if timestamp == 0 {
-        return "-".to_string();
+/// Rendering of post content with ANSI styling.
+pub trait AnsiRender {
+    /// Render as text with terminal escape sequences.
+    ///
+    /// Follows the `colored` override, so `--no-color` yields plain text.
+    fn to_ansi(&self) -> String;
+}
+
+impl AnsiRender for PostContent {
+    fn to_ansi(&self) -> String {
+        let color = colored::control::SHOULD_COLORIZE.should_colorize();
+        render_spans(&self.spans, color)
+    }
+}
+
+/// Wrap `text` in the SGR escape `code` when `color` is set.
+///
+/// Styling is applied here rather than through `Colorize`, whose output
+/// depends on the process-wide override at display time.
+fn sgr(text: &str, code: &str, color: bool) -> String {
+    if color {
+        format!("\u{1b}[{}m{}\u{1b}[0m", code, text)
+    } else {
+        text.to_owned()
+    }
+}
+
+fn render_spans(spans: &[Span], color: bool) -> String {
+    spans.iter().map(|span| render_span(span, color)).collect()
+}
+
+fn render_span(span: &Span, color: bool) -> String {
+    if let SpanKind::Collapse { title, spans } = &span.kind {
+        let title = format!("[{}]", title.as_deref().unwrap_or("..."));
+        return format!(
+            "{}\n{}",
+            sgr(&title, "2", color),
+            render_spans(spans, color)
+        );
+    }
+
+    let SpanKind::Tagged {
+        tag,
+        attributes,
+        spans,
+        ..
+    } = &span.kind
+    else {
+        return span.to_plain_text();
+    };
+
+    let inner = render_spans(spans, color);
+    match tag.as_str() {
+        "b" => sgr(&inner, "1", color),
+        "i" => sgr(&inner, "3", color),
+        "u" => sgr(&inner, "4", color),
+        "del" => sgr(&inner, "9", color),
+        "color" => match attributes.first().and_then(|c| ansi_color(c)) {
+            Some(fg) => sgr(&inner, &fg.to_fg_str(), color),
+            None => inner,
+        },
+        "quote" => {
+            let block: Vec<String> = inner
+                .trim_matches('\n')
+                .lines()
+                .map(|line| format!("    {}", line))
+                .collect();
+            format!("\n{}\n", sgr(&block.join("\n"), "2", color))
+        }
+        _ => inner,
+    }
+}
+
+/// Map an NGA color name to the nearest ANSI color.
+fn ansi_color(name: &str) -> Option<Color> {
+    let color = match name.to_lowercase().as_str() {
+        "red" | "crimson" | "firebrick" | "darkred" | "orangered" | "tomato" => Color::Red,
+        "orange" | "coral" | "sandybrown" | "burlywood" | "chocolate" | "sienna" => Color::Yellow,
+        "green" | "limegreen" | "seagreen" => Color::Green,
+        "blue" | "royalblue" | "darkblue" | "navy" => Color::Blue,
+        "skyblue" | "teal" | "cyan" => Color::Cyan,
+        "purple" | "indigo" | "deeppink" | "magenta" => Color::Magenta,
+        "silver" | "gray" | "grey" => Color::BrightBlack,
+        _ => return None,
+    };
+    Some(color)
+}
+
+/// Serialize items as newline-delimited JSON.
+pub fn to_json_lines<T: Serialize>(items: &[T]) -> String {
+    items
+        .iter()
+        .filter_map(|item| serde_json::to_string(item).ok())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// How absolute timestamps are rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeStyle {
+    /// Timezone to convert to, or the machine's local one.
+    timezone: Option<Tz>,
+    /// strftime format string.
+    format: String,
+}
+
+impl Default for TimeStyle {
+    fn default() -> Self {
+        Self {
+            timezone: None,
+            format: DEFAULT_TIME_FORMAT.to_owned(),
+        }
+    }
+}
+
+impl TimeStyle {
+    /// Build a style from an IANA timezone name and a strftime format.
+    pub fn new(timezone: Option<&str>, format: Option<&str>) -> Result<Self> {
+        let timezone = match timezone {
+            Some(name) => match name.parse::<Tz>() {
+                Ok(tz) => Some(tz),
+                Err(_) => bail!("Invalid timezone: {}", name),
+            },
+            None => None,
+        };
+
+        let format = format.unwrap_or(DEFAULT_TIME_FORMAT);
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            bail!("Invalid time format: {}", format);
+        }
+
+        Ok(Self {
+            timezone,
+            format: format.to_owned(),
+        })
     }
 
-    let dt = Local.timestamp_opt(timestamp, 0).single();
-    match dt {
-        Some(dt) => dt.format("%Y-%m-%d %H:%M").to_string(),
-        None => "-".to_string(),
+    /// Render a Unix timestamp, or "-" if it is unset or out of range.
+    pub fn format(&self, timestamp: i64) -> String {
+        if timestamp == 0 {
+            return "-".to_string();
+        }
+
+        let formatted = match self.timezone {
+            Some(tz) => tz
+                .timestamp_opt(timestamp, 0)
+                .single()
+                .map(|dt| dt.format(&self.format).to_string()),
+            None => Local
+                .timestamp_opt(timestamp, 0)
+                .single()
+                .map(|dt| dt.format(&self.format).to_string()),
+        };
+        formatted.unwrap_or_else(|| "-".to_string())
+    }
+}
+
+/// Set the style used by `format_time`.
+pub fn set_time_style(style: TimeStyle) {
+    let _ = TIME_STYLE.set(style);
+}
+
+/// Format a Unix timestamp for display.
+pub fn format_time(timestamp: i64) -> String {
+    match TIME_STYLE.get() {
+        Some(style) => style.format(timestamp),
+        None => TimeStyle::default().format(timestamp),
     }
 }
 
 /// Format a relative time for display.
 pub fn format_relative_time(timestamp: i64) -> String {
+    format_relative_time_at(Local::now().timestamp(), timestamp)
+}
+
+/// Format a timestamp relative to the given `now`.
+///
+/// Timestamps slightly in the future (server clock skew) are shown as "just now".
+pub fn format_relative_time_at(now: i64, timestamp: i64) -> String {
     if timestamp == 0 {
         return "-".to_string();
     }
 
-    let now = Local::now().timestamp();
     let diff = now - timestamp;
 
-    if diff < 60 {
+    if diff <= 0 {
+        t!("time_just_now").to_string()
+    } else if diff < 60 {
         t!("time_ago_seconds", count = diff).to_string()
     } else if diff < 3600 {
         t!("time_ago_minutes", m = diff / 60, s = diff % 60).to_string()
@@ -95,6 +314,22 @@ pub fn format_relative_time(timestamp: i64) -> String {
 
 /// Print a table of items with proper formatting for each output mode.
 pub fn print_table<T: TableRow + Serialize + PlainPrint>(items: Vec<T>, format: OutputFormat) {
+    let columns: Vec<&str> = TABLE_COLUMNS
+        .get()
+        .map(|c| c.iter().map(String::as_str).collect())
+        .unwrap_or_default();
+    print_table_columns(items, format, &columns);
+}
+
+/// Print items like `print_table`, with tables limited to `columns`.
+///
+/// Columns are picked by header name; with none matching, all are shown.
+/// Other formats are unaffected.
+pub fn print_table_columns<T: TableRow + Serialize + PlainPrint>(
+    items: Vec<T>,
+    format: OutputFormat,
+    columns: &[&str],
+) {
     match format {
         OutputFormat::Json => {
             println!(
@@ -102,6 +337,11 @@ pub fn print_table<T: TableRow + Serialize + PlainPrint>(items: Vec<T>, format:
                 serde_json::to_string_pretty(&items).unwrap_or_default()
             );
         }
+        OutputFormat::JsonLines => {
+            if !items.is_empty() {
+                println!("{}", to_json_lines(&items));
+            }
+        }
         OutputFormat::Toon => {
             let json_value = serde_json::to_value(&items).unwrap_or_default();
             println!("{}", toon_format::encode_default(&json_value).unwrap_or_default());
@@ -114,9 +354,17 @@ pub fn print_table<T: TableRow + Serialize + PlainPrint>(items: Vec<T>, format:
             let mut table = Table::new();
             table.load_preset(UTF8_FULL_CONDENSED);
             table.set_content_arrangement(ContentArrangement::Dynamic);
-            table.set_header(T::headers());
-            for item in &items {
-                table.add_row(item.row());
+            let indices = T::column_indices(columns);
+            if indices.is_empty() {
+                table.set_header(T::headers());
+                for item in &items {
+                    table.add_row(item.row());
+                }
+            } else {
+                table.set_header(T::selected_headers(&indices));
+                for item in &items {
+                    table.add_row(item.selected_row(&indices));
+                }
             }
             println!("{table}");
         }
@@ -125,3 +373,106 @@ pub fn print_table<T: TableRow + Serialize + PlainPrint>(items: Vec<T>, format:
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[derive(Serialize)]
+    struct Item {
+        id: u32,
+        name: &'static str,
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let items = vec![
+            Item { id: 1, name: "a" },
+            Item {
+                id: 2,
+                name: "b\nc",
+            },
+        ];
+        let output = to_json_lines(&items);
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], r#"{"id":1,"name":"a"}"#);
+        assert_eq!(lines[1], r#"{"id":2,"name":"b\nc"}"#);
+        assert!(!output.starts_with('['));
+        assert!(!output.ends_with(']'));
+    }
+
+    #[test]
+    fn test_to_ansi() {
+        let content =
+            rnga::parser::parse_content("[b]bold[/b] [quote]quoted[/quote][color=red]!![/color]");
+
+        let ansi = render_spans(&content.spans, true);
+        let plain = render_spans(&content.spans, false);
+
+        assert!(ansi.starts_with("\u{1b}[1mbold\u{1b}[0m"));
+        assert!(ansi.contains("\u{1b}[2m    quoted\u{1b}[0m"));
+        assert!(ansi.ends_with("\u{1b}[31m!!\u{1b}[0m"));
+        assert_eq!(plain, "bold \n    quoted\n!!");
+    }
+
+    struct Row;
+
+    impl TableRow for Row {
+        fn headers() -> Vec<&'static str> {
+            vec!["ID", "Subject", "Author", "Replies"]
+        }
+        fn row(&self) -> Vec<String> {
+            vec!["1".into(), "Hello".into(), "alice".into(), "3".into()]
+        }
+    }
+
+    #[test]
+    fn test_column_projection() {
+        let indices = Row::column_indices(&["replies", "id", "missing"]);
+        assert_eq!(indices, vec![3, 0]);
+        assert_eq!(Row::selected_headers(&indices), vec!["Replies", "ID"]);
+        assert_eq!(Row.selected_row(&indices), vec!["3", "1"]);
+    }
+
+    #[test]
+    fn test_time_style_timezones() {
+        // 2023-11-14 22:13:20 UTC
+        let shanghai = TimeStyle::new(Some("Asia/Shanghai"), None).unwrap();
+        assert_eq!(shanghai.format(NOW), "2023-11-15 06:13");
+
+        let new_york = TimeStyle::new(Some("America/New_York"), Some("%d/%m %H:%M %Z")).unwrap();
+        assert_eq!(new_york.format(NOW), "14/11 17:13 EST");
+
+        assert_eq!(shanghai.format(0), "-");
+        assert!(TimeStyle::new(Some("Mars/Olympus"), None).is_err());
+        assert!(TimeStyle::new(None, Some("%Y-%")).is_err());
+    }
+
+    #[test]
+    fn test_relative_time_future() {
+        assert_eq!(format_relative_time_at(NOW, NOW + 3), "just now");
+    }
+
+    #[test]
+    fn test_relative_time_just_now() {
+        assert_eq!(format_relative_time_at(NOW, NOW), "just now");
+        assert_eq!(format_relative_time_at(NOW, NOW - 5), "5s ago");
+    }
+
+    #[test]
+    fn test_relative_time_minutes() {
+        assert_eq!(format_relative_time_at(NOW, NOW - 125), "2m 5s ago");
+    }
+
+    #[test]
+    fn test_relative_time_days() {
+        assert_eq!(
+            format_relative_time_at(NOW, NOW - (2 * 86400 + 3 * 3600 + 4 * 60 + 5)),
+            "2d 3h 4m 5s ago"
+        );
+    }
+}
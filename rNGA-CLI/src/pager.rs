@@ -0,0 +1,141 @@
+//! Interactive page navigation.
+
+/// Posts per topic page on NGA.
+pub const POSTS_PER_PAGE: u32 = 20;
+
+/// A command typed at the pager prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagerCommand {
+    /// Next page (`n`, or just Enter)
+    Next,
+    /// Previous page (`p`)
+    Prev,
+    /// Jump to a page (`g 3`)
+    Page(u32),
+    /// Jump to the page holding a floor (`#45` or `f 45`)
+    Floor(u32),
+    /// Leave the pager (`q`)
+    Quit,
+}
+
+impl PagerCommand {
+    /// Parse one line of input, or `None` if it isn't a command.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        if let Some(floor) = input.strip_prefix('#') {
+            return floor.trim().parse().ok().map(Self::Floor);
+        }
+
+        let (cmd, arg) = match input.split_once(char::is_whitespace) {
+            Some((cmd, arg)) => (cmd, Some(arg.trim())),
+            None => (input.as_str(), None),
+        };
+        match (cmd, arg) {
+            ("" | "n" | "next", None) => Some(Self::Next),
+            ("p" | "prev", None) => Some(Self::Prev),
+            ("q" | "quit", None) => Some(Self::Quit),
+            ("g" | "page", Some(arg)) => arg.parse().ok().map(Self::Page),
+            ("f" | "floor", Some(arg)) => arg.parse().ok().map(Self::Floor),
+            _ => None,
+        }
+    }
+}
+
+/// What the pager should do after a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagerStep {
+    /// Fetch and show this page.
+    Show(u32),
+    /// Keep the current page, e.g. `Next` on the last page.
+    Stay,
+    /// Leave the pager.
+    Quit,
+}
+
+/// Current position within a paged topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pager {
+    page: u32,
+    total_pages: u32,
+}
+
+impl Pager {
+    /// Start at `page` of `total_pages`.
+    pub fn new(page: u32, total_pages: u32) -> Self {
+        let total_pages = total_pages.max(1);
+        Self {
+            page: page.clamp(1, total_pages),
+            total_pages,
+        }
+    }
+
+    pub fn page(&self) -> u32 {
+        self.page
+    }
+
+    pub fn total_pages(&self) -> u32 {
+        self.total_pages
+    }
+
+    /// Update the page count after a fetch; topics grow while being read.
+    pub fn set_total_pages(&mut self, total_pages: u32) {
+        self.total_pages = total_pages.max(1);
+        self.page = self.page.min(self.total_pages);
+    }
+
+    /// Apply a command, moving to the page it selects.
+    pub fn apply(&mut self, command: PagerCommand) -> PagerStep {
+        let target = match command {
+            PagerCommand::Quit => return PagerStep::Quit,
+            PagerCommand::Next => self.page + 1,
+            PagerCommand::Prev => self.page.saturating_sub(1),
+            PagerCommand::Page(page) => page,
+            PagerCommand::Floor(floor) => floor / POSTS_PER_PAGE + 1,
+        };
+
+        let target = target.clamp(1, self.total_pages);
+        if target == self.page {
+            return PagerStep::Stay;
+        }
+        self.page = target;
+        PagerStep::Show(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(PagerCommand::parse(""), Some(PagerCommand::Next));
+        assert_eq!(PagerCommand::parse(" N \n"), Some(PagerCommand::Next));
+        assert_eq!(PagerCommand::parse("p"), Some(PagerCommand::Prev));
+        assert_eq!(PagerCommand::parse("g 3"), Some(PagerCommand::Page(3)));
+        assert_eq!(PagerCommand::parse("#45"), Some(PagerCommand::Floor(45)));
+        assert_eq!(PagerCommand::parse("f 7"), Some(PagerCommand::Floor(7)));
+        assert_eq!(PagerCommand::parse("q"), Some(PagerCommand::Quit));
+        assert_eq!(PagerCommand::parse("g"), None);
+        assert_eq!(PagerCommand::parse("g x"), None);
+        assert_eq!(PagerCommand::parse("hello"), None);
+    }
+
+    #[test]
+    fn test_pager_navigation() {
+        let mut pager = Pager::new(1, 3);
+        assert_eq!(pager.apply(PagerCommand::Prev), PagerStep::Stay);
+        assert_eq!(pager.apply(PagerCommand::Next), PagerStep::Show(2));
+        assert_eq!(pager.apply(PagerCommand::Page(9)), PagerStep::Show(3));
+        assert_eq!(pager.apply(PagerCommand::Next), PagerStep::Stay);
+        assert_eq!(pager.apply(PagerCommand::Floor(19)), PagerStep::Show(1));
+        assert_eq!(pager.apply(PagerCommand::Floor(20)), PagerStep::Show(2));
+        assert_eq!(pager.apply(PagerCommand::Quit), PagerStep::Quit);
+        assert_eq!(pager.page(), 2);
+
+        pager.set_total_pages(5);
+        assert_eq!(pager.apply(PagerCommand::Page(5)), PagerStep::Show(5));
+        pager.set_total_pages(0);
+        assert_eq!((pager.page(), pager.total_pages()), (1, 1));
+        assert_eq!(Pager::new(7, 2).page(), 2);
+    }
+}
@@ -1,15 +1,25 @@
 //! Forum API.
 
+use serde_json::Value;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::{
+    api::{TopicApi, TopicDetailsResult},
+    cache::CacheStorageExt,
     client::NGAClientInner,
     client::FORUM_ICON_PATH,
-    error::Result,
-    models::{Category, FavoriteForumOp, Forum, ForumIdKind, SubforumFilterOp},
+    error::{Error, Result},
+    models::{Category, FavoriteForumOp, Forum, ForumDetail, ForumIdKind, SubforumFilterOp},
     parser::XmlDocument,
 };
 
+/// Cache key of the category tree used by `find_local`.
+const CATEGORIES_CACHE_KEY: &str = "forums:categories";
+
+/// How long the cached category tree is reused; boards rarely change.
+const CATEGORIES_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
 /// API for forum operations.
 pub struct ForumApi {
     client: Arc<NGAClientInner>,
@@ -22,6 +32,18 @@ impl ForumApi {
 
     /// List all forum categories.
     pub async fn list(&self) -> Result<Vec<Category>> {
+        if self.client.config.prefer_json {
+            let data = self
+                .client
+                .post_json(
+                    "app_api.php",
+                    &[("__lib", "home"), ("__act", "category")],
+                    &[],
+                )
+                .await?;
+            return Ok(parse_categories_json(&data));
+        }
+
         let xml = self
             .client
             .post(
@@ -62,6 +84,61 @@ impl ForumApi {
         Ok(forums)
     }
 
+    /// Find forums whose name or info contains `keyword`, ignoring case.
+    ///
+    /// Unlike `search`, this matches locally against the whole category
+    /// tree from `list()`, so subforums are found too. With a cache
+    /// configured the tree is fetched once and reused.
+    pub async fn find_local(&self, keyword: &str) -> Result<Vec<Forum>> {
+        let categories = match &self.client.cache {
+            Some(cache) => match cache.get_json(CATEGORIES_CACHE_KEY).await {
+                Some(categories) => categories,
+                None => {
+                    let categories = self.list().await?;
+                    cache
+                        .set_json(CATEGORIES_CACHE_KEY, &categories, Some(CATEGORIES_TTL))
+                        .await?;
+                    categories
+                }
+            },
+            None => self.list().await?,
+        };
+
+        Ok(find_forums(&categories, keyword))
+    }
+
+    /// Get full metadata of a forum, including moderators and announcement.
+    pub async fn info(&self, forum_id: ForumIdKind) -> Result<ForumDetail> {
+        let xml = self
+            .client
+            .post(
+                "thread.php",
+                &[(forum_id.param_name(), forum_id.id()), ("page", "1")],
+                &[],
+            )
+            .await?;
+
+        parse_forum_detail(&xml, &forum_id)
+    }
+
+    /// Get the forum's pinned announcement topic, if it has one.
+    ///
+    /// Looks up the topped topic from the forum info and fetches its first
+    /// page, which usually holds the forum rules.
+    pub async fn announcement(&self, forum_id: ForumIdKind) -> Result<Option<TopicDetailsResult>> {
+        let detail = self.info(forum_id).await?;
+        let topic_id = match detail.forum.topped_topic_id.as_str() {
+            "" | "0" => return Ok(None),
+            id => id.to_owned(),
+        };
+
+        TopicApi::new(self.client.clone())
+            .details(topic_id)
+            .send()
+            .await
+            .map(Some)
+    }
+
     /// Get favorite forums.
     pub async fn favorites(&self) -> Result<Vec<Forum>> {
         let xml = self
@@ -127,6 +204,26 @@ impl ForumApi {
     }
 }
 
+/// Forums in `categories` matching `keyword`, each listed once.
+fn find_forums(categories: &[Category], keyword: &str) -> Vec<Forum> {
+    let keyword = keyword.trim().to_lowercase();
+    let mut found: Vec<Forum> = Vec::new();
+
+    for forum in categories.iter().flat_map(|cat| &cat.forums) {
+        let matches = forum.name.to_lowercase().contains(&keyword)
+            || forum.info.to_lowercase().contains(&keyword);
+        if matches
+            && !found
+                .iter()
+                .any(|f| f.id == forum.id && f.name == forum.name)
+        {
+            found.push(forum.clone());
+        }
+    }
+
+    found
+}
+
 /// Parse category from XML node.
 fn parse_category(node: &crate::parser::XmlNode<'_>) -> Result<Option<Category>> {
     let attrs = node.attrs();
@@ -157,6 +254,84 @@ fn parse_category(node: &crate::parser::XmlNode<'_>) -> Result<Option<Category>>
     Ok(Some(Category { id, name, forums }))
 }
 
+/// Elements of a JSON array, or values of an object keyed by index.
+fn json_items(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(map) => {
+            // Keys are sorted as strings, so "10" would precede "2".
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(k, _)| k.parse::<u64>().unwrap_or(u64::MAX));
+            entries.into_iter().map(|(_, v)| v).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// String form of a JSON string or number field.
+fn json_str(value: &Value, key: &str) -> Option<String> {
+    match value.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Parse categories from the JSON `data` of a category response.
+fn parse_categories_json(data: &Value) -> Vec<Category> {
+    json_items(data)
+        .into_iter()
+        .filter_map(|cat| {
+            let id = json_str(cat, "_id")?;
+            let name = json_str(cat, "name")?;
+            let forums = cat
+                .get("groups")
+                .map(json_items)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|group| group.get("forums"))
+                .flat_map(json_items)
+                .filter_map(parse_forum_json)
+                .collect();
+            Some(Category { id, name, forums })
+        })
+        .collect()
+}
+
+/// Parse a forum from a JSON object.
+fn parse_forum_json(value: &Value) -> Option<Forum> {
+    let field = |key: &str| json_str(value, key).filter(|s| !s.is_empty() && s != "0");
+
+    let icon_id = json_str(value, "id")
+        .or_else(|| json_str(value, "fid"))
+        .unwrap_or_default();
+    let id = field("stid")
+        .map(ForumIdKind::stid)
+        .or_else(|| field("fid").map(ForumIdKind::fid));
+
+    Some(Forum {
+        id,
+        name: json_str(value, "name")?,
+        info: json_str(value, "info").unwrap_or_default(),
+        icon_url: format!("{}{}.png", FORUM_ICON_PATH, icon_id),
+        topped_topic_id: json_str(value, "topped_topic").unwrap_or_default(),
+        topic_count: forum_count(TOPIC_COUNT_KEYS, |key| json_str(value, key)),
+        today_count: forum_count(TODAY_COUNT_KEYS, |key| json_str(value, key)),
+    })
+}
+
+/// Fields in which a category listing reports a forum's topic count.
+const TOPIC_COUNT_KEYS: &[&str] = &["topic_num", "topics"];
+
+/// Fields in which a category listing reports a forum's posts today.
+const TODAY_COUNT_KEYS: &[&str] = &["today_num", "today"];
+
+/// First of `keys` whose `field` value is a number.
+fn forum_count(keys: &[&str], field: impl Fn(&str) -> Option<String>) -> Option<i64> {
+    keys.iter()
+        .find_map(|key| field(key).and_then(|s| s.trim().parse().ok()))
+}
+
 /// Parse forum from XML node.
 fn parse_forum(node: &crate::parser::XmlNode<'_>) -> Result<Option<Forum>> {
     let attrs = node.attrs();
@@ -170,10 +345,11 @@ fn parse_forum(node: &crate::parser::XmlNode<'_>) -> Result<Option<Forum>> {
 
     let id = if let Some(stid) = attrs.get("stid").filter(|s| !s.is_empty() && *s != "0") {
         Some(ForumIdKind::stid(stid.clone()))
-    } else if let Some(fid) = attrs.get("fid").filter(|s| !s.is_empty() && *s != "0") {
-        Some(ForumIdKind::fid(fid.clone()))
     } else {
-        None
+        attrs
+            .get("fid")
+            .filter(|s| !s.is_empty() && *s != "0")
+            .map(|fid| ForumIdKind::fid(fid.clone()))
     };
 
     let name = match attrs.get("name") {
@@ -187,12 +363,64 @@ fn parse_forum(node: &crate::parser::XmlNode<'_>) -> Result<Option<Forum>> {
         info: attrs.get("info").cloned().unwrap_or_default(),
         icon_url,
         topped_topic_id: attrs.get("topped_topic").cloned().unwrap_or_default(),
+        topic_count: forum_count(TOPIC_COUNT_KEYS, |key| attrs.get(key).cloned()),
+        today_count: forum_count(TODAY_COUNT_KEYS, |key| attrs.get(key).cloned()),
     }))
 }
 
+/// Parse forum detail from a `thread.php` response.
+fn parse_forum_detail(xml: &str, forum_id: &ForumIdKind) -> Result<ForumDetail> {
+    let doc = XmlDocument::parse(xml)?;
+
+    let node = doc
+        .select_one("/root/__F")?
+        .ok_or_else(|| Error::missing("forum"))?;
+    let attrs = node.attrs();
+
+    let mut forum = match parse_forum(&node)? {
+        Some(forum) => forum,
+        None => Forum::minimal(forum_id.clone(), ""),
+    };
+    if forum.id.is_none() {
+        forum.id = Some(forum_id.clone());
+    }
+
+    let moderators = node
+        .children_named("moderators")
+        .iter()
+        .flat_map(|n| n.children())
+        .map(|n| {
+            n.attrs()
+                .get("username")
+                .cloned()
+                .unwrap_or_else(|| n.text())
+        })
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    let count = |key: &str| attrs.get(key).and_then(|s| s.parse().ok());
+
+    Ok(ForumDetail {
+        forum,
+        topic_count: count("topics")
+            .or_else(|| doc.string_opt("/root/__ROWS").and_then(|s| s.parse().ok()))
+            .unwrap_or(0),
+        post_count: count("posts").unwrap_or(0),
+        moderators,
+        announcement: attrs
+            .get("announcement")
+            .or_else(|| attrs.get("rule"))
+            .filter(|s| !s.is_empty())
+            .map(|s| html_escape::decode_html_entities(s).into_owned()),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::testing::{has_param, serving_client, QueryRecorder};
+    use crate::cache::{CacheStorage, MemoryCache};
+    use crate::NGAClient;
 
     #[test]
     fn test_forum_id_kind_param() {
@@ -202,4 +430,212 @@ mod tests {
         let stid = ForumIdKind::stid("456");
         assert_eq!(stid.param_name(), "stid");
     }
+
+    #[test]
+    fn test_parse_categories_json() {
+        let data: Value = serde_json::from_str(
+            r#"{
+                "0": {"_id": "mmo", "name": "网络游戏", "groups": [
+                    {"name": "", "forums": {
+                        "0": {"fid": -7, "name": "网事杂谈", "info": "闲聊"},
+                        "1": {"fid": 310, "stid": "0", "name": "精英议会", "topped_topic": "123"}
+                    }}
+                ]},
+                "1": {"_id": "empty", "name": "Empty", "groups": []},
+                "2": {"name": "No ID"}
+            }"#,
+        )
+        .unwrap();
+
+        let categories = parse_categories_json(&data);
+        assert_eq!(categories.len(), 2);
+        assert_eq!(categories[0].id, "mmo");
+        assert_eq!(categories[0].forums.len(), 2);
+
+        let forum = &categories[0].forums[0];
+        assert_eq!(forum.name, "网事杂谈");
+        assert_eq!(forum.id_str(), Some("-7"));
+        assert_eq!(forum.info, "闲聊");
+
+        let forum = &categories[0].forums[1];
+        assert_eq!(forum.id, Some(ForumIdKind::fid("310")));
+        assert_eq!(forum.topped_topic_id, "123");
+        assert!(forum.icon_url.ends_with("310.png"));
+
+        assert!(categories[1].forums.is_empty());
+    }
+
+    #[test]
+    fn test_parse_category_forum_counts() {
+        let xml = "<root><item><_id>mmo</_id><name>网络游戏</name><groups><item><forums>\
+<item><fid>-7</fid><name>网事杂谈</name><topic_num>1203456</topic_num><today_num>8421</today_num></item>\
+<item><fid>310</fid><name>精英议会</name></item>\
+</forums></item></groups></item></root>";
+        let doc = XmlDocument::parse(xml).unwrap();
+        let node = doc.select_one("/root/item").unwrap().unwrap();
+
+        let category = parse_category(&node).unwrap().unwrap();
+        let forum = &category.forums[0];
+        assert_eq!(forum.topic_count, Some(1_203_456));
+        assert_eq!(forum.today_count, Some(8421));
+        assert_eq!(category.forums[1].topic_count, None);
+        assert_eq!(category.forums[1].today_count, None);
+
+        let data: Value = serde_json::from_str(
+            r#"[{"_id": "mmo", "name": "网络游戏", "groups": [{"forums": [
+                {"fid": 7, "name": "艾泽拉斯议事厅", "topics": 99, "today": "12"}
+            ]}]}]"#,
+        )
+        .unwrap();
+        let forum = &parse_categories_json(&data)[0].forums[0];
+        assert_eq!((forum.topic_count, forum.today_count), (Some(99), Some(12)));
+    }
+
+    fn forum(id: &str, name: &str, info: &str) -> Forum {
+        Forum {
+            info: info.into(),
+            ..Forum::minimal(ForumIdKind::fid(id), name)
+        }
+    }
+
+    fn category_tree() -> Vec<Category> {
+        vec![
+            Category {
+                id: "mmo".into(),
+                name: "网络游戏".into(),
+                forums: vec![
+                    forum("7", "艾泽拉斯议事厅", "魔兽世界综合讨论"),
+                    forum("310", "精英议会", "World of Warcraft raiding"),
+                    forum("-7", "网事杂谈", "闲聊"),
+                ],
+            },
+            Category {
+                id: "sub".into(),
+                name: "子版面".into(),
+                forums: vec![
+                    forum("459", "魔兽世界插件", "UI and addons"),
+                    forum("310", "精英议会", "World of Warcraft raiding"),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_find_forums() {
+        let categories = category_tree();
+        let names = |keyword: &str| -> Vec<String> {
+            find_forums(&categories, keyword)
+                .into_iter()
+                .map(|f| f.name)
+                .collect()
+        };
+
+        assert_eq!(names("魔兽世界"), vec!["艾泽拉斯议事厅", "魔兽世界插件"]);
+        assert_eq!(names("WARCRAFT"), vec!["精英议会"]);
+        assert_eq!(names(" addons "), vec!["魔兽世界插件"]);
+        assert_eq!(names("杂谈"), vec!["网事杂谈"]);
+        assert!(names("暗黑破坏神").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_local_uses_cached_categories() {
+        let cache: Arc<dyn CacheStorage> = Arc::new(MemoryCache::new());
+        cache
+            .set_json(CATEGORIES_CACHE_KEY, &category_tree(), None)
+            .await
+            .unwrap();
+        let recorder = Arc::new(QueryRecorder::default());
+        let client = NGAClient::builder()
+            .base_url("http://127.0.0.1:1/")
+            .cache(cache)
+            .observer(recorder.clone())
+            .build()
+            .unwrap();
+
+        let found = client.forums().find_local("插件").await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id_str(), Some("459"));
+        assert!(recorder.queries().is_empty());
+    }
+
+    #[test]
+    fn test_parse_forum_detail() {
+        let xml = r#"<root>
+<__ROWS>1520</__ROWS>
+<__F>
+<fid>310</fid>
+<name>Test Forum</name>
+<info>A forum for testing</info>
+<topped_topic>123</topped_topic>
+<topics>1520</topics>
+<posts>98765</posts>
+<moderators><item><uid>1</uid><username>alice</username></item><item>bob</item></moderators>
+<announcement>No spam &amp; be nice</announcement>
+</__F>
+</root>"#;
+
+        let detail = parse_forum_detail(xml, &ForumIdKind::fid("310")).unwrap();
+        assert_eq!(detail.forum.name, "Test Forum");
+        assert_eq!(detail.forum.info, "A forum for testing");
+        assert_eq!(detail.forum.id_str(), Some("310"));
+        assert_eq!(detail.topic_count, 1520);
+        assert_eq!(detail.post_count, 98765);
+        assert_eq!(detail.moderators, vec!["alice", "bob"]);
+        assert_eq!(detail.announcement.as_deref(), Some("No spam & be nice"));
+    }
+
+    #[tokio::test]
+    async fn test_announcement_fetches_topped_topic() {
+        let forum = "<root><__F><fid>310</fid><name>Test Forum</name>\
+<topped_topic>123</topped_topic></__F></root>";
+        let topic = "<root><__T><tid>123</tid><subject>Forum rules</subject></__T>\
+<__R><item><pid>0</pid><tid>123</tid><lou>0</lou><authorid>1</authorid>\
+<content>Be nice</content></item></__R></root>";
+        let (client, recorder) = serving_client(vec![forum, topic]);
+
+        let announcement = client
+            .forums()
+            .announcement(ForumIdKind::fid("310"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(announcement.topic.subject.content, "Forum rules");
+        assert_eq!(announcement.posts.len(), 1);
+
+        let queries = recorder.queries();
+        assert!(has_param(&queries[0], "fid", "310"));
+        assert!(has_param(&queries[1], "tid", "123"));
+    }
+
+    #[tokio::test]
+    async fn test_announcement_without_topped_topic() {
+        let forum = "<root><__F><fid>310</fid><name>Test Forum</name></__F></root>";
+        let (client, recorder) = serving_client(vec![forum]);
+
+        let announcement = client
+            .forums()
+            .announcement(ForumIdKind::fid("310"))
+            .await
+            .unwrap();
+        assert!(announcement.is_none());
+        assert_eq!(recorder.queries().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_requests() {
+        let forum = "<root><__F><fid>310</fid><name>Test Forum</name></__F></root>";
+        let (client, _) = serving_client(vec![forum; 3]);
+
+        for _ in 0..3 {
+            client.forums().info(ForumIdKind::fid("310")).await.unwrap();
+        }
+
+        let snapshot = client.metrics_snapshot().unwrap();
+        assert_eq!(snapshot.total_requests, 3);
+        assert_eq!(snapshot.total_errors, 0);
+        assert_eq!(snapshot.endpoints.len(), 1);
+        let endpoint = snapshot.endpoints.values().next().unwrap();
+        assert_eq!(endpoint.requests, 3);
+        assert_eq!(endpoint.buckets.iter().sum::<u64>(), 3);
+    }
 }
@@ -1,5 +1,7 @@
 //! Message API.
 
+use futures::stream::{self, StreamExt};
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use crate::{
@@ -35,6 +37,43 @@ impl MessageApi {
         parse_message_list(&xml, self.client.auth.as_ref().map(|a| a.uid.as_str()))
     }
 
+    /// Get every conversation in the inbox.
+    ///
+    /// Pages after the first are fetched with up to `concurrency` requests
+    /// in flight. Conversations keep page order, and ones that shift onto
+    /// the next page while fetching appear only once.
+    pub async fn list_all(&self, concurrency: usize) -> Result<Vec<ShortMessage>> {
+        let first = self.list(1).await?;
+        let rest: Vec<Result<MessageListResult>> = stream::iter(2..=first.total_pages)
+            .map(|page| self.list(page))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut seen = HashSet::new();
+        let mut conversations = first.conversations;
+        for page in rest {
+            conversations.extend(page?.conversations);
+        }
+        conversations.retain(|conv| seen.insert(conv.id.clone()));
+
+        Ok(conversations)
+    }
+
+    /// Find conversations whose subject or participant contains `keyword`.
+    ///
+    /// NGA has no message search endpoint, so this fetches the whole inbox
+    /// with `list_all` and filters it locally with `ShortMessage::matches`.
+    pub async fn search_local(
+        &self,
+        keyword: &str,
+        concurrency: usize,
+    ) -> Result<Vec<ShortMessage>> {
+        let mut conversations = self.list_all(concurrency).await?;
+        conversations.retain(|conv| conv.matches(keyword));
+        Ok(conversations)
+    }
+
     /// Get messages in a conversation.
     pub fn conversation(&self, mid: impl Into<String>) -> ConversationBuilder {
         ConversationBuilder {
@@ -65,6 +104,27 @@ impl MessageApi {
             reply_mid: Some(mid.into()),
         }
     }
+
+    /// Delete a conversation.
+    pub async fn delete(&self, mid: impl AsRef<str>) -> Result<()> {
+        let mid = mid.as_ref();
+        if mid.trim().is_empty() {
+            return Err(Error::InvalidArgument(
+                "Conversation ID cannot be empty".into(),
+            ));
+        }
+
+        let xml = self
+            .client
+            .post_authed(
+                "nuke.php",
+                &[("__lib", "pm"), ("__act", "del")],
+                &[("mid", mid)],
+            )
+            .await?;
+
+        parse_delete_response(&xml)
+    }
 }
 
 /// Result of message list request.
@@ -199,6 +259,20 @@ impl SendMessageBuilder {
     }
 }
 
+fn parse_delete_response(xml: &str) -> Result<()> {
+    let doc = XmlDocument::parse(xml)?;
+    if doc.string_opt("/root/data/item[1]").is_some() {
+        return Ok(());
+    }
+
+    let error = doc
+        .string_opt("/root/error/item[1]")
+        .or_else(|| doc.string_opt("/root/data/__MESSAGE"))
+        .or_else(|| doc.string_opt("/root/__MESSAGE"))
+        .unwrap_or_else(|| "Unknown error".to_owned());
+    Err(Error::from_nga("pm", error))
+}
+
 fn parse_message_list(xml: &str, current_uid: Option<&str>) -> Result<MessageListResult> {
     let doc = XmlDocument::parse(xml)?;
     let mut conversations = Vec::new();
@@ -209,12 +283,7 @@ fn parse_message_list(xml: &str, current_uid: Option<&str>) -> Result<MessageLis
         }
     }
 
-    let total_rows = doc.int_or("/root/__ROWS", 0) as u32;
-    let total_pages = if total_rows > 0 {
-        (total_rows + 19) / 20
-    } else {
-        1
-    };
+    let total_pages = doc.total_pages("/root/__ROWS_PAGE", 20);
 
     Ok(MessageListResult {
         conversations,
@@ -239,11 +308,35 @@ fn parse_conversation_item(
     let from_username = attrs.get("from_username").cloned().unwrap_or_default();
     let to_username = attrs.get("to_username").cloned().unwrap_or_default();
 
+    let mut participants = attrs
+        .get("all_user")
+        .map(|all| parse_participants(all))
+        .unwrap_or_default();
+    if participants.is_empty() {
+        for (uid, name) in [(&from_uid, &from_username), (&to_uid, &to_username)] {
+            if !uid.is_empty() && !participants.iter().any(|(id, _)| id.as_str() == uid) {
+                participants.push((uid.as_str().into(), name.clone()));
+            }
+        }
+    }
+
     let (other_user_id, other_username) = if Some(from_uid.as_str()) == current_uid {
         (to_uid, to_username)
     } else {
         (from_uid, from_username)
     };
+    let (other_user_id, other_username) = if other_user_id.is_empty() {
+        participants
+            .iter()
+            .find(|(id, _)| Some(id.as_str()) != current_uid)
+            .map(|(id, name)| (id.as_str().to_owned(), name.clone()))
+            .unwrap_or_default()
+    } else {
+        (other_user_id, other_username)
+    };
+
+    let is_unread = attrs.get("bit").map(|s| s == "1").unwrap_or(false);
+    let unread_count: Option<i32> = attrs.get("unread").and_then(|s| s.trim().parse().ok());
 
     let conv = ShortMessage {
         id,
@@ -251,13 +344,28 @@ fn parse_conversation_item(
         other_username,
         subject: attrs.get("subject").cloned().unwrap_or_default(),
         last_time: attrs.get("time").and_then(|s| s.parse().ok()).unwrap_or(0),
-        is_unread: attrs.get("bit").map(|s| s == "1").unwrap_or(false),
+        is_unread: is_unread || unread_count.is_some_and(|n| n > 0),
+        unread_count,
         message_count: attrs.get("count").and_then(|s| s.parse().ok()).unwrap_or(1),
+        participants,
     };
 
     Ok(Some(conv))
 }
 
+/// Parse an `all_user` list of tab-separated user ID and name pairs.
+fn parse_participants(all_user: &str) -> Vec<(UserId, String)> {
+    let fields: Vec<&str> = all_user.split('\t').map(str::trim).collect();
+    fields
+        .chunks(2)
+        .filter(|pair| !pair[0].is_empty())
+        .map(|pair| {
+            let name = pair.get(1).copied().unwrap_or_default();
+            (pair[0].into(), name.to_owned())
+        })
+        .collect()
+}
+
 fn parse_conversation(xml: &str, current_uid: Option<&str>) -> Result<ConversationResult> {
     let doc = XmlDocument::parse(xml)?;
     let mut messages = Vec::new();
@@ -274,12 +382,7 @@ fn parse_conversation(xml: &str, current_uid: Option<&str>) -> Result<Conversati
         }
     }
 
-    let total_rows = doc.int_or("/root/__ROWS", 0) as u32;
-    let total_pages = if total_rows > 0 {
-        (total_rows + 19) / 20
-    } else {
-        1
-    };
+    let total_pages = doc.total_pages("/root/__ROWS_PAGE", 20);
 
     Ok(ConversationResult {
         messages,
@@ -322,20 +425,115 @@ fn parse_message_post(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::testing::paging_client;
+
+    fn anonymous_client() -> Arc<NGAClientInner> {
+        Arc::new(NGAClientInner {
+            http: reqwest::Client::new(),
+            config: crate::client::HttpConfig::default(),
+            auth: None,
+            cache: None,
+            observer: None,
+            metrics: None,
+            refreshes: Default::default(),
+        })
+    }
 
     #[test]
     fn test_message_builder() {
         let _ = SendMessageBuilder {
-            client: Arc::new(crate::client::NGAClientInner {
-                http: reqwest::Client::new(),
-                config: crate::client::HttpConfig::default(),
-                auth: None,
-                cache: None,
-            }),
+            client: anonymous_client(),
             to_username: "test".into(),
             subject: "Hello".into(),
             content: "Hi".into(),
             reply_mid: None,
         };
     }
+
+    #[tokio::test]
+    async fn test_delete_validation() {
+        let api = MessageApi::new(anonymous_client());
+
+        let result = api.delete(" ").await;
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+
+        let result = api.delete("123").await;
+        assert!(matches!(result, Err(Error::AuthRequired)));
+    }
+
+    #[test]
+    fn test_parse_delete_response() {
+        assert!(parse_delete_response("<root><data><item>ok</item></data></root>").is_ok());
+
+        let result =
+            parse_delete_response("<root><error><item>No permission</item></error></root>");
+        assert!(matches!(result, Err(Error::NGAApi { message, .. }) if message == "No permission"));
+
+        assert!(parse_delete_response(
+            "<root><__MESSAGE>操作成功</__MESSAGE><data><item>ok</item></data></root>"
+        )
+        .is_ok());
+
+        let result = parse_delete_response("<root><__MESSAGE>消息不存在</__MESSAGE></root>");
+        assert!(matches!(result, Err(Error::NGAApi { message, .. }) if message == "消息不存在"));
+    }
+
+    #[test]
+    fn test_parse_group_conversation_item() {
+        let xml = "<root><data><item><mid>77</mid><subject>Raid night</subject>\
+<from_uid>200</from_uid><from_username>Bob</from_username><time>1700000000</time>\
+<count>12</count><bit>1</bit><unread>3</unread>\
+<all_user>100\tAlice\t200\tBob\t300\tCarol\t</all_user></item></data></root>";
+        let doc = XmlDocument::parse(xml).unwrap();
+        let node = doc.select_one("/root/data/item").unwrap().unwrap();
+
+        let conv = parse_conversation_item(&node, Some("100"))
+            .unwrap()
+            .unwrap();
+        let names: Vec<&str> = conv.participants.iter().map(|(_, n)| n.as_str()).collect();
+        assert_eq!(names, ["Alice", "Bob", "Carol"]);
+        assert_eq!(conv.participants[2].0.as_str(), "300");
+        assert!(conv.is_group());
+        assert_eq!(conv.unread_count, Some(3));
+        assert!(conv.is_unread);
+        assert_eq!(conv.message_count, 12);
+        assert_eq!(conv.other_user_id.as_str(), "200");
+        assert_eq!(conv.other_username, "Bob");
+    }
+
+    #[test]
+    fn test_parse_direct_conversation_item() {
+        let xml = "<root><data><item><mid>5</mid><from_uid>100</from_uid>\
+<from_username>Alice</from_username><to_uid>200</to_uid><to_username>Bob</to_username>\
+<bit>1</bit></item></data></root>";
+        let doc = XmlDocument::parse(xml).unwrap();
+        let node = doc.select_one("/root/data/item").unwrap().unwrap();
+
+        let conv = parse_conversation_item(&node, Some("100"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(conv.participants.len(), 2);
+        assert!(!conv.is_group());
+        assert!(conv.is_unread);
+        assert_eq!(conv.unread_count, None);
+        assert_eq!(conv.other_username, "Bob");
+    }
+
+    #[tokio::test]
+    async fn test_list_all_merges_pages_in_order() {
+        let (client, recorder) = paging_client(vec![
+            "<root><__ROWS>60</__ROWS><__ROWS_PAGE>20</__ROWS_PAGE><data>\
+<item><mid>1</mid></item><item><mid>2</mid></item></data></root>",
+            "<root><__ROWS>60</__ROWS><__ROWS_PAGE>20</__ROWS_PAGE><data>\
+<item><mid>2</mid></item><item><mid>3</mid></item></data></root>",
+            "<root><__ROWS>60</__ROWS><__ROWS_PAGE>20</__ROWS_PAGE><data>\
+<item><mid>4</mid></item></data></root>",
+        ]);
+
+        let conversations = client.messages().list_all(2).await.unwrap();
+
+        let ids: Vec<&str> = conversations.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "3", "4"]);
+        assert_eq!(recorder.queries().len(), 3);
+    }
 }
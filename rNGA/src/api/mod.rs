@@ -4,6 +4,8 @@ mod forum;
 mod message;
 mod notification;
 mod post;
+#[cfg(test)]
+mod testing;
 mod topic;
 mod user;
 
@@ -13,11 +15,13 @@ pub use message::{
 };
 pub use notification::{NotificationApi, NotificationListBuilder, NotificationListResult};
 pub use post::{
-    CommentBuilder, CommentResult, CommentsResult, PostApi, ReplyBuilder, ReplyResult, UserPost,
-    UserPostsResult, VoteResult,
+    CommentBuilder, CommentResult, CommentsResult, EditBuilder, EditResult, PostApi,
+    PostsByUserBuilder, ReplyBuilder, ReplyResult, UserPost, UserPostsResult, VoteResult,
 };
 pub use topic::{
     FavoriteTopicsBuilder, Subforum, TopicApi, TopicDetailsBuilder, TopicDetailsResult,
-    TopicListBuilder, TopicListResult, TopicSearchBuilder,
+    TopicListBuilder, TopicListResult, TopicPostStream, TopicSearchBuilder,
+};
+pub use user::{
+    Activity, Image, UserApi, UserSearchBuilder, UserSearchListResult, UserSearchResult,
 };
-pub use user::{UserApi, UserSearchResult};
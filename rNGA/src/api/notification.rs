@@ -2,6 +2,8 @@
 
 use std::sync::Arc;
 
+use futures::future;
+
 use crate::{
     client::NGAClientInner,
     error::Result,
@@ -39,9 +41,32 @@ impl NotificationApi {
             client: self.client.clone(),
             kind,
             page: 1,
+            unread_only: false,
+            topic_id: None,
         }
     }
 
+    /// Get one page of reply, quote, mention, comment and system
+    /// notifications together, newest first.
+    ///
+    /// Each type is fetched concurrently and its notifications keep their
+    /// `kind`.
+    pub async fn list_all_types(&self, page: u32) -> Result<Vec<Notification>> {
+        let results = future::try_join_all(
+            INBOX_TYPES
+                .iter()
+                .map(|&kind| self.list(kind).page(page).send()),
+        )
+        .await?;
+
+        let mut notifications: Vec<Notification> = results
+            .into_iter()
+            .flat_map(|result| result.notifications)
+            .collect();
+        notifications.sort_by_key(|n| std::cmp::Reverse(n.time));
+        Ok(notifications)
+    }
+
     /// Mark notification as read.
     pub async fn mark_read(&self, notification_id: impl AsRef<str>) -> Result<()> {
         self.client
@@ -77,11 +102,22 @@ impl NotificationApi {
     }
 }
 
+/// Notification types merged by [`NotificationApi::list_all_types`].
+const INBOX_TYPES: [NotificationType; 5] = [
+    NotificationType::Reply,
+    NotificationType::Quote,
+    NotificationType::Mention,
+    NotificationType::Comment,
+    NotificationType::System,
+];
+
 /// Builder for notification list requests.
 pub struct NotificationListBuilder {
     client: Arc<NGAClientInner>,
     kind: NotificationType,
     page: u32,
+    unread_only: bool,
+    topic_id: Option<TopicId>,
 }
 
 impl NotificationListBuilder {
@@ -91,6 +127,23 @@ impl NotificationListBuilder {
         self
     }
 
+    /// Only keep unread notifications.
+    ///
+    /// NGA returns read and unread notifications together, so this filters
+    /// the fetched page and may leave it shorter than usual.
+    pub fn unread_only(mut self, unread_only: bool) -> Self {
+        self.unread_only = unread_only;
+        self
+    }
+
+    /// Only keep notifications about the given topic.
+    ///
+    /// Like `unread_only`, this filters the fetched page.
+    pub fn topic(mut self, topic_id: impl Into<TopicId>) -> Self {
+        self.topic_id = Some(topic_id.into());
+        self
+    }
+
     /// Execute the request.
     pub async fn send(self) -> Result<NotificationListResult> {
         let page_str = self.page.to_string();
@@ -109,7 +162,19 @@ impl NotificationListBuilder {
             )
             .await?;
 
-        parse_notification_list(&xml, self.kind)
+        let mut result = parse_notification_list(&xml, self.kind)?;
+        result.page = self.page;
+        result.notifications.retain(|n| self.matches(n));
+        Ok(result)
+    }
+
+    /// Whether a notification passes the unread and topic filters.
+    fn matches(&self, notification: &Notification) -> bool {
+        (!self.unread_only || !notification.is_read)
+            && self
+                .topic_id
+                .as_ref()
+                .is_none_or(|tid| notification.topic_id.as_ref() == Some(tid))
     }
 }
 
@@ -147,12 +212,7 @@ fn parse_notification_list(xml: &str, kind: NotificationType) -> Result<Notifica
         }
     }
 
-    let total_rows = doc.int_or("/root/__ROWS", 0) as u32;
-    let total_pages = if total_rows > 0 {
-        (total_rows + 19) / 20
-    } else {
-        1
-    };
+    let total_pages = doc.total_pages("/root/__ROWS_PAGE", 20);
 
     Ok(NotificationListResult {
         notifications,
@@ -187,7 +247,7 @@ fn parse_notification(
     let from_user_id = attrs
         .get("from_uid")
         .or_else(|| attrs.get("2"))
-        .map(|s| UserId::new(s));
+        .map(UserId::new);
     let from_username = attrs
         .get("from_username")
         .or_else(|| attrs.get("3"))
@@ -202,7 +262,7 @@ fn parse_notification(
             .or_else(|| attrs.get("4"))
             .and_then(|s| s.parse().ok())
             .unwrap_or(0),
-        is_read: false,
+        is_read: parse_is_read(&attrs),
         topic_id,
         post_id,
         from_user_id,
@@ -212,31 +272,58 @@ fn parse_notification(
     Ok(Some(noti))
 }
 
+/// Pull topic and post IDs out of a notification link.
+///
+/// The link may be entity-escaped (`&amp;`) or percent-encoded, and may carry
+/// the IDs as query parameters (`read.php?tid=1&pid=2`), path segments
+/// (`/read/tid/1`) or a post anchor (`#pid2Anchor`).
 fn extract_ids_from_url(url: &str) -> (Option<TopicId>, Option<PostId>) {
     use lazy_static::lazy_static;
     use regex::Regex;
 
     lazy_static! {
-        static ref TID_RE: Regex = Regex::new(r"tid=(\d+)").unwrap();
-        static ref PID_RE: Regex = Regex::new(r"pid=(\d+)").unwrap();
+        static ref TID_RE: Regex = Regex::new(r"\btid[=/](\d+)").unwrap();
+        static ref PID_RE: Regex = Regex::new(r"(?:\bpid[=/]|#pid)(\d+)").unwrap();
     }
 
+    let unescaped = html_escape::decode_html_entities(url);
+    let url = percent_encoding::percent_decode_str(&unescaped).decode_utf8_lossy();
+
     let topic_id = TID_RE
-        .captures(url)
+        .captures(&url)
         .and_then(|c| c.get(1))
         .map(|m| TopicId::new(m.as_str()));
 
     let post_id = PID_RE
-        .captures(url)
+        .captures(&url)
         .and_then(|c| c.get(1))
         .map(|m| PostId::new(m.as_str()));
 
     (topic_id, post_id)
 }
 
+/// Read state from a `read`/`is_read` flag or an inverted `unread` flag.
+///
+/// Notifications without any of these are treated as unread.
+fn parse_is_read(attrs: &std::collections::HashMap<String, String>) -> bool {
+    let flag = |key: &str| {
+        attrs
+            .get(key)
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s != "0" && !s.eq_ignore_ascii_case("false"))
+    };
+
+    flag("read")
+        .or_else(|| flag("is_read"))
+        .or_else(|| flag("unread").map(|unread| !unread))
+        .unwrap_or(false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::testing::{authed_routing_client, has_param, paging_client};
 
     #[test]
     fn test_extract_ids_from_url() {
@@ -247,6 +334,125 @@ mod tests {
         assert_eq!(pid.unwrap().as_str(), "67890");
     }
 
+    #[test]
+    fn test_extract_ids_from_escaped_url() {
+        for url in [
+            "/read.php?tid&#61;12345&amp;pid&#61;67890",
+            "/read.php%3Ftid%3D12345%26pid%3D67890",
+        ] {
+            let (tid, pid) = extract_ids_from_url(url);
+            assert_eq!(tid.unwrap().as_str(), "12345", "{}", url);
+            assert_eq!(pid.unwrap().as_str(), "67890", "{}", url);
+        }
+
+        let (tid, _) = extract_ids_from_url("thread.php?stid=999");
+        assert!(tid.is_none());
+    }
+
+    #[test]
+    fn test_extract_ids_from_anchor_url() {
+        let (tid, pid) = extract_ids_from_url("/read.php?tid=12345&page=3#pid67890Anchor");
+        assert_eq!(tid.unwrap().as_str(), "12345");
+        assert_eq!(pid.unwrap().as_str(), "67890");
+
+        let (tid, pid) = extract_ids_from_url("/read/tid/12345/pid/67890");
+        assert_eq!(tid.unwrap().as_str(), "12345");
+        assert_eq!(pid.unwrap().as_str(), "67890");
+
+        let (tid, pid) = extract_ids_from_url("/read.php?pid=67890&opt=128");
+        assert!(tid.is_none());
+        assert_eq!(pid.unwrap().as_str(), "67890");
+    }
+
+    const MIXED_LIST: &str = "<root><__ROWS>4</__ROWS><data>\
+<item><id>1</id><url>read.php?tid=100&amp;pid=1</url><read>1</read></item>\
+<item><id>2</id><url>read.php?tid=100&amp;pid=2</url><read>0</read></item>\
+<item><id>3</id><url>read.php?tid=200&amp;pid=3</url><unread>1</unread></item>\
+<item><id>4</id><url>read.php?tid=200&amp;pid=4</url><is_read>1</is_read></item>\
+</data></root>";
+
+    #[test]
+    fn test_parse_is_read() {
+        let result = parse_notification_list(MIXED_LIST, NotificationType::Reply).unwrap();
+        let read: Vec<bool> = result.notifications.iter().map(|n| n.is_read).collect();
+        assert_eq!(read, vec![true, false, false, true]);
+    }
+
+    #[tokio::test]
+    async fn test_list_filters() {
+        let ids = |result: NotificationListResult| -> Vec<String> {
+            result.notifications.into_iter().map(|n| n.id).collect()
+        };
+
+        let (client, _) = paging_client(vec![MIXED_LIST]);
+        let unread = client
+            .notifications()
+            .list(NotificationType::Reply)
+            .unread_only(true)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(ids(unread), vec!["2", "3"]);
+
+        let (client, _) = paging_client(vec![MIXED_LIST]);
+        let unread_in_topic = client
+            .notifications()
+            .list(NotificationType::Reply)
+            .unread_only(true)
+            .topic("200")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(ids(unread_in_topic), vec!["3"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_all_types() {
+        let (client, recorder) = authed_routing_client(
+            "type",
+            vec![
+                (
+                    "reply",
+                    "<root><data><item><id>r1</id><time>300</time></item>\
+<item><id>r2</id><time>100</time></item></data></root>",
+                ),
+                (
+                    "quote",
+                    "<root><data><item><id>q1</id><time>250</time></item></data></root>",
+                ),
+                (
+                    "at",
+                    "<root><data><item><id>m1</id><time>400</time></item></data></root>",
+                ),
+                ("comment", "<root><data></data></root>"),
+                (
+                    "system",
+                    "<root><data><item><id>s1</id><time>200</time></item></data></root>",
+                ),
+            ],
+        );
+
+        let merged = client.notifications().list_all_types(2).await.unwrap();
+        let tagged: Vec<(&str, NotificationType, i64)> = merged
+            .iter()
+            .map(|n| (n.id.as_str(), n.kind, n.time))
+            .collect();
+        assert_eq!(
+            tagged,
+            vec![
+                ("m1", NotificationType::Mention, 400),
+                ("r1", NotificationType::Reply, 300),
+                ("q1", NotificationType::Quote, 250),
+                ("s1", NotificationType::System, 200),
+                ("r2", NotificationType::Reply, 100),
+            ]
+        );
+
+        let queries = recorder.queries();
+        assert_eq!(queries.len(), 5);
+        assert!(queries.iter().all(|q| has_param(q, "page", "2")));
+    }
+
     #[test]
     fn test_notification_type_param() {
         assert_eq!(NotificationType::Reply.param(), "reply");
@@ -2,6 +2,7 @@
 
 use std::sync::Arc;
 
+use super::user::{fetch_image, Image};
 use crate::{
     client::NGAClientInner,
     error::{Error, Result},
@@ -134,6 +135,21 @@ impl PostApi {
         }
     }
 
+    /// Edit an existing post.
+    ///
+    /// To change the topic subject, set the post's floor with
+    /// [`EditBuilder::floor`]; only the main post (floor 0) has one.
+    pub fn edit(&self, topic_id: impl Into<TopicId>, post_id: impl Into<PostId>) -> EditBuilder {
+        EditBuilder {
+            client: self.client.clone(),
+            topic_id: topic_id.into(),
+            post_id: post_id.into(),
+            floor: None,
+            content: String::new(),
+            subject: None,
+        }
+    }
+
     /// Fetch content for quoting a post.
     pub async fn fetch_quote_content(
         &self,
@@ -159,22 +175,58 @@ impl PostApi {
         Ok(html_escape::decode_html_entities(&content).into_owned())
     }
 
-    /// Get posts by a specific user.
-    pub async fn by_user(&self, user_id: impl AsRef<str>, page: u32) -> Result<UserPostsResult> {
-        let page_str = page.to_string();
+    /// Download an image, such as one from [`Post::all_image_urls`].
+    ///
+    /// [`Post::all_image_urls`]: crate::models::Post::all_image_urls
+    pub async fn fetch_image(&self, url: &str) -> Result<Image> {
+        fetch_image(&self.client, url).await
+    }
 
-        let xml = self
-            .client
-            .post(
-                "thread.php",
-                &[
-                    ("searchpost", "1"),
-                    ("authorid", user_id.as_ref()),
-                    ("page", &page_str),
-                ],
-                &[],
-            )
-            .await?;
+    /// Get posts by a specific user, across all forums by default.
+    pub fn by_user(&self, user_id: impl AsRef<str>) -> PostsByUserBuilder {
+        PostsByUserBuilder {
+            client: self.client.clone(),
+            user_id: user_id.as_ref().to_owned(),
+            page: 1,
+            forum_id: None,
+        }
+    }
+}
+
+/// Builder for a user's post list.
+pub struct PostsByUserBuilder {
+    client: Arc<NGAClientInner>,
+    user_id: String,
+    page: u32,
+    forum_id: Option<String>,
+}
+
+impl PostsByUserBuilder {
+    /// Set the page number.
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// Only include posts in this forum.
+    pub fn forum(mut self, fid: impl Into<String>) -> Self {
+        self.forum_id = Some(fid.into());
+        self
+    }
+
+    /// Execute the request.
+    pub async fn send(self) -> Result<UserPostsResult> {
+        let page_str = self.page.to_string();
+        let mut query = vec![
+            ("searchpost", "1"),
+            ("authorid", self.user_id.as_str()),
+            ("page", &page_str),
+        ];
+        if let Some(fid) = &self.forum_id {
+            query.push(("fid", fid));
+        }
+
+        let xml = self.client.post("thread.php", &query, &[]).await?;
 
         parse_user_posts(&xml)
     }
@@ -225,6 +277,7 @@ pub struct UserPost {
 }
 
 /// Builder for reply posts.
+#[derive(Clone)]
 pub struct ReplyBuilder {
     client: Arc<NGAClientInner>,
     topic_id: TopicId,
@@ -307,7 +360,23 @@ impl ReplyBuilder {
                 .string_opt("/root/data/__MESSAGE")
                 .or_else(|| doc.string_opt("/root/__MESSAGE"))
                 .unwrap_or_else(|| "Unknown error".to_owned());
-            Err(Error::nga("post", error))
+            Err(Error::from_nga("post", error))
+        }
+    }
+
+    /// Execute the request, waiting out NGA's flood control once.
+    ///
+    /// On `Error::FloodControl`, or `Error::RateLimited` with a known delay,
+    /// this sleeps for the suggested `retry_after` and sends again. Other
+    /// errors, and a second rejection, are returned as is.
+    pub async fn send_with_backoff(self) -> Result<ReplyResult> {
+        let result = self.clone().send().await;
+        match result.as_ref().err().and_then(Error::retry_after) {
+            Some(retry_after) => {
+                tokio::time::sleep(retry_after).await;
+                self.send().await
+            }
+            None => result,
         }
     }
 }
@@ -319,6 +388,96 @@ pub struct ReplyResult {
     pub post_id: PostId,
 }
 
+/// Builder for post edits.
+pub struct EditBuilder {
+    client: Arc<NGAClientInner>,
+    topic_id: TopicId,
+    post_id: PostId,
+    floor: Option<i32>,
+    content: String,
+    subject: Option<String>,
+}
+
+impl EditBuilder {
+    /// Set the new post content.
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.content = content.into();
+        self
+    }
+
+    /// Set the floor of the post being edited.
+    pub fn floor(mut self, floor: i32) -> Self {
+        self.floor = Some(floor);
+        self
+    }
+
+    /// Change the topic subject. Only allowed when editing the main post.
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Execute the request.
+    pub async fn send(self) -> Result<EditResult> {
+        if self.content.trim().is_empty() {
+            return Err(Error::InvalidArgument(
+                "Post content cannot be empty".into(),
+            ));
+        }
+
+        if let Some(subject) = &self.subject {
+            match self.floor {
+                Some(0) => {}
+                Some(_) => {
+                    return Err(Error::InvalidArgument(
+                        "Only the main post's subject can be edited".into(),
+                    ))
+                }
+                None => {
+                    return Err(Error::InvalidArgument(
+                        "Set the floor to edit the subject".into(),
+                    ))
+                }
+            }
+            if subject.trim().is_empty() {
+                return Err(Error::InvalidArgument("Subject cannot be empty".into()));
+            }
+        }
+
+        let mut form = vec![
+            ("tid", self.topic_id.as_str()),
+            ("pid", self.post_id.as_str()),
+            ("post_content", self.content.as_str()),
+        ];
+        if let Some(subject) = &self.subject {
+            form.push(("post_subject", subject.as_str()));
+        }
+
+        let xml = self
+            .client
+            .post_authed("post.php", &[("action", "modify")], &form)
+            .await?;
+
+        let doc = XmlDocument::parse(&xml)?;
+        if doc.string_opt("/root/data/item[1]").is_some() {
+            Ok(EditResult { success: true })
+        } else {
+            let error = doc
+                .string_opt("/root/data/__MESSAGE")
+                .or_else(|| doc.string_opt("/root/__MESSAGE"))
+                .unwrap_or_else(|| "Unknown error".to_owned());
+            Err(Error::from_nga("edit", error))
+        }
+    }
+}
+
+/// Result of a post edit.
+#[derive(Debug, Clone)]
+pub struct EditResult {
+    /// Whether the post was updated successfully.
+    pub success: bool,
+}
+
 /// Builder for comment posts.
 pub struct CommentBuilder {
     client: Arc<NGAClientInner>,
@@ -364,7 +523,7 @@ impl CommentBuilder {
             let error = doc
                 .string_opt("/root/__MESSAGE")
                 .unwrap_or_else(|| "Unknown error".to_owned());
-            Err(Error::nga("comment", error))
+            Err(Error::from_nga("comment", error))
         }
     }
 }
@@ -399,12 +558,7 @@ fn parse_comments(xml: &str) -> Result<CommentsResult> {
         }
     }
 
-    let total_pages = doc.int_or("/root/__ROWS", 0) as u32;
-    let total_pages = if total_pages > 0 {
-        (total_pages + 19) / 20
-    } else {
-        1
-    };
+    let total_pages = doc.total_pages("/root/__ROWS_PAGE", 20);
 
     Ok(CommentsResult {
         comments,
@@ -434,6 +588,16 @@ fn parse_light_post(node: &crate::parser::XmlNode<'_>) -> Result<Option<LightPos
     let content_raw = attrs.get("content").cloned().unwrap_or_default();
     let content = parse_content(&content_raw);
 
+    let count = |key: &str| attrs.get(key).and_then(|s| s.parse::<i32>().ok());
+    let vote = match (count("recommend"), count("against")) {
+        (None, None) => None,
+        (up, down) => Some(VoteState {
+            up: up.unwrap_or(0),
+            down: down.unwrap_or(0),
+            user_vote: None,
+        }),
+    };
+
     Ok(Some(LightPost {
         id: id.into(),
         author,
@@ -442,11 +606,15 @@ fn parse_light_post(node: &crate::parser::XmlNode<'_>) -> Result<Option<LightPos
             .get("postdate")
             .and_then(|s| s.parse().ok())
             .unwrap_or(0),
-        score: attrs.get("score").and_then(|s| s.parse().ok()).unwrap_or(0),
+        score: vote
+            .map(|v| v.net())
+            .or_else(|| count("score"))
+            .unwrap_or(0),
+        vote,
     }))
 }
 
-fn parse_user_posts(xml: &str) -> Result<UserPostsResult> {
+pub(super) fn parse_user_posts(xml: &str) -> Result<UserPostsResult> {
     let doc = XmlDocument::parse(xml)?;
     let mut posts = Vec::new();
 
@@ -467,12 +635,7 @@ fn parse_user_posts(xml: &str) -> Result<UserPostsResult> {
         }
     }
 
-    let total_rows = doc.int_or("/root/__ROWS", 0) as u32;
-    let total_pages = if total_rows > 0 {
-        (total_rows + 34) / 35
-    } else {
-        1
-    };
+    let total_pages = doc.total_pages("/root/__T__ROWS_PAGE", 35);
 
     Ok(UserPostsResult {
         posts,
@@ -484,10 +647,134 @@ fn parse_user_posts(xml: &str) -> Result<UserPostsResult> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::testing::{authed_serving_client, has_param, recording_client};
+
+    const EDITED: &str = "<root><data><item>Edited</item></data></root>";
+
+    #[tokio::test]
+    async fn test_edit_main_post_subject() {
+        let (client, recorder) = authed_serving_client(vec![EDITED]);
+
+        let result = client
+            .posts()
+            .edit("123", "0")
+            .floor(0)
+            .content("Updated body")
+            .subject("New title")
+            .send()
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        assert!(has_param(&recorder.first_query(), "action", "modify"));
+        let form = recorder.first_form();
+        assert!(has_param(&form, "post_subject", "New title"));
+        assert!(has_param(&form, "post_content", "Updated body"));
+    }
+
+    #[tokio::test]
+    async fn test_edit_reply_subject_rejected() {
+        let (client, recorder) = recording_client();
+
+        for floor in [Some(3), None] {
+            let mut edit = client.posts().edit("123", "456").content("Body");
+            if let Some(floor) = floor {
+                edit = edit.floor(floor);
+            }
+            let result = edit.subject("New title").send().await;
+            assert!(matches!(result, Err(Error::InvalidArgument(_))));
+        }
+        assert!(recorder.queries().is_empty());
+
+        let (client, recorder) = authed_serving_client(vec![EDITED]);
+        let result = client
+            .posts()
+            .edit("123", "456")
+            .floor(3)
+            .content("Body")
+            .send()
+            .await;
+        assert!(result.is_ok());
+        assert!(!recorder
+            .first_form()
+            .iter()
+            .any(|(k, _)| k == "post_subject"));
+    }
 
     #[test]
     fn test_vote_param() {
         assert_eq!(Vote::Up.param(), "1");
         assert_eq!(Vote::Down.param(), "0");
     }
+
+    #[test]
+    fn test_parse_hot_reply_vote_counts() {
+        let xml = r#"<root><data>
+            <item pid="1" authorid="10" author="a" content="hot" postdate="100" recommend="12" against="3"/>
+            <item pid="2" authorid="11" author="b" content="old" postdate="101" score="5"/>
+        </data></root>"#;
+
+        let replies = parse_hot_replies(xml).unwrap();
+        assert_eq!(replies.len(), 2);
+
+        let vote = replies[0].vote.unwrap();
+        assert_eq!((vote.up, vote.down), (12, 3));
+        assert_eq!(replies[0].score, 9);
+
+        assert!(replies[1].vote.is_none());
+        assert_eq!(replies[1].score, 5);
+    }
+
+    #[tokio::test]
+    async fn test_by_user_forum_filter() {
+        let (client, recorder) = recording_client();
+
+        let _ = client.posts().by_user("42").send().await;
+        let _ = client
+            .posts()
+            .by_user("42")
+            .forum("310")
+            .page(2)
+            .send()
+            .await;
+
+        let queries = recorder.queries();
+        assert!(has_param(&queries[0], "authorid", "42"));
+        assert!(!queries[0].iter().any(|(k, _)| k == "fid"));
+        assert!(has_param(&queries[1], "fid", "310"));
+        assert!(has_param(&queries[1], "page", "2"));
+    }
+
+    const FLOOD: &str =
+        "<root><data><__MESSAGE>你发帖太快了，请 0 秒后再试</__MESSAGE></data></root>";
+    const REPLIED: &str = "<root><data><item>456</item></data></root>";
+
+    #[tokio::test]
+    async fn test_reply_backoff_retries_once() {
+        let (client, recorder) = authed_serving_client(vec![FLOOD, REPLIED]);
+
+        let result = client
+            .posts()
+            .reply("123")
+            .content("hello")
+            .send_with_backoff()
+            .await
+            .unwrap();
+        assert_eq!(result.post_id.as_str(), "456");
+        assert_eq!(recorder.queries().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reply_backoff_gives_up_after_one_retry() {
+        let (client, recorder) = authed_serving_client(vec![FLOOD, FLOOD]);
+
+        let result = client
+            .posts()
+            .reply("123")
+            .content("hello")
+            .send_with_backoff()
+            .await;
+        assert!(matches!(result, Err(Error::FloodControl { .. })));
+        assert_eq!(recorder.queries().len(), 2);
+    }
 }
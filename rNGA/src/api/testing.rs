@@ -0,0 +1,131 @@
+//! Test helpers for inspecting outgoing API requests.
+
+use std::sync::{Arc, Mutex};
+
+use crate::testing::{closed_base_url, serve, serve_bodies, Routes};
+use crate::{NGAClient, RequestObserver};
+
+/// Observer recording the query and form of every request.
+#[derive(Default)]
+pub(crate) struct QueryRecorder {
+    queries: Mutex<Vec<Vec<(String, String)>>>,
+    forms: Mutex<Vec<Vec<(String, String)>>>,
+}
+
+impl QueryRecorder {
+    /// Query of the first recorded request.
+    pub(crate) fn first_query(&self) -> Vec<(String, String)> {
+        self.queries.lock().unwrap()[0].clone()
+    }
+
+    /// Queries of all recorded requests, in order.
+    pub(crate) fn queries(&self) -> Vec<Vec<(String, String)>> {
+        self.queries.lock().unwrap().clone()
+    }
+
+    /// Form of the first recorded request, with credentials redacted.
+    pub(crate) fn first_form(&self) -> Vec<(String, String)> {
+        self.forms.lock().unwrap()[0].clone()
+    }
+}
+
+impl RequestObserver for QueryRecorder {
+    fn on_request(&self, _api: &str, query: &[(&str, &str)], form: &[(&str, &str)]) {
+        let owned = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+                .collect()
+        };
+        self.queries.lock().unwrap().push(owned(query));
+        self.forms.lock().unwrap().push(owned(form));
+    }
+}
+
+/// Client pointed at a closed local port.
+///
+/// Requests are observed and then fail with a network error.
+pub(crate) fn recording_client() -> (NGAClient, Arc<QueryRecorder>) {
+    client_at(closed_base_url())
+}
+
+/// Client pointed at a local server answering with `bodies`, one per request.
+pub(crate) fn serving_client(bodies: Vec<&'static str>) -> (NGAClient, Arc<QueryRecorder>) {
+    client_at(serve_bodies(bodies))
+}
+
+/// Logged-in `serving_client`, for endpoints that require auth.
+pub(crate) fn authed_serving_client(bodies: Vec<&'static str>) -> (NGAClient, Arc<QueryRecorder>) {
+    authed_client_at(serve_bodies(bodies))
+}
+
+/// Logged-in client pointed at a local server answering each request with
+/// `pages[page - 1]`, where `page` is the request's `page` parameter.
+///
+/// Unlike `serving_client`, responses don't depend on the order in which
+/// concurrent requests arrive.
+pub(crate) fn paging_client(pages: Vec<&'static str>) -> (NGAClient, Arc<QueryRecorder>) {
+    let routes = pages
+        .into_iter()
+        .enumerate()
+        .map(|(i, body)| ((i + 1).to_string(), body))
+        .collect();
+    authed_client_at(serve_routes("page", routes))
+}
+
+/// Client pointed at a local server answering each request with the body
+/// routed to the value of its `param` query parameter.
+pub(crate) fn routing_client(
+    param: &'static str,
+    routes: Vec<(&'static str, &'static str)>,
+) -> (NGAClient, Arc<QueryRecorder>) {
+    let routes = routes
+        .into_iter()
+        .map(|(value, body)| (value.to_owned(), body))
+        .collect();
+    client_at(serve_routes(param, routes))
+}
+
+/// Logged-in `routing_client`, for endpoints that require auth.
+pub(crate) fn authed_routing_client(
+    param: &'static str,
+    routes: Vec<(&'static str, &'static str)>,
+) -> (NGAClient, Arc<QueryRecorder>) {
+    let routes = routes
+        .into_iter()
+        .map(|(value, body)| (value.to_owned(), body))
+        .collect();
+    authed_client_at(serve_routes(param, routes))
+}
+
+/// Serve one request per route, picking the body by the `param` query value.
+fn serve_routes(param: &'static str, routes: Vec<(String, &'static str)>) -> String {
+    serve(Routes::ByParam(param, routes), "200 OK", "")
+}
+
+fn client_at(base_url: String) -> (NGAClient, Arc<QueryRecorder>) {
+    let recorder = Arc::new(QueryRecorder::default());
+    let client = NGAClient::builder()
+        .base_url(base_url)
+        .observer(recorder.clone())
+        .metrics(true)
+        .build()
+        .unwrap();
+    (client, recorder)
+}
+
+fn authed_client_at(base_url: String) -> (NGAClient, Arc<QueryRecorder>) {
+    let recorder = Arc::new(QueryRecorder::default());
+    let client = NGAClient::builder()
+        .base_url(base_url)
+        .auth("token", "1")
+        .observer(recorder.clone())
+        .build()
+        .unwrap();
+    (client, recorder)
+}
+
+/// Whether a recorded query contains the given parameter.
+pub(crate) fn has_param(query: &[(String, String)], key: &str, value: &str) -> bool {
+    query.iter().any(|(k, v)| k == key && v == value)
+}
@@ -1,15 +1,22 @@
 //! Topic API.
 
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::{
+    cache::{read_offline, stale_while_revalidate},
     client::NGAClientInner,
     error::{Error, Result},
     models::{
-        Attachment, AttachmentKind, FavoriteFolder, FavoriteTopicOp, Forum, ForumIdKind, Post,
-        SearchTimeRange, Subject, Topic, TopicId, TopicOrder, TopicType, User, UserName,
+        anon_context, Attachment, AttachmentKind, Debate, DebateSide, FavoriteFolder,
+        FavoriteTopicOp, Forum, ForumIdKind, Post, ReadOptions, SearchTimeRange, Subject, Topic,
+        TopicId, TopicOrder, TopicType, ToplistScope, User, UserName,
     },
-    parser::{parse_subject, XmlDocument, XmlNode},
+    parser::{find_element, find_element_path, parse_subject, XmlDocument, XmlNode},
 };
 
 /// API for topic operations.
@@ -30,9 +37,41 @@ impl TopicApi {
             page: 1,
             order: TopicOrder::default(),
             recommended_only: false,
+            subforum: None,
         }
     }
 
+    /// Get every page of a forum's topic list, merged into one result.
+    ///
+    /// Pages after the first are fetched with up to `concurrency` requests
+    /// in flight. Topics keep page order, and topics repeated across pages,
+    /// such as pinned ones, appear only once.
+    pub async fn list_all(
+        &self,
+        forum_id: ForumIdKind,
+        order: TopicOrder,
+        concurrency: usize,
+    ) -> Result<TopicListResult> {
+        let list = |page: u32| self.list(forum_id.clone()).order(order).page(page).send();
+
+        let mut result = list(1).await?;
+        let rest: Vec<Result<TopicListResult>> = stream::iter(2..=result.total_pages)
+            .map(list)
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut seen = HashSet::new();
+        let mut topics = std::mem::take(&mut result.topics);
+        for page in rest {
+            topics.extend(page?.topics);
+        }
+        topics.retain(|topic| seen.insert(topic.id.clone()));
+        result.topics = topics;
+
+        Ok(result)
+    }
+
     /// Get topic details and posts.
     pub fn details(&self, topic_id: impl Into<TopicId>) -> TopicDetailsBuilder {
         TopicDetailsBuilder {
@@ -42,8 +81,67 @@ impl TopicApi {
             fav: None,
             post_id: None,
             author_id: None,
-            anonymous_only: false,
+            options: ReadOptions::empty(),
+            goto_unread: false,
+            timeout: None,
+        }
+    }
+
+    /// Get topic metadata without parsing its posts.
+    ///
+    /// Only the `__T` node of the first page is parsed, which is cheaper than
+    /// `details` when refreshing many topics at once.
+    pub async fn metadata(&self, topic_id: impl Into<TopicId>) -> Result<Topic> {
+        let topic_id = topic_id.into();
+
+        let xml = self
+            .client
+            .post(
+                "read.php",
+                &[("tid", topic_id.as_str()), ("page", "1")],
+                &[],
+            )
+            .await?;
+
+        parse_topic_metadata_response(&xml, &topic_id)
+    }
+
+    /// Get replies posted after a known floor, oldest first.
+    ///
+    /// Starts from the last page and walks backward until the page holding
+    /// `last_floor + 1` is reached, so the result covers the whole gap.
+    /// Always fetched from the server, bypassing the cache.
+    pub async fn new_replies_since(
+        &self,
+        topic_id: impl Into<TopicId>,
+        last_floor: i32,
+    ) -> Result<Vec<Post>> {
+        let topic_id = topic_id.into();
+        collect_new_replies(last_floor, |page| self.fetch_page(&topic_id, page)).await
+    }
+
+    /// Fetch one page of a topic, or the last page when `page` is `None`.
+    async fn fetch_page(
+        &self,
+        topic_id: &TopicId,
+        page: Option<u32>,
+    ) -> Result<TopicDetailsResult> {
+        let page_str = page.map_or_else(|| LAST_PAGE.to_owned(), |p| p.to_string());
+
+        let xml = self
+            .client
+            .post(
+                "read.php",
+                &[("tid", topic_id.as_str()), ("page", &page_str)],
+                &[],
+            )
+            .await?;
+
+        let mut result = parse_topic_details_response(&xml, topic_id, page.unwrap_or(0))?;
+        if result.page == 0 {
+            result.page = result.total_pages;
         }
+        Ok(result)
     }
 
     /// Search topics in a forum.
@@ -126,6 +224,26 @@ impl TopicApi {
         Ok(())
     }
 
+    /// Vote for a side of a debate topic, returning the updated tallies.
+    pub async fn vote_debate(
+        &self,
+        topic_id: impl Into<TopicId>,
+        side: DebateSide,
+    ) -> Result<Debate> {
+        let topic_id = topic_id.into();
+
+        let xml = self
+            .client
+            .post_authed(
+                "nuke.php",
+                &[("__lib", "vote"), ("__act", "debate")],
+                &[("tid", topic_id.as_str()), ("side", side.param())],
+            )
+            .await?;
+
+        parse_debate_vote_response(&xml)
+    }
+
     /// Get topics posted by a specific user.
     pub async fn by_user(&self, user_id: impl AsRef<str>, page: u32) -> Result<TopicListResult> {
         let page_str = page.to_string();
@@ -138,10 +256,45 @@ impl TopicApi {
             )
             .await?;
 
-        parse_topic_list_response(&xml)
+        parse_topic_list_response(&xml, page)
+    }
+
+    /// Get the hot topics of the day, highest ranked first.
+    ///
+    /// Experimental: the endpoint and its response format haven't been
+    /// confirmed against the live site, so this may change or break.
+    #[doc(hidden)]
+    pub async fn toplist(&self, scope: ToplistScope) -> Result<Vec<Topic>> {
+        let mut query = vec![
+            ("__lib", "load_topic"),
+            ("__act", "load_topic_reply_ladder2"),
+            ("opt", "1"),
+        ];
+        match &scope {
+            ToplistScope::Global => query.push(("all", "1")),
+            ToplistScope::Forum(forum_id) => query.push((forum_id.param_name(), forum_id.id())),
+        }
+
+        let xml = self.client.post("nuke.php", &query, &[]).await?;
+
+        parse_toplist_response(&xml)
     }
 }
 
+/// Parse toplist topics, which NGA lists in rank order.
+fn parse_toplist_response(xml: &str) -> Result<Vec<Topic>> {
+    let doc = XmlDocument::parse(xml)?;
+    let mut topics = Vec::new();
+
+    for node in doc.select("/root/data/item/item")? {
+        if let Some(topic) = parse_topic(&node)? {
+            topics.push(topic);
+        }
+    }
+
+    Ok(topics)
+}
+
 /// Builder for topic list requests.
 pub struct TopicListBuilder {
     client: Arc<NGAClientInner>,
@@ -149,6 +302,7 @@ pub struct TopicListBuilder {
     page: u32,
     order: TopicOrder,
     recommended_only: bool,
+    subforum: Option<String>,
 }
 
 impl TopicListBuilder {
@@ -170,26 +324,35 @@ impl TopicListBuilder {
         self
     }
 
+    /// Only list topics of one subforum.
+    ///
+    /// Takes a `Subforum::filter_id` from the `subforums` of an unfiltered
+    /// list of the same forum.
+    pub fn subforum(mut self, filter_id: impl Into<String>) -> Self {
+        self.subforum = Some(filter_id.into());
+        self
+    }
+
     /// Execute the request.
     pub async fn send(self) -> Result<TopicListResult> {
         let page_str = self.page.to_string();
         let recommend_str = if self.recommended_only { "1" } else { "" };
 
-        let xml = self
-            .client
-            .post(
-                "thread.php",
-                &[
-                    (self.forum_id.param_name(), self.forum_id.id()),
-                    ("page", &page_str),
-                    ("order_by", self.order.param()),
-                    ("recommend", recommend_str),
-                ],
-                &[],
-            )
-            .await?;
+        let mut query = vec![(self.forum_id.param_name(), self.forum_id.id())];
+        if let Some(filter_id) = &self.subforum {
+            // The subforum filter takes precedence over a stid forum ID.
+            query.retain(|&(k, _)| k != "stid");
+            query.push(("stid", filter_id));
+        }
+        query.extend([
+            ("page", page_str.as_str()),
+            ("order_by", self.order.param()),
+            ("recommend", recommend_str),
+        ]);
 
-        let mut result = parse_topic_list_response(&xml)?;
+        let xml = self.client.post("thread.php", &query, &[]).await?;
+
+        let mut result = parse_topic_list_response(&xml, self.page)?;
 
         let doc = XmlDocument::parse(&xml)?;
         for node in doc.select("/root/__F/sub_forums/*")? {
@@ -215,9 +378,11 @@ pub struct TopicListResult {
     pub forum: Option<Forum>,
     /// Subforums.
     pub subforums: Vec<Subforum>,
+    /// Total number of topics across all pages, from `__ROWS`.
+    pub total_topics: u32,
     /// Total number of pages.
     pub total_pages: u32,
-    /// Current page.
+    /// Current page, as reported by the server.
     pub page: u32,
 }
 
@@ -242,7 +407,9 @@ pub struct TopicDetailsBuilder {
     fav: Option<String>,
     post_id: Option<String>,
     author_id: Option<String>,
-    anonymous_only: bool,
+    options: ReadOptions,
+    goto_unread: bool,
+    timeout: Option<Duration>,
 }
 
 impl TopicDetailsBuilder {
@@ -271,38 +438,252 @@ impl TopicDetailsBuilder {
     }
 
     /// Only show anonymous posts.
+    ///
+    /// Can be combined with [`author`](Self::author) and other read options.
     pub fn anonymous_only(mut self, only: bool) -> Self {
-        self.anonymous_only = only;
+        self.options.set(ReadOptions::ANONYMOUS_ONLY, only);
+        self
+    }
+
+    /// Replace all read options; `ReadOptions::empty()` shows every post.
+    pub fn options(mut self, options: ReadOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Jump to the page holding the first unread post.
+    ///
+    /// The explicit page is ignored and resolved by the server from the
+    /// current user's read position.
+    pub fn goto_unread(mut self, goto: bool) -> Self {
+        self.goto_unread = goto;
+        self
+    }
+
+    /// Override the client timeout for this request only.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
         self
     }
 
     /// Execute the request.
+    ///
+    /// With a cache configured, plain page reads are served from cache and
+    /// refreshed in the background once older than `DETAILS_SOFT_TTL`. In
+    /// offline mode they are served from cache only.
     pub async fn send(self) -> Result<TopicDetailsResult> {
+        let cache = match &self.client.cache {
+            Some(cache) if self.is_cacheable() => cache.clone(),
+            _ => return self.fetch().await,
+        };
+        let key = format!(
+            "topic:{}:{}:{}",
+            self.topic_id.as_str(),
+            self.page,
+            self.author_id.as_deref().unwrap_or("")
+        );
+
+        let config = &self.client.config;
+        if config.offline {
+            return read_offline(cache.as_ref(), &key).await;
+        }
+
+        let fallback = config.cache_fallback;
+        let refreshes = self.client.refreshes.clone();
+        stale_while_revalidate(
+            cache,
+            &refreshes,
+            key,
+            DETAILS_SOFT_TTL,
+            DETAILS_HARD_TTL,
+            fallback,
+            move || self.fetch(),
+        )
+        .await
+    }
+
+    /// Whether the page is fully identified by topic, page and author.
+    fn is_cacheable(&self) -> bool {
+        !self.goto_unread && self.options.is_empty() && self.post_id.is_none() && self.fav.is_none()
+    }
+
+    /// Execute the request, parsing posts one at a time as they are consumed.
+    ///
+    /// Only the page header (topic and users) is parsed up front; no DOM of the
+    /// whole page is built and the posts are never collected, so each can be
+    /// processed and dropped. The cache is bypassed.
+    pub async fn stream_posts(self) -> Result<TopicPostStream> {
+        let xml = self.fetch_xml().await?;
+        TopicPostStream::new(xml, &self.topic_id)
+    }
+
+    async fn fetch(self) -> Result<TopicDetailsResult> {
+        let xml = self.fetch_xml().await?;
+        parse_topic_details_response(&xml, &self.topic_id, self.page)
+    }
+
+    async fn fetch_xml(&self) -> Result<String> {
         let page_str = self.page.to_string();
-        let opt = if self.anonymous_only { "512" } else { "" };
+        let opt = self.options.param();
 
-        let xml = self
-            .client
-            .post(
-                "read.php",
-                &[
-                    ("tid", self.topic_id.as_str()),
-                    ("page", &page_str),
-                    ("fav", self.fav.as_deref().unwrap_or("")),
-                    ("pid", self.post_id.as_deref().unwrap_or("")),
-                    ("authorid", self.author_id.as_deref().unwrap_or("")),
-                    ("opt", opt),
-                ],
-                &[],
-            )
-            .await?;
+        let mut query = vec![("tid", self.topic_id.as_str())];
+        if self.goto_unread {
+            query.push(("fromread", "1"));
+        } else {
+            query.push(("page", &page_str));
+        }
+        if let Some(fav) = &self.fav {
+            query.push(("fav", fav));
+        }
+        if let Some(post_id) = &self.post_id {
+            query.push(("pid", post_id));
+        }
+        if let Some(author_id) = &self.author_id {
+            query.push(("authorid", author_id));
+        }
+        if !self.options.is_empty() {
+            query.push(("opt", &opt));
+        }
+
+        self.client
+            .post_with_timeout("read.php", &query, &[], self.timeout)
+            .await
+    }
+}
+
+/// Posts of a topic page, parsed lazily from its `__R` items.
+///
+/// Returned by [`TopicDetailsBuilder::stream_posts`]. A post list that is
+/// cut off or malformed ends with an `Error::Xml` item.
+pub struct TopicPostStream {
+    topic: Topic,
+    users: HashMap<String, User>,
+    xml: String,
+    pos: usize,
+    end: usize,
+}
+
+impl TopicPostStream {
+    fn new(xml: String, topic_id: &TopicId) -> Result<Self> {
+        let (pos, end) = find_element_path(&xml, &["root", "__R"])
+            .map(|span| (span.inner.start, span.inner.end))
+            .unwrap_or((0, 0));
+        let header = format!("{}{}", &xml[..pos], &xml[end..]);
+
+        let doc = parse_details_document(&header, topic_id)?;
+        let users = parse_details_users(&doc)?;
+        let topic = parse_details_topic(&doc, topic_id)?;
+
+        Ok(Self {
+            topic,
+            users,
+            xml,
+            pos,
+            end,
+        })
+    }
 
-        parse_topic_details_response(&xml, self.page)
+    /// The topic the posts belong to.
+    pub fn topic(&self) -> &Topic {
+        &self.topic
     }
 }
 
+impl Iterator for TopicPostStream {
+    type Item = Result<Post>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(item) = find_element(&self.xml, self.pos..self.end, "item") else {
+                // Anything but whitespace left means the post list was cut off
+                // or malformed, not that it ended.
+                let rest = &self.xml[self.pos..self.end];
+                self.pos = self.end;
+                return (!rest.trim().is_empty())
+                    .then(|| Err(Error::Xml("Truncated or malformed post list".into())));
+            };
+            self.pos = item.outer.end;
+
+            let parsed =
+                XmlDocument::parse_fragment(&self.xml[item.outer]).and_then(|doc| {
+                    match doc.select_one("/item")? {
+                        Some(node) => parse_post(&node, &self.users),
+                        None => Ok(None),
+                    }
+                });
+            match parsed {
+                Ok(Some(post)) => return Some(Ok(post)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// `page` value that makes `read.php` return the last page.
+const LAST_PAGE: &str = "e";
+
+/// Whether an NGA error message says the topic was deleted or never existed.
+///
+/// Only phrases naming the topic count, since NGA uses the same wording for
+/// missing users, attachments and the like.
+fn is_topic_gone(message: &str) -> bool {
+    const MARKERS: &[&str] = &[
+        "帖子不存在",
+        "主题不存在",
+        "帖子已被删除",
+        "主题已被删除",
+        "topic does not exist",
+        "topic was deleted",
+    ];
+
+    let lower = message.to_lowercase();
+    MARKERS.iter().any(|m| lower.contains(m))
+}
+
+/// Walk pages backward from the last one, keeping posts after `last_floor`.
+///
+/// `fetch(None)` must return the last page and `fetch(Some(n))` page `n`.
+async fn collect_new_replies<F, Fut>(last_floor: i32, mut fetch: F) -> Result<Vec<Post>>
+where
+    F: FnMut(Option<u32>) -> Fut,
+    Fut: Future<Output = Result<TopicDetailsResult>>,
+{
+    let mut pages = Vec::new();
+    let mut result = fetch(None).await?;
+
+    loop {
+        let page = result.page;
+        let covered = page <= 1
+            || result
+                .posts
+                .first()
+                .is_none_or(|p| p.floor <= last_floor + 1);
+
+        pages.push(
+            result
+                .posts
+                .into_iter()
+                .filter(|p| p.floor > last_floor)
+                .collect::<Vec<_>>(),
+        );
+
+        if covered {
+            break;
+        }
+        result = fetch(Some(page - 1)).await?;
+    }
+
+    Ok(pages.into_iter().rev().flatten().collect())
+}
+
+/// Age after which a cached topic page is refreshed in the background.
+const DETAILS_SOFT_TTL: Duration = Duration::from_secs(60);
+/// Age after which a cached topic page is discarded.
+const DETAILS_HARD_TTL: Duration = Duration::from_secs(30 * 60);
+
 /// Result of a topic details request.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TopicDetailsResult {
     /// The topic.
     pub topic: Topic,
@@ -312,8 +693,12 @@ pub struct TopicDetailsResult {
     pub forum_name: String,
     /// Total number of pages.
     pub total_pages: u32,
-    /// Current page.
+    /// Current page, as resolved by the server.
     pub page: u32,
+    /// Floor of the first unread post, if the server reported a read position.
+    pub first_unread_floor: Option<i32>,
+    /// Vote tallies, for debate topics.
+    pub debate: Option<Debate>,
 }
 
 /// Builder for topic search requests.
@@ -373,7 +758,7 @@ impl TopicSearchBuilder {
             )
             .await?;
 
-        parse_topic_list_response(&xml)
+        parse_topic_list_response(&xml, self.page)
     }
 }
 
@@ -407,11 +792,11 @@ impl FavoriteTopicsBuilder {
             .post_authed("thread.php", &[("favor", folder), ("page", &page_str)], &[])
             .await?;
 
-        parse_topic_list_response(&xml)
+        parse_topic_list_response(&xml, self.page)
     }
 }
 
-fn parse_topic_list_response(xml: &str) -> Result<TopicListResult> {
+pub(super) fn parse_topic_list_response(xml: &str, page: u32) -> Result<TopicListResult> {
     let doc = XmlDocument::parse(xml)?;
     let mut topics = Vec::new();
 
@@ -421,31 +806,34 @@ fn parse_topic_list_response(xml: &str) -> Result<TopicListResult> {
         }
     }
 
-    let total_pages = parse_pages(&doc, "/root/__ROWS", "/root/__T__ROWS_PAGE", 35)?;
+    let total_pages = doc.total_pages("/root/__T__ROWS_PAGE", 35);
+    let total_topics = doc
+        .string_opt("/root/__ROWS")
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(topics.len() as u32);
+    let page = doc
+        .string_opt("/root/__PAGE")
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(page);
 
     Ok(TopicListResult {
         topics,
         forum: None,
         subforums: Vec::new(),
+        total_topics,
         total_pages,
-        page: 1,
+        page,
     })
 }
 
-fn parse_topic_details_response(xml: &str, page: u32) -> Result<TopicDetailsResult> {
-    let doc = XmlDocument::parse(xml)?;
-
-    let mut users = std::collections::HashMap::new();
-    for node in doc.select("/root/__U/item")? {
-        if let Some(user) = parse_user(&node)? {
-            users.insert(user.id.0.clone(), user);
-        }
-    }
-
-    let topic = doc
-        .select_one("/root/__T")?
-        .and_then(|n| parse_topic(&n).ok().flatten())
-        .ok_or_else(|| Error::missing("topic"))?;
+fn parse_topic_details_response(
+    xml: &str,
+    topic_id: &TopicId,
+    page: u32,
+) -> Result<TopicDetailsResult> {
+    let doc = parse_details_document(xml, topic_id)?;
+    let users = parse_details_users(&doc)?;
+    let topic = parse_details_topic(&doc, topic_id)?;
 
     let mut posts = Vec::new();
     for node in doc.select("/root/__R/item")? {
@@ -459,7 +847,20 @@ fn parse_topic_details_response(xml: &str, page: u32) -> Result<TopicDetailsResu
         .or_else(|| doc.string_opt("/root/__F"))
         .unwrap_or_default();
 
-    let total_pages = parse_pages(&doc, "/root/__ROWS", "/root/__R__ROWS_PAGE", 20)?;
+    let total_pages = doc.total_pages("/root/__R__ROWS_PAGE", 20);
+    let page = doc
+        .string_opt("/root/__PAGE")
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(page);
+    let first_unread_floor = doc
+        .string_opt("/root/__T/unread_lou")
+        .and_then(|s| s.trim().parse().ok());
+    let debate = if topic.topic_type == TopicType::Debate {
+        doc.select_one("/root/__T/debate")?
+            .map(|n| parse_debate(&n.attrs()))
+    } else {
+        None
+    };
 
     Ok(TopicDetailsResult {
         topic,
@@ -467,9 +868,80 @@ fn parse_topic_details_response(xml: &str, page: u32) -> Result<TopicDetailsResu
         forum_name,
         total_pages,
         page,
+        first_unread_floor,
+        debate,
+    })
+}
+
+/// Parse a `read.php` response, reporting deleted topics as `TopicNotFound`.
+fn parse_details_document(xml: &str, topic_id: &TopicId) -> Result<XmlDocument> {
+    XmlDocument::parse(xml).map_err(|e| match e {
+        Error::NGAApi { ref message, .. } if is_topic_gone(message) => {
+            Error::TopicNotFound(topic_id.clone())
+        }
+        e => e,
+    })
+}
+
+/// Users of a topic page, keyed by ID.
+fn parse_details_users(doc: &XmlDocument) -> Result<HashMap<String, User>> {
+    let mut users = HashMap::new();
+    for node in doc.select("/root/__U/item")? {
+        if let Some(user) = parse_user(&node)? {
+            users.insert(user.id.0.clone(), user);
+        }
+    }
+    Ok(users)
+}
+
+/// The topic of a topic page, or why it is missing.
+fn parse_details_topic(doc: &XmlDocument, topic_id: &TopicId) -> Result<Topic> {
+    let topic = match doc.select_one("/root/__T")? {
+        Some(node) => parse_topic(&node)?,
+        None => None,
+    };
+    topic.ok_or_else(|| {
+        let message = doc
+            .string_opt("string(/root/__MESSAGE)")
+            .unwrap_or_default();
+        if is_topic_gone(&message) {
+            Error::TopicNotFound(topic_id.clone())
+        } else {
+            Error::missing("topic")
+        }
     })
 }
 
+/// Parse debate tallies from `agree`/`disagree`/`side` values.
+fn parse_debate(attrs: &std::collections::HashMap<String, String>) -> Debate {
+    let count = |key: &str| {
+        attrs
+            .get(key)
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0)
+    };
+
+    Debate {
+        pro_count: count("agree"),
+        con_count: count("disagree"),
+        user_side: attrs.get("side").and_then(|s| DebateSide::from_param(s)),
+    }
+}
+
+fn parse_debate_vote_response(xml: &str) -> Result<Debate> {
+    let doc = XmlDocument::parse(xml)?;
+    let node = doc
+        .select_one("/root/data")?
+        .ok_or_else(|| Error::missing("debate"))?;
+
+    Ok(parse_debate(&node.attrs()))
+}
+
+fn parse_topic_metadata_response(xml: &str, topic_id: &TopicId) -> Result<Topic> {
+    let doc = parse_details_document(xml, topic_id)?;
+    parse_details_topic(&doc, topic_id)
+}
+
 fn parse_topic(node: &crate::parser::XmlNode<'_>) -> Result<Option<Topic>> {
     let attrs = node.attrs();
 
@@ -487,8 +959,10 @@ fn parse_topic(node: &crate::parser::XmlNode<'_>) -> Result<Option<Topic>> {
     let (tags, content) = parse_subject(&subject_raw);
     let subject = Subject::new(tags, content);
 
+    let author_id = attrs.get("authorid").cloned().unwrap_or_default();
     let author = User {
-        id: attrs.get("authorid").cloned().unwrap_or_default().into(),
+        anon_context: anon_context(&author_id, attrs.get("author").map(String::as_str)),
+        id: author_id.into(),
         name: attrs
             .get("author")
             .map(|s| UserName::parse(s))
@@ -540,6 +1014,10 @@ fn parse_topic(node: &crate::parser::XmlNode<'_>) -> Result<Option<Topic>> {
             .get("recommend")
             .and_then(|s| s.parse().ok())
             .unwrap_or(0),
+        against: attrs
+            .get("against")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
     };
 
     Ok(Some(topic))
@@ -557,6 +1035,7 @@ fn parse_user(node: &crate::parser::XmlNode<'_>) -> Result<Option<User>> {
         .get("username")
         .map(|s| UserName::parse(s))
         .unwrap_or_default();
+    let anon_context = anon_context(&id, attrs.get("username").map(String::as_str));
 
     let user = User {
         id: id.into(),
@@ -583,6 +1062,7 @@ fn parse_user(node: &crate::parser::XmlNode<'_>) -> Result<Option<User>> {
             .map(|t| t > 0)
             .unwrap_or(false),
         honor: attrs.get("honor").cloned(),
+        anon_context,
     };
 
     Ok(Some(user))
@@ -603,13 +1083,17 @@ fn parse_post(
     };
 
     let author_id = attrs.get("authorid").cloned().unwrap_or_default();
-    let author = users.get(&author_id).cloned().unwrap_or_else(|| User {
-        id: author_id.into(),
+    let mut author = users.get(&author_id).cloned().unwrap_or_else(|| User {
+        id: author_id.clone().into(),
         ..Default::default()
     });
+    if author.is_anonymous() && author.anon_context.is_none() {
+        author.anon_context = anon_context(&author_id, None);
+    }
 
     let content_raw = attrs.get("content").cloned().unwrap_or_default();
     let content = parse_content(&content_raw);
+    let signature = attrs.get("signature").filter(|s| !s.trim().is_empty());
 
     let floor = attrs
         .get("lou")
@@ -627,7 +1111,7 @@ fn parse_post(
             .and_then(|s| s.parse().ok())
             .unwrap_or(0),
         edit_date: attrs.get("alterinfo").and_then(|s| s.parse().ok()),
-        is_edited: attrs.get("alterinfo").is_some(),
+        is_edited: attrs.contains_key("alterinfo"),
         attachments: parse_attachments(node),
         vote: VoteState {
             up: attrs.get("score").and_then(|s| s.parse().ok()).unwrap_or(0),
@@ -642,6 +1126,7 @@ fn parse_post(
             .unwrap_or(false),
         from_device: attrs.get("from_client").cloned(),
         signature: attrs.get("signature").cloned(),
+        signature_content: signature.map(|s| parse_content(s)),
         hot_replies: Vec::new(),
         comments: Vec::new(),
         comment_count: attrs
@@ -683,30 +1168,17 @@ fn parse_attachment(node: &XmlNode<'_>) -> Option<Attachment> {
 
     let explicit_type = attrs.get("type").cloned().unwrap_or_default();
     let name = attrs.get("name").cloned().unwrap_or_default();
-    let ext = attrs.get("ext").cloned().unwrap_or_else(|| {
-        name.rsplit('.')
-            .next()
-            .or_else(|| url.rsplit('.').next().and_then(|s| s.split('?').next()))
-            .unwrap_or("")
-            .to_owned()
-    });
+    let ext = attrs
+        .get("ext")
+        .cloned()
+        .or_else(|| file_ext(&name))
+        .or_else(|| file_ext(url))
+        .unwrap_or_default();
 
-    let kind = if explicit_type.contains("img") || explicit_type.contains("image") {
-        AttachmentKind::Image
-    } else {
-        AttachmentKind::from_ext(&ext)
-    };
+    let kind =
+        AttachmentKind::from_mime(&explicit_type).unwrap_or_else(|| AttachmentKind::from_ext(&ext));
 
-    let dimensions = explicit_type.split(':').nth(1).and_then(|dim| {
-        let parts: Vec<&str> = dim.split('x').collect();
-        if parts.len() == 2 {
-            let w = parts[0].parse().ok()?;
-            let h = parts[1].parse().ok()?;
-            Some((w, h))
-        } else {
-            None
-        }
-    });
+    let dimensions = parse_dimensions(&attrs);
 
     let thumb_url = attrs.get("thumb").cloned().filter(|s| !s.is_empty());
 
@@ -724,13 +1196,23 @@ fn parse_attachment(node: &XmlNode<'_>) -> Option<Attachment> {
     })
 }
 
+/// Extension of the file name at the end of a path or URL, ignoring any query
+/// string or fragment.
+fn file_ext(path: &str) -> Option<String> {
+    let path = path.split(['?', '#']).next()?;
+    let file = path.rsplit('/').next()?;
+    file.rsplit_once('.')
+        .map(|(_, ext)| ext.to_owned())
+        .filter(|ext| !ext.is_empty())
+}
+
 fn parse_subforum(node: &crate::parser::XmlNode<'_>) -> Option<Subforum> {
     use crate::client::FORUM_ICON_PATH;
 
     let is_fid = node.name() == "item";
     let children = node.children();
 
-    let id = children.get(0).map(|n| n.text())?;
+    let id = children.first().map(|n| n.text())?;
     let name = children.get(1).map(|n| n.text()).unwrap_or_default();
     let info = children.get(2).map(|n| n.text()).unwrap_or_default();
     let filter_id = children.get(3).map(|n| n.text()).unwrap_or_default();
@@ -754,6 +1236,8 @@ fn parse_subforum(node: &crate::parser::XmlNode<'_>) -> Option<Subforum> {
             info,
             icon_url,
             topped_topic_id: String::new(),
+            topic_count: None,
+            today_count: None,
         },
         filter_id,
         filterable: attributes > 40,
@@ -766,7 +1250,7 @@ fn parse_forum_from_node(node: &crate::parser::XmlNode<'_>) -> Option<Forum> {
 
     let attrs = node.attrs();
     let id = attrs.get("fid").or_else(|| attrs.get("stid"))?;
-    let is_stid = attrs.get("stid").is_some();
+    let is_stid = attrs.contains_key("stid");
 
     let forum_id = if is_stid {
         ForumIdKind::stid(id)
@@ -782,24 +1266,363 @@ fn parse_forum_from_node(node: &crate::parser::XmlNode<'_>) -> Option<Forum> {
         info: attrs.get("info").cloned().unwrap_or_default(),
         icon_url,
         topped_topic_id: attrs.get("topped_topic").cloned().unwrap_or_default(),
+        topic_count: None,
+        today_count: None,
     })
 }
 
-fn parse_pages(doc: &XmlDocument, rows_path: &str, page_path: &str, per_page: u32) -> Result<u32> {
-    let total_rows = doc.int_or(rows_path, 0) as u32;
-    let per_page_actual = doc.int_or(page_path, per_page as i64) as u32;
-
-    if total_rows == 0 {
-        return Ok(1);
+/// Image dimensions from explicit `width`/`height` attributes, `pic_size`,
+/// or a `WxH` token anywhere in `type` (`img:800x600`, `img:0:800x600:1`).
+fn parse_dimensions(attrs: &HashMap<String, String>) -> Option<(u32, u32)> {
+    let explicit = |key: &str| attrs.get(key).and_then(|v| v.trim().parse::<u32>().ok());
+    if let (Some(w), Some(h)) = (explicit("width"), explicit("height")) {
+        if w > 0 && h > 0 {
+            return Some((w, h));
+        }
     }
 
-    let pages = (total_rows + per_page_actual - 1) / per_page_actual;
-    Ok(pages.max(1))
+    ["pic_size", "type"]
+        .iter()
+        .filter_map(|key| attrs.get(*key))
+        .flat_map(|value| value.split(|c: char| !c.is_ascii_alphanumeric() && c != '*'))
+        .find_map(parse_size_token)
+}
+
+/// Parse a `WxH` (or `W*H`) token with positive sides.
+fn parse_size_token(token: &str) -> Option<(u32, u32)> {
+    let (w, h) = token.split_once(['x', 'X', '*'])?;
+    let (w, h) = (w.parse().ok()?, h.parse().ok()?);
+    (w > 0 && h > 0).then_some((w, h))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::testing::{has_param, recording_client, serving_client, QueryRecorder};
+    use crate::cache::{CacheStorage, CacheStorageExt, MemoryCache};
+    use crate::NGAClient;
+
+    #[test]
+    fn test_parse_topic_recommend_against() {
+        let xml = "<root><__T>\
+<item><tid>1</tid><subject>Controversial</subject><recommend>40</recommend><against>55</against></item>\
+<item><tid>2</tid><subject>Plain</subject><recommend>3</recommend></item></__T></root>";
+
+        let result = parse_topic_list_response(xml, 1).unwrap();
+        let topic = &result.topics[0];
+        assert_eq!((topic.recommend, topic.against), (40, 55));
+        assert_eq!(topic.net_recommend(), -15);
+        assert_eq!(result.topics[1].against, 0);
+        assert_eq!(result.topics[1].net_recommend(), 3);
+    }
+
+    #[test]
+    fn test_parse_topic_list_totals() {
+        let xml = "<root><__ROWS>1203</__ROWS><__R__ROWS_PAGE>20</__R__ROWS_PAGE>\
+<__T__ROWS_PAGE>35</__T__ROWS_PAGE><__PAGE>4</__PAGE><__T><item><tid>1</tid><subject>First</subject></item></__T></root>";
+
+        let result = parse_topic_list_response(xml, 1).unwrap();
+        assert_eq!(result.total_topics, 1203);
+        assert_eq!(result.total_pages, 35);
+        assert_eq!(result.page, 4);
+
+        let xml = "<root><__T><item><tid>1</tid><subject>Only</subject></item></__T></root>";
+        let result = parse_topic_list_response(xml, 2).unwrap();
+        assert_eq!((result.total_topics, result.page), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_list_all_dedupes_pinned_topics() {
+        let page1 = "<root><__ROWS>70</__ROWS><__T__ROWS_PAGE>35</__T__ROWS_PAGE><__T>\
+<item><tid>1</tid><subject>Pinned</subject></item>\
+<item><tid>2</tid><subject>Second</subject></item></__T></root>";
+        let page2 = "<root><__ROWS>70</__ROWS><__T__ROWS_PAGE>35</__T__ROWS_PAGE><__T>\
+<item><tid>1</tid><subject>Pinned</subject></item>\
+<item><tid>3</tid><subject>Third</subject></item></__T></root>";
+        let (client, recorder) = serving_client(vec![page1, page2]);
+
+        let result = client
+            .topics()
+            .list_all(ForumIdKind::fid("310"), TopicOrder::default(), 2)
+            .await
+            .unwrap();
+
+        let ids: Vec<&str> = result.topics.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "3"]);
+        assert_eq!(result.total_pages, 2);
+
+        let queries = recorder.queries();
+        assert!(has_param(&queries[0], "page", "1"));
+        assert!(has_param(&queries[1], "page", "2"));
+    }
+
+    /// Page `page` of a topic with `floors` replies, 20 per page.
+    fn mock_page(page: u32, floors: i32) -> TopicDetailsResult {
+        let first = (page as i32 - 1) * 20;
+        TopicDetailsResult {
+            posts: (first..(first + 20).min(floors + 1))
+                .map(|floor| Post {
+                    floor,
+                    ..Default::default()
+                })
+                .collect(),
+            total_pages: (floors as u32) / 20 + 1,
+            page,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_new_replies_since() {
+        let floors = 75;
+        let mut requested = Vec::new();
+
+        let posts = collect_new_replies(30, |page| {
+            requested.push(page);
+            let page = page.unwrap_or(floors as u32 / 20 + 1);
+            async move { Ok(mock_page(page, floors)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(requested, vec![None, Some(3), Some(2)]);
+        let got: Vec<i32> = posts.iter().map(|p| p.floor).collect();
+        assert_eq!(got, (31..=75).collect::<Vec<_>>());
+
+        let none = collect_new_replies(75, |page| {
+            let page = page.unwrap_or(4);
+            async move { Ok(mock_page(page, floors)) }
+        })
+        .await
+        .unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_subforum_query() {
+        let (client, recorder) = recording_client();
+
+        let result = client
+            .topics()
+            .list(ForumIdKind::fid("310"))
+            .subforum("12345")
+            .send()
+            .await;
+        assert!(matches!(result, Err(Error::Network(_))));
+
+        let query = recorder.first_query();
+        assert!(has_param(&query, "fid", "310"));
+        assert!(has_param(&query, "stid", "12345"));
+    }
+
+    #[test]
+    fn test_details_timeout_override() {
+        let client = crate::NGAClient::builder().build().unwrap();
+
+        let builder = client.topics().details("100");
+        assert_eq!(builder.timeout, None);
+
+        let builder = builder.timeout(Duration::from_secs(90));
+        assert_eq!(builder.timeout, Some(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn test_details_debate() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<root>
+<__T><tid>200</tid><subject>Cats or dogs?</subject><type>2</type>
+<debate><agree>128</agree><disagree>64</disagree><side>2</side></debate></__T>
+<__R><item><pid>0</pid><tid>200</tid><lou>0</lou><authorid>1</authorid><content>Discuss</content></item></__R>
+</root>"#;
+
+        let result = parse_topic_details_response(xml, &"1".into(), 1).unwrap();
+        assert_eq!(
+            result.debate,
+            Some(Debate {
+                pro_count: 128,
+                con_count: 64,
+                user_side: Some(DebateSide::Con),
+            })
+        );
+
+        let normal = xml.replace("<type>2</type>", "<type>0</type>");
+        let result = parse_topic_details_response(&normal, &"1".into(), 1).unwrap();
+        assert_eq!(result.debate, None);
+    }
+
+    #[test]
+    fn test_debate_vote_response() {
+        let xml =
+            r#"<root><data><agree>129</agree><disagree>64</disagree><side>1</side></data></root>"#;
+        let debate = parse_debate_vote_response(xml).unwrap();
+        assert_eq!(debate.pro_count, 129);
+        assert_eq!(debate.user_side, Some(DebateSide::Pro));
+    }
+
+    #[test]
+    fn test_details_read_position() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<root>
+<__PAGE>3</__PAGE>
+<__ROWS>57</__ROWS>
+<__R__ROWS_PAGE>20</__R__ROWS_PAGE>
+<__T><tid>100</tid><subject>Hello</subject><replies>56</replies><unread_lou>44</unread_lou></__T>
+<__R><item><pid>9001</pid><tid>100</tid><lou>44</lou><authorid>1</authorid><content>hi</content></item></__R>
+</root>"#;
+
+        let result = parse_topic_details_response(xml, &"1".into(), 1).unwrap();
+        assert_eq!(result.page, 3);
+        assert_eq!(result.total_pages, 3);
+        assert_eq!(result.first_unread_floor, Some(44));
+        assert_eq!(result.posts.len(), 1);
+    }
+
+    #[test]
+    fn test_details_anonymous_labels() {
+        let xml = r#"<root>
+<__U>
+<item><uid>-1,abc123</uid><username>#anon_abc123</username></item>
+<item><uid>-1,def456</uid><username>#anon_def456</username></item>
+</__U>
+<__T><tid>100</tid><subject>Hello</subject></__T>
+<__R>
+<item><pid>1</pid><tid>100</tid><lou>0</lou><authorid>-1,abc123</authorid><content>a</content></item>
+<item><pid>2</pid><tid>100</tid><lou>1</lou><authorid>-1,def456</authorid><content>b</content></item>
+<item><pid>3</pid><tid>100</tid><lou>2</lou><authorid>-1,abc123</authorid><content>c</content></item>
+<item><pid>4</pid><tid>100</tid><lou>3</lou><authorid>-1,def456</authorid><content>d</content></item>
+</__R>
+</root>"#;
+
+        let result = parse_topic_details_response(xml, &"1".into(), 1).unwrap();
+        let labels: Vec<String> = result
+            .posts
+            .iter()
+            .map(|p| p.author.anon_label().unwrap())
+            .collect();
+        assert_eq!(labels[0], labels[2]);
+        assert_eq!(labels[1], labels[3]);
+        assert_ne!(labels[0], labels[1]);
+    }
+
+    #[test]
+    fn test_details_signature_content() {
+        let xml = r#"<root>
+<__T><tid>100</tid><subject>Hello</subject></__T>
+<__R>
+<item><pid>1</pid><tid>100</tid><lou>0</lou><content>a</content>
+<signature>[img]./mon_202401/sig.png[/img] [url=https://example.com]my blog[/url]</signature></item>
+<item><pid>2</pid><tid>100</tid><lou>1</lou><content>b</content><signature> </signature></item>
+</__R>
+</root>"#;
+
+        let result = parse_topic_details_response(xml, &"100".into(), 1).unwrap();
+        let post = &result.posts[0];
+        assert!(post.signature.as_deref().unwrap().starts_with("[img]"));
+
+        let signature = post.signature_content.as_ref().unwrap();
+        assert!(signature.spans[0].is_tag("img"));
+        assert_eq!(signature.spans[0].to_plain_text(), "./mon_202401/sig.png");
+        assert_eq!(
+            signature.links(),
+            vec![("my blog".to_owned(), "https://example.com".to_owned())]
+        );
+        assert!(result.posts[1].signature_content.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_posts_matches_batch() {
+        let xml = r#"<root>
+<__U>
+<item><uid>1</uid><username>Alice</username></item>
+<item><uid>2</uid><username>Bob(Bobby)</username></item>
+</__U>
+<__T><tid>100</tid><subject>Hello</subject></__T>
+<__R>
+<item><pid>0</pid><tid>100</tid><lou>0</lou><authorid>1</authorid><content>first</content></item>
+<item><pid>11</pid><tid>100</tid><lou>1</lou><authorid>2</authorid><content><![CDATA[[b]<item>[/b]]]></content>
+<attachs><item><attachurl>mon_202401/a.jpg</attachurl><type>img</type></item></attachs></item>
+<item><tid>100</tid><content>no pid</content></item>
+<item><pid>12</pid><tid>100</tid><lou>2</lou><authorid>1</authorid><content>third</content></item>
+</__R>
+<__ROWS>3</__ROWS>
+</root>"#;
+        let (client, _) = serving_client(vec![xml]);
+
+        let stream = client.topics().details("100").stream_posts().await.unwrap();
+        assert_eq!(stream.topic().id.as_str(), "100");
+        let streamed: Vec<Post> = stream.collect::<Result<_>>().unwrap();
+
+        let batch = parse_topic_details_response(xml, &"100".into(), 1).unwrap();
+        assert_eq!(streamed.len(), 3);
+        assert_eq!(
+            serde_json::to_value(&streamed).unwrap(),
+            serde_json::to_value(&batch.posts).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_stream_posts_truncated() {
+        let xml = "<item><pid>1</pid><tid>100</tid><lou>0</lou><content>first</content></item>\n\
+                   <item><pid>2</pid><tid>100</tid><content>cut o";
+        let mut stream = TopicPostStream {
+            topic: Topic::with_id("100"),
+            users: HashMap::new(),
+            xml: xml.to_owned(),
+            pos: 0,
+            end: xml.len(),
+        };
+
+        assert!(matches!(stream.next(), Some(Ok(post)) if post.id.as_str() == "1"));
+        assert!(matches!(stream.next(), Some(Err(Error::Xml(_)))));
+        assert!(stream.next().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_posts_deleted_topic() {
+        let xml = "<root><__MESSAGE>帖子不存在</__MESSAGE></root>";
+        let (client, _) = serving_client(vec![xml]);
+
+        let err = client.topics().details("100").stream_posts().await;
+        assert!(matches!(err, Err(Error::TopicNotFound(_))));
+    }
+
+    #[test]
+    fn test_details_without_read_position() {
+        let xml = r#"<root><__T><tid>100</tid><subject>Hello</subject></__T><__R/></root>"#;
+
+        let result = parse_topic_details_response(xml, &"1".into(), 2).unwrap();
+        assert_eq!(result.page, 2);
+        assert_eq!(result.first_unread_floor, None);
+    }
+
+    #[test]
+    fn test_topic_metadata() {
+        let xml = r#"<root>
+<__T><tid>100</tid><subject>[News] Patch notes</subject><replies>56</replies><lastpost>1700000000</lastpost></__T>
+<__R><item><pid>1</pid><tid>100</tid><lou>0</lou><content>ignored</content></item></__R>
+</root>"#;
+
+        let topic = parse_topic_metadata_response(xml, &"100".into()).unwrap();
+        assert_eq!(topic.id.as_str(), "100");
+        assert_eq!(topic.subject.content, "Patch notes");
+        assert_eq!(topic.subject.tags, vec!["News"]);
+        assert_eq!(topic.replies, 56);
+        assert_eq!(topic.last_post_date, 1700000000);
+
+        let id = TopicId::from("100");
+        assert!(matches!(
+            parse_topic_metadata_response("<root><__T>", &id),
+            Err(Error::Xml(_))
+        ));
+        assert!(matches!(
+            parse_topic_metadata_response("<root><__T/></root>", &id),
+            Err(Error::MissingField(_))
+        ));
+        assert!(matches!(
+            parse_topic_metadata_response("<root><__MESSAGE>帖子不存在</__MESSAGE></root>", &id),
+            Err(Error::TopicNotFound(_))
+        ));
+    }
 
     #[test]
     fn test_topic_order_param() {
@@ -807,4 +1630,241 @@ mod tests {
         assert_eq!(TopicOrder::PostDate.param(), "postdate");
         assert_eq!(TopicOrder::Recommend.param(), "recommend");
     }
+
+    fn offline_client() -> (NGAClient, Arc<dyn CacheStorage>, Arc<QueryRecorder>) {
+        let cache: Arc<dyn CacheStorage> = Arc::new(MemoryCache::new());
+        let recorder = Arc::new(QueryRecorder::default());
+        let client = NGAClient::builder()
+            .cache(cache.clone())
+            .observer(recorder.clone())
+            .offline(true)
+            .build()
+            .unwrap();
+        (client, cache, recorder)
+    }
+
+    #[tokio::test]
+    async fn test_offline_details_cache_hit() {
+        let (client, cache, recorder) = offline_client();
+        let cached = TopicDetailsResult {
+            topic: Topic::with_id("100"),
+            forum_name: "Cached".into(),
+            page: 1,
+            ..Default::default()
+        };
+        cache
+            .set_stale("topic:100:1:", &cached, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        let result = client.topics().details("100").send().await.unwrap();
+        assert_eq!(result.forum_name, "Cached");
+        assert!(recorder.queries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_offline_details_cache_miss() {
+        let (client, _, recorder) = offline_client();
+
+        let result = client.topics().details("100").send().await;
+        assert!(matches!(result, Err(Error::Offline)));
+
+        let result = client.topics().details("100").page(2).send().await;
+        assert!(matches!(result, Err(Error::Offline)));
+        assert!(recorder.queries().is_empty());
+    }
+
+    fn attachment_from(item: &str) -> Attachment {
+        let xml = format!("<root>{}</root>", item);
+        let doc = XmlDocument::parse(&xml).unwrap();
+        let node = doc.select_one("//item").unwrap().unwrap();
+        parse_attachment(&node).unwrap()
+    }
+
+    #[test]
+    fn test_attachment_kind() {
+        let cases = [
+            (
+                r#"<item attachurl="https://cdn.example.com/img?id=1" type="image/webp"/>"#,
+                AttachmentKind::Image,
+            ),
+            (
+                r#"<item attachurl="mon_202401/clip?x=1" type="video/mp4"/>"#,
+                AttachmentKind::Video,
+            ),
+            (
+                r#"<item attachurl="mon_202401/a.mp3?v=2" type="application/octet-stream"/>"#,
+                AttachmentKind::Audio,
+            ),
+            (
+                r#"<item attachurl="mon_202401/a.webp?v=2"/>"#,
+                AttachmentKind::Image,
+            ),
+            (
+                r#"<item attachurl="mon_202401/abc" name="clip.MKV"/>"#,
+                AttachmentKind::Video,
+            ),
+            (
+                r#"<item attachurl="mon_202401/a.jpg" type="img:800x600"/>"#,
+                AttachmentKind::Image,
+            ),
+            (
+                r#"<item attachurl="mon_202401/abc?x=1.5"/>"#,
+                AttachmentKind::File,
+            ),
+        ];
+
+        for (xml, expected) in cases {
+            assert_eq!(attachment_from(xml).kind, expected, "{}", xml);
+        }
+    }
+
+    #[test]
+    fn test_attachment_dimensions() {
+        let cases = [
+            (
+                r#"<item attachurl="a.jpg" type="img:800x600"/>"#,
+                Some((800, 600)),
+            ),
+            (
+                r#"<item attachurl="a.jpg" type="img:0:1024x768:1"/>"#,
+                Some((1024, 768)),
+            ),
+            (
+                r#"<item attachurl="a.jpg" type="1920X1080"/>"#,
+                Some((1920, 1080)),
+            ),
+            (r#"<item attachurl="a.jpg" type="img"/>"#, None),
+            (r#"<item attachurl="a.jpg" type="0"/>"#, None),
+            (r#"<item attachurl="a.jpg" type="img:0x0"/>"#, None),
+            (
+                r#"<item attachurl="a.jpg" type="img" pic_size="640*480"/>"#,
+                Some((640, 480)),
+            ),
+            (
+                r#"<item attachurl="a.jpg" type="img:1x1" width="300" height="200"/>"#,
+                Some((300, 200)),
+            ),
+            (
+                "<item><attachurl>a.jpg</attachurl><type>img</type>\
+<width>320</width><height>240</height></item>",
+                Some((320, 240)),
+            ),
+        ];
+
+        for (item, expected) in cases {
+            assert_eq!(attachment_from(item).dimensions, expected, "{}", item);
+        }
+    }
+
+    #[test]
+    fn test_details_deleted_topic() {
+        let id = TopicId::from("404");
+        let error = r#"<root><__error code="15" message="帖子不存在或已被删除"/></root>"#;
+        let result = parse_topic_details_response(error, &id, 1);
+        assert!(matches!(result, Err(Error::TopicNotFound(ref tid)) if *tid == id));
+
+        let message = "<root><__MESSAGE><item>1</item><item>(ERROR:15)帖子不存在或已被删除</item>\
+</__MESSAGE></root>";
+        let result = parse_topic_details_response(message, &id, 1);
+        assert!(matches!(result, Err(Error::TopicNotFound(_))));
+
+        let other = r#"<root><__error code="2" message="需要登录"/></root>"#;
+        let result = parse_topic_details_response(other, &id, 1);
+        assert!(matches!(result, Err(Error::NGAApi { .. })));
+
+        for other in ["用户不存在", "附件不存在", "the file was deleted"] {
+            let xml = format!(r#"<root><__error code="1" message="{}"/></root>"#, other);
+            let result = parse_topic_details_response(&xml, &id, 1);
+            assert!(matches!(result, Err(Error::NGAApi { .. })), "{}", other);
+        }
+
+        let empty = "<root><__R/></root>";
+        let result = parse_topic_details_response(empty, &id, 1);
+        assert!(matches!(result, Err(Error::MissingField(_))));
+    }
+
+    #[test]
+    fn test_parse_toplist() {
+        let xml = "<root><data><item>\
+<item><tid>301</tid><fid>-7</fid><subject>First</subject><author>alice</author>\
+<authorid>1</authorid><replies>980</replies></item>\
+<item><tid>302</tid><fid>310</fid><subject>Second</subject><replies>640</replies></item>\
+<item><tid>303</tid><fid>-7</fid><subject>Third</subject><replies>512</replies></item>\
+</item></data><time>1700000000</time></root>";
+
+        let topics = parse_toplist_response(xml).unwrap();
+        let ids: Vec<&str> = topics.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["301", "302", "303"]);
+        assert_eq!(topics[0].subject.content, "First");
+        assert_eq!(topics[0].replies, 980);
+        assert_eq!(topics[1].forum_id, Some(ForumIdKind::fid("310")));
+    }
+
+    #[tokio::test]
+    async fn test_details_read_options() {
+        let (client, recorder) = recording_client();
+
+        let _ = client
+            .topics()
+            .details("100")
+            .author("42")
+            .anonymous_only(true)
+            .send()
+            .await;
+        let _ = client
+            .topics()
+            .details("100")
+            .options(ReadOptions::ANONYMOUS_ONLY | ReadOptions::from_bits(2))
+            .send()
+            .await;
+        let _ = client
+            .topics()
+            .details("100")
+            .anonymous_only(true)
+            .options(ReadOptions::empty())
+            .send()
+            .await;
+
+        let queries = recorder.queries();
+        assert!(has_param(&queries[0], "opt", "512"));
+        assert!(has_param(&queries[0], "authorid", "42"));
+        assert!(has_param(&queries[1], "opt", "514"));
+        assert!(!queries[2].iter().any(|(k, _)| k == "opt"));
+    }
+
+    #[tokio::test]
+    async fn test_details_goto_unread_params() {
+        let (client, recorder) = recording_client();
+
+        let _ = client.topics().details("100").page(3).send().await;
+        let _ = client
+            .topics()
+            .details("100")
+            .goto_unread(true)
+            .send()
+            .await;
+
+        let queries = recorder.queries();
+        assert!(has_param(&queries[0], "page", "3"));
+        assert!(!queries[0].iter().any(|(k, _)| k == "fromread"));
+        assert!(has_param(&queries[1], "fromread", "1"));
+        assert!(!queries[1].iter().any(|(k, _)| k == "page"));
+    }
+
+    #[tokio::test]
+    async fn test_toplist_scope_params() {
+        let (client, recorder) = recording_client();
+
+        let _ = client.topics().toplist(ToplistScope::Global).await;
+        let _ = client
+            .topics()
+            .toplist(ToplistScope::Forum(ForumIdKind::fid("310")))
+            .await;
+
+        let queries = recorder.queries();
+        assert!(has_param(&queries[0], "all", "1"));
+        assert!(has_param(&queries[1], "fid", "310"));
+        assert!(!queries[1].iter().any(|(k, _)| k == "all"));
+    }
 }
@@ -1,11 +1,14 @@
 //! User API.
 
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use super::{PostApi, TopicApi, UserPost};
 use crate::{
     client::NGAClientInner,
     error::{Error, Result},
-    models::{User, UserId, UserName},
+    models::{MyProfile, OnlineStatus, Topic, User, UserId, UserName},
     parser::XmlDocument,
 };
 
@@ -19,6 +22,19 @@ impl UserApi {
         Self { client }
     }
 
+    /// Download a user's avatar image.
+    ///
+    /// Fails with `Error::NoAvatar` if the user has no avatar URL.
+    pub async fn fetch_avatar(&self, user: &User) -> Result<Image> {
+        let url = user
+            .avatar_url
+            .as_deref()
+            .filter(|url| !url.is_empty())
+            .ok_or(Error::NoAvatar)?;
+
+        fetch_image(&self.client, url).await
+    }
+
     /// Get user by ID.
     pub async fn get(&self, user_id: impl Into<UserId>) -> Result<User> {
         let user_id = user_id.into();
@@ -39,6 +55,25 @@ impl UserApi {
         parse_user_response(&xml, &user_id)
     }
 
+    /// Get several users by ID.
+    ///
+    /// Profiles are fetched with up to `concurrency` requests in flight.
+    /// Each ID is paired with its own result, in input order, so one
+    /// failed lookup doesn't abort the batch.
+    pub async fn get_many(
+        &self,
+        user_ids: &[&str],
+        concurrency: usize,
+    ) -> Result<Vec<(String, Result<User>)>> {
+        let results = stream::iter(user_ids)
+            .map(|&id| async move { (id.to_owned(), self.get(id).await) })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+
+        Ok(results)
+    }
+
     /// Get user by username.
     pub async fn get_by_name(&self, username: &str) -> Result<User> {
         let xml = self
@@ -64,21 +99,147 @@ impl UserApi {
         self.get(&auth.uid).await
     }
 
-    /// Search users.
+    /// Get the current user's own profile, including private fields such as
+    /// email verification and coin balance (requires auth).
+    pub async fn me_detailed(&self) -> Result<MyProfile> {
+        let auth = self.client.require_auth()?;
+        let user_id = UserId::new(auth.uid.clone());
+
+        let xml = self
+            .client
+            .post_authed(
+                "nuke.php",
+                &[
+                    ("__lib", "ucp"),
+                    ("__act", "get"),
+                    ("uid", user_id.as_str()),
+                ],
+                &[],
+            )
+            .await?;
+
+        parse_my_profile_response(&xml, &user_id)
+    }
+
+    /// Check whether a user is online.
+    ///
+    /// NGA has no presence endpoint, so this reads the last visit time from
+    /// the user's profile: users seen within the last 15 minutes count as
+    /// online. Profiles that don't disclose a last visit are `Hidden`.
+    pub async fn online_status(&self, user_id: impl Into<UserId>) -> Result<OnlineStatus> {
+        let user_id = user_id.into();
+
+        let xml = self
+            .client
+            .post(
+                "nuke.php",
+                &[
+                    ("__lib", "ucp"),
+                    ("__act", "get"),
+                    ("uid", user_id.as_str()),
+                ],
+                &[],
+            )
+            .await?;
+
+        let attrs = user_item_attrs(&xml)?;
+        Ok(parse_online_status(&attrs, chrono::Utc::now().timestamp()))
+    }
+
+    /// Search users, returning the first page of matches.
     pub async fn search(&self, keyword: &str) -> Result<Vec<UserSearchResult>> {
+        Ok(self.search_paged(keyword).send().await?.users)
+    }
+
+    /// Search users with pagination.
+    pub fn search_paged(&self, keyword: &str) -> UserSearchBuilder {
+        UserSearchBuilder {
+            client: self.client.clone(),
+            keyword: keyword.to_owned(),
+            page: 1,
+        }
+    }
+
+    /// Get a user's topics and posts merged into one feed, newest first.
+    pub async fn activity(&self, user_id: impl Into<UserId>, page: u32) -> Result<Vec<Activity>> {
+        let user_id = user_id.into();
+        let topics = TopicApi::new(self.client.clone());
+        let posts = PostApi::new(self.client.clone());
+
+        let (topics, posts) = tokio::try_join!(
+            topics.by_user(&user_id, page),
+            posts.by_user(&user_id).page(page).send()
+        )?;
+
+        Ok(merge_activity(topics.topics, posts.posts))
+    }
+}
+
+/// Builder for user search requests.
+pub struct UserSearchBuilder {
+    client: Arc<NGAClientInner>,
+    keyword: String,
+    page: u32,
+}
+
+impl UserSearchBuilder {
+    /// Set the page number.
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// Execute the request.
+    pub async fn send(self) -> Result<UserSearchListResult> {
+        let page_str = self.page.to_string();
+
         let xml = self
             .client
             .post(
                 "nuke.php",
-                &[("__lib", "ucp"), ("__act", "search"), ("key", keyword)],
+                &[
+                    ("__lib", "ucp"),
+                    ("__act", "search"),
+                    ("key", &self.keyword),
+                    ("page", &page_str),
+                ],
                 &[],
             )
             .await?;
 
-        parse_user_search(&xml)
+        parse_user_search(&xml, self.page)
     }
 }
 
+/// An entry in a user's activity feed.
+#[derive(Debug, Clone)]
+pub enum Activity {
+    /// A topic started by the user.
+    Topic(Box<Topic>),
+    /// A reply posted by the user.
+    Post(UserPost),
+}
+
+impl Activity {
+    /// Time the topic or post was created.
+    pub fn date(&self) -> i64 {
+        match self {
+            Activity::Topic(topic) => topic.post_date,
+            Activity::Post(post) => post.post_date,
+        }
+    }
+}
+
+fn merge_activity(topics: Vec<Topic>, posts: Vec<UserPost>) -> Vec<Activity> {
+    let mut activity: Vec<Activity> = topics
+        .into_iter()
+        .map(|t| Activity::Topic(Box::new(t)))
+        .chain(posts.into_iter().map(Activity::Post))
+        .collect();
+    activity.sort_by_key(|a| std::cmp::Reverse(a.date()));
+    activity
+}
+
 /// Result of a user search.
 #[derive(Debug, Clone)]
 pub struct UserSearchResult {
@@ -90,21 +251,81 @@ pub struct UserSearchResult {
     pub avatar_url: Option<String>,
 }
 
+/// A page of user search results.
+#[derive(Debug, Clone, Default)]
+pub struct UserSearchListResult {
+    /// Matching users.
+    pub users: Vec<UserSearchResult>,
+    /// Total number of pages.
+    pub total_pages: u32,
+    /// Current page.
+    pub page: u32,
+}
+
 fn parse_user_response(xml: &str, user_id: &UserId) -> Result<User> {
+    Ok(parse_user_attrs(&user_item_attrs(xml)?, user_id))
+}
+
+/// Parse the self-profile response, which adds private fields to a user.
+fn parse_my_profile_response(xml: &str, user_id: &UserId) -> Result<MyProfile> {
+    let attrs = user_item_attrs(xml)?;
+    let number = |key: &str| attrs.get(key).and_then(|s| s.parse::<i64>().ok());
+
+    Ok(MyProfile {
+        user: parse_user_attrs(&attrs, user_id),
+        email: attrs.get("email").filter(|s| !s.is_empty()).cloned(),
+        email_verified: number("yz").is_some_and(|yz| yz > 0),
+        money: number("money").unwrap_or(0),
+        credit: number("credit").unwrap_or(0),
+        following_count: number("follow_num").unwrap_or(0) as i32,
+        follower_count: number("follow_by_num").unwrap_or(0) as i32,
+    })
+}
+
+/// Attributes of the `/root/data/item` node of a profile response.
+fn user_item_attrs(xml: &str) -> Result<HashMap<String, String>> {
     let doc = XmlDocument::parse(xml)?;
 
     let node = doc
         .select_one("/root/data/item")?
         .ok_or_else(|| Error::missing("user data"))?;
 
-    let attrs = node.attrs();
+    Ok(node.attrs())
+}
+
+/// How long after their last visit a user still counts as online, in seconds.
+const ONLINE_WINDOW_SECS: i64 = 15 * 60;
 
+/// Derive a user's online status from profile fields at time `now`.
+///
+/// An explicit `online` flag wins; otherwise the `thisvisit` (or `lastvisit`)
+/// timestamp is compared against `ONLINE_WINDOW_SECS`.
+fn parse_online_status(attrs: &HashMap<String, String>, now: i64) -> OnlineStatus {
+    let last_active = ["thisvisit", "lastvisit"]
+        .iter()
+        .find_map(|key| attrs.get(*key).and_then(|s| s.trim().parse::<i64>().ok()))
+        .filter(|&t| t > 0);
+
+    match attrs.get("online").map(|s| s.trim()) {
+        Some("1") => return OnlineStatus::Online { last_active },
+        Some("0") => return OnlineStatus::Offline { last_active },
+        _ => {}
+    }
+
+    match last_active {
+        Some(t) if now - t <= ONLINE_WINDOW_SECS => OnlineStatus::Online { last_active },
+        Some(_) => OnlineStatus::Offline { last_active },
+        None => OnlineStatus::Hidden,
+    }
+}
+
+fn parse_user_attrs(attrs: &HashMap<String, String>, user_id: &UserId) -> User {
     let name = attrs
         .get("username")
         .map(|s| UserName::parse(s))
         .unwrap_or_default();
 
-    let user = User {
+    User {
         id: user_id.clone(),
         name,
         avatar_url: attrs.get("avatar").cloned(),
@@ -129,20 +350,19 @@ fn parse_user_response(xml: &str, user_id: &UserId) -> Result<User> {
             .map(|t| t > 0)
             .unwrap_or(false),
         honor: attrs.get("honor").cloned(),
-    };
-
-    Ok(user)
+        anon_context: None,
+    }
 }
 
-fn parse_user_search(xml: &str) -> Result<Vec<UserSearchResult>> {
+fn parse_user_search(xml: &str, page: u32) -> Result<UserSearchListResult> {
     let doc = XmlDocument::parse(xml)?;
-    let mut results = Vec::new();
+    let mut users = Vec::new();
 
     for node in doc.select("/root/data/item")? {
         let attrs = node.attrs();
 
         if let Some(uid) = attrs.get("uid") {
-            results.push(UserSearchResult {
+            users.push(UserSearchResult {
                 id: uid.clone().into(),
                 name: attrs.get("username").cloned().unwrap_or_default(),
                 avatar_url: attrs.get("avatar").cloned(),
@@ -150,16 +370,245 @@ fn parse_user_search(xml: &str) -> Result<Vec<UserSearchResult>> {
         }
     }
 
-    Ok(results)
+    let total_pages = if doc.int_or("/root/__ROWS", 0) > 0 {
+        doc.total_pages("/root/__R__ROWS_PAGE", 20)
+    } else {
+        page.max(1)
+    };
+
+    Ok(UserSearchListResult {
+        users,
+        total_pages,
+        page: doc
+            .string_opt("/root/__PAGE")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(page),
+    })
+}
+
+/// A downloaded image.
+#[derive(Debug, Clone)]
+pub struct Image {
+    /// Raw image bytes.
+    pub bytes: Vec<u8>,
+    /// MIME type from the response, or sniffed from the bytes.
+    pub content_type: Option<String>,
+}
+
+/// Download an image, taking its type from the response or its bytes.
+pub(crate) async fn fetch_image(client: &NGAClientInner, url: &str) -> Result<Image> {
+    let (bytes, content_type) = client.executor().get_bytes(url).await?;
+    let content_type = content_type
+        .filter(|ct| ct.starts_with("image/"))
+        .or_else(|| sniff_image_type(&bytes).map(str::to_owned));
+
+    Ok(Image {
+        bytes,
+        content_type,
+    })
+}
+
+/// Detect an image MIME type from its leading bytes.
+fn sniff_image_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::testing::{
+        authed_serving_client, has_param, recording_client, routing_client, serving_client,
+    };
+    use crate::api::{post::parse_user_posts, topic::parse_topic_list_response};
+
+    #[test]
+    fn test_parse_online_status() {
+        let now = 1_700_000_000;
+        let status = |xml: &str| parse_online_status(&user_item_attrs(xml).unwrap(), now);
+
+        let recent = "<root><data><item><uid>1</uid><thisvisit>1699999700</thisvisit>\
+</item></data></root>";
+        assert_eq!(
+            status(recent),
+            OnlineStatus::Online {
+                last_active: Some(1_699_999_700)
+            }
+        );
+
+        let stale = "<root><data><item><uid>1</uid><thisvisit>1699990000</thisvisit>\
+</item></data></root>";
+        assert!(!status(stale).is_online());
+        assert_eq!(status(stale).last_active(), Some(1_699_990_000));
+
+        let flagged = "<root><data><item><uid>1</uid><online>1</online></item></data></root>";
+        assert_eq!(status(flagged), OnlineStatus::Online { last_active: None });
+
+        let hidden = "<root><data><item><uid>1</uid><thisvisit>0</thisvisit></item></data></root>";
+        assert_eq!(status(hidden), OnlineStatus::Hidden);
+    }
+
+    #[tokio::test]
+    async fn test_get_many() {
+        let (client, _) = routing_client(
+            "uid",
+            vec![
+                (
+                    "1",
+                    "<root><data><item><uid>1</uid><username>Alice</username></item></data></root>",
+                ),
+                (
+                    "2",
+                    "<root><error><item>User not found</item></error></root>",
+                ),
+                (
+                    "3",
+                    "<root><data><item><uid>3</uid><username>Carol</username></item></data></root>",
+                ),
+            ],
+        );
+
+        let results = client.users().get_many(&["1", "2", "3"], 3).await.unwrap();
+
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "3"]);
+        assert_eq!(results[0].1.as_ref().unwrap().name.display(), "Alice");
+        assert!(results[1].1.is_err());
+        assert_eq!(results[2].1.as_ref().unwrap().name.display(), "Carol");
+    }
+
+    #[tokio::test]
+    async fn test_me_detailed() {
+        let (client, recorder) = authed_serving_client(vec![
+            "<root><data><item>\
+<uid>1</uid><username>Alice</username><fame>25</fame><postnum>1024</postnum>\
+<regdate>1500000000</regdate><email>al***@example.com</email><yz>1</yz>\
+<money>123456</money><credit>88</credit><follow_num>12</follow_num>\
+<follow_by_num>345</follow_by_num>\
+</item></data></root>",
+        ]);
+
+        let profile = client.users().me_detailed().await.unwrap();
+        assert_eq!(profile.user.id.as_str(), "1");
+        assert_eq!(profile.user.name.display(), "Alice");
+        assert_eq!(profile.user.posts, 1024);
+        assert_eq!(profile.email.as_deref(), Some("al***@example.com"));
+        assert!(profile.email_verified);
+        assert_eq!(profile.money, 123456);
+        assert_eq!(profile.credit, 88);
+        assert_eq!(profile.following_count, 12);
+        assert_eq!(profile.follower_count, 345);
+        assert!(has_param(&recorder.first_query(), "uid", "1"));
+    }
+
+    #[tokio::test]
+    async fn test_me_detailed_requires_auth() {
+        let (client, _) = recording_client();
+        assert!(matches!(
+            client.users().me_detailed().await,
+            Err(Error::AuthRequired)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_avatar() {
+        let (client, _) = serving_client(vec!["GIF89a\x01\x00\x01\x00"]);
+        let user = User {
+            avatar_url: Some("avatars/42.gif".into()),
+            ..Default::default()
+        };
+
+        let image = client.users().fetch_avatar(&user).await.unwrap();
+        assert_eq!(image.bytes, b"GIF89a\x01\x00\x01\x00");
+        assert_eq!(image.content_type.as_deref(), Some("image/gif"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_avatar_missing() {
+        let (client, recorder) = recording_client();
+        let user = User::default();
+
+        let result = client.users().fetch_avatar(&user).await;
+        assert!(matches!(result, Err(Error::NoAvatar)));
+        assert!(recorder.queries().is_empty());
+    }
+
+    #[test]
+    fn test_sniff_image_type() {
+        assert_eq!(sniff_image_type(b"\x89PNG\r\n"), Some("image/png"));
+        assert_eq!(sniff_image_type(b"\xFF\xD8\xFF\xE0"), Some("image/jpeg"));
+        assert_eq!(sniff_image_type(b"RIFF\0\0\0\0WEBPVP8"), Some("image/webp"));
+        assert_eq!(sniff_image_type(b"<html>"), None);
+    }
 
     #[test]
     fn test_user_id_creation() {
         let id = UserId::new("12345");
         assert_eq!(id.as_str(), "12345");
     }
+
+    #[tokio::test]
+    async fn test_search_page_query() {
+        let (client, recorder) = recording_client();
+
+        let result = client.users().search_paged("nga").page(2).send().await;
+        assert!(matches!(result, Err(Error::Network(_))));
+
+        let query = recorder.first_query();
+        assert!(has_param(&query, "__act", "search"));
+        assert!(has_param(&query, "key", "nga"));
+        assert!(has_param(&query, "page", "2"));
+    }
+
+    #[test]
+    fn test_parse_user_search_pages() {
+        let xml = r#"<root>
+<data><item uid="1" username="alice"/><item uid="2" username="bob"/></data>
+<__ROWS>45</__ROWS>
+<__PAGE>2</__PAGE>
+</root>"#;
+
+        let result = parse_user_search(xml, 2).unwrap();
+        assert_eq!(result.users.len(), 2);
+        assert_eq!(result.users[1].name, "bob");
+        assert_eq!(result.total_pages, 3);
+        assert_eq!(result.page, 2);
+    }
+
+    #[test]
+    fn test_merge_activity() {
+        let topics = parse_topic_list_response(
+            r#"<root><__T>
+<item><tid>1</tid><subject>Old topic</subject><postdate>1000</postdate></item>
+<item><tid>2</tid><subject>New topic</subject><postdate>3000</postdate></item>
+</__T></root>"#,
+            1,
+        )
+        .unwrap();
+        let posts = parse_user_posts(
+            r#"<root><__T>
+<item><tid>9</tid><pid>91</pid><subject>Reply</subject><postdate>2000</postdate></item>
+<item><tid>9</tid><pid>92</pid><subject>Reply</subject><postdate>4000</postdate></item>
+</__T></root>"#,
+        )
+        .unwrap();
+
+        let activity = merge_activity(topics.topics, posts.posts);
+        let summary: Vec<(&str, i64)> = activity
+            .iter()
+            .map(|a| match a {
+                Activity::Topic(t) => (t.id.as_str(), t.post_date),
+                Activity::Post(p) => (p.post_id.as_str(), p.post_date),
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("92", 4000), ("2", 3000), ("91", 2000), ("1", 1000)]
+        );
+    }
 }
@@ -0,0 +1,236 @@
+//! On-disk cache implementation.
+
+use async_trait::async_trait;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::traits::CacheStorage;
+
+/// Extension of entry files, so `clear` leaves anything else in the directory alone.
+const ENTRY_EXTENSION: &str = "entry";
+
+/// Cache storing one file per key in a directory.
+///
+/// Files are named after a hash of the key, so keys of any length make valid
+/// names. Each holds the expiry time as milliseconds since the Unix epoch (0
+/// for none) in 8 little-endian bytes, the key's length as 4 little-endian
+/// bytes, the key, and then the value.
+#[derive(Debug, Clone)]
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+impl FileCache {
+    /// Create a cache in `dir`, which is created on the first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory the entries are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:032x}.{}", hash_key(key), ENTRY_EXTENSION))
+    }
+
+    /// Paths of all entry files in the directory.
+    async fn entry_paths(&self) -> Vec<PathBuf> {
+        let Ok(mut entries) = tokio::fs::read_dir(&self.dir).await else {
+            return Vec::new();
+        };
+
+        let mut paths = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == ENTRY_EXTENSION) {
+                paths.push(path);
+            }
+        }
+        paths
+    }
+}
+
+#[async_trait]
+impl CacheStorage for FileCache {
+    async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let path = self.entry_path(key);
+        let bytes = tokio::fs::read(&path).await.ok()?;
+        let entry = Entry::decode(&bytes)?;
+        // Another key with the same hash.
+        if entry.key != key {
+            return None;
+        }
+        if entry.expires_at != 0 && now_millis() > entry.expires_at {
+            let _ = tokio::fs::remove_file(&path).await;
+            return None;
+        }
+        Some(entry.value.to_vec())
+    }
+
+    async fn set(&self, key: &str, value: &[u8], ttl: Option<Duration>) {
+        let expires_at = ttl.map_or(0, |ttl| now_millis() + ttl.as_millis() as u64);
+        let mut bytes = Vec::with_capacity(12 + key.len() + value.len());
+        bytes.extend_from_slice(&expires_at.to_le_bytes());
+        bytes.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(key.as_bytes());
+        bytes.extend_from_slice(value);
+
+        if tokio::fs::create_dir_all(&self.dir).await.is_ok() {
+            let _ = tokio::fs::write(self.entry_path(key), bytes).await;
+        }
+    }
+
+    async fn remove(&self, key: &str) {
+        let _ = tokio::fs::remove_file(self.entry_path(key)).await;
+    }
+
+    async fn clear(&self) {
+        for path in self.entry_paths().await {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+
+    async fn scan_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut keys = Vec::new();
+        for path in self.entry_paths().await {
+            let Ok(bytes) = tokio::fs::read(&path).await else {
+                continue;
+            };
+            if let Some(entry) = Entry::decode(&bytes) {
+                if entry.key.starts_with(prefix) {
+                    keys.push(entry.key.to_owned());
+                }
+            }
+        }
+        keys
+    }
+}
+
+/// Contents of an entry file.
+struct Entry<'a> {
+    expires_at: u64,
+    key: &'a str,
+    value: &'a [u8],
+}
+
+impl<'a> Entry<'a> {
+    fn decode(bytes: &'a [u8]) -> Option<Self> {
+        let expires_at = u64::from_le_bytes(bytes.get(..8)?.try_into().ok()?);
+        let key_len = u32::from_le_bytes(bytes.get(8..12)?.try_into().ok()?) as usize;
+        let key = std::str::from_utf8(bytes.get(12..12usize.checked_add(key_len)?)?).ok()?;
+        Some(Self {
+            expires_at,
+            key,
+            value: &bytes[12 + key_len..],
+        })
+    }
+}
+
+/// 128-bit FNV-1a hash of `key`, stable across builds so existing files
+/// keep their names.
+fn hash_key(key: &str) -> u128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    key.bytes().fold(OFFSET_BASIS, |hash, b| {
+        (hash ^ u128::from(b)).wrapping_mul(PRIME)
+    })
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str) -> FileCache {
+        let dir =
+            std::env::temp_dir().join(format!("rnga-file-cache-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        FileCache::new(dir)
+    }
+
+    #[tokio::test]
+    async fn test_basic_operations() {
+        let cache = temp_cache("basic");
+
+        assert_eq!(cache.get("topic:1").await, None);
+        cache.set("topic:1", b"value1", None).await;
+        assert_eq!(cache.get("topic:1").await, Some(b"value1".to_vec()));
+
+        cache.remove("topic:1").await;
+        assert_eq!(cache.get("topic:1").await, None);
+
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+
+    #[tokio::test]
+    async fn test_ttl_expiry() {
+        let cache = temp_cache("ttl");
+
+        cache
+            .set("key", b"value", Some(Duration::from_millis(60)))
+            .await;
+        assert!(cache.get("key").await.is_some());
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(cache.get("key").await.is_none());
+
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix() {
+        let cache = temp_cache("scan");
+
+        cache.set("prefix/a", b"1", None).await;
+        cache.set("prefix/b", b"2", None).await;
+        cache.set("other/c", b"3", None).await;
+
+        let mut keys = cache.scan_prefix("prefix/").await;
+        keys.sort();
+        assert_eq!(keys, vec!["prefix/a", "prefix/b"]);
+
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+
+    #[tokio::test]
+    async fn test_long_keys() {
+        let cache = temp_cache("long");
+        let key = format!("topic:search:{}", "关键词".repeat(100));
+
+        cache.set(&key, b"value", None).await;
+        assert_eq!(cache.get(&key).await, Some(b"value".to_vec()));
+        assert_eq!(cache.scan_prefix("topic:search:").await, vec![key]);
+
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+
+    #[tokio::test]
+    async fn test_clear() {
+        let cache = temp_cache("clear");
+
+        cache.set("a", b"1", None).await;
+        cache.set("b", b"2", None).await;
+        let other = cache.dir().join("notes.txt");
+        std::fs::write(&other, "keep").unwrap();
+
+        cache.clear().await;
+
+        assert!(cache.get("a").await.is_none());
+        assert!(cache.get("b").await.is_none());
+        assert!(other.exists());
+
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+}
@@ -3,16 +3,22 @@
 use async_trait::async_trait;
 use std::{
     collections::HashMap,
-    sync::RwLock,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
     time::{Duration, Instant},
 };
 
-use super::traits::CacheStorage;
+use super::traits::{CacheStats, CacheStorage};
 
 /// In-memory cache with optional TTL support.
 #[derive(Debug, Default)]
 pub struct MemoryCache {
     data: RwLock<HashMap<String, CacheEntry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
 }
 
 #[derive(Debug, Clone)]
@@ -30,7 +36,7 @@ impl CacheEntry {
     }
 
     fn is_expired(&self) -> bool {
-        self.expires_at.map_or(false, |e| Instant::now() > e)
+        self.expires_at.is_some_and(|e| Instant::now() > e)
     }
 }
 
@@ -43,26 +49,60 @@ impl MemoryCache {
     /// Remove expired entries.
     pub fn cleanup(&self) {
         let mut data = self.data.write().unwrap();
+        let before = data.len();
         data.retain(|_, v| !v.is_expired());
+        self.evictions
+            .fetch_add((before - data.len()) as u64, Ordering::Relaxed);
+    }
+
+    /// Snapshot of hit, miss and eviction counters.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Drop an entry if it has expired, counting the eviction.
+    fn evict_expired(&self, key: &str) {
+        let mut data = self.data.write().unwrap();
+        if data.get(key).is_some_and(CacheEntry::is_expired) {
+            data.remove(key);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
     }
 }
 
 #[async_trait]
 impl CacheStorage for MemoryCache {
     async fn get(&self, key: &str) -> Option<Vec<u8>> {
-        let data = self.data.read().unwrap();
-        data.get(key).and_then(|entry| {
-            if entry.is_expired() {
-                None
-            } else {
-                Some(entry.data.clone())
+        let (value, expired) = {
+            let data = self.data.read().unwrap();
+            match data.get(key) {
+                Some(entry) if entry.is_expired() => (None, true),
+                Some(entry) => (Some(entry.data.clone()), false),
+                None => (None, false),
             }
-        })
+        };
+
+        if value.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        if expired {
+            self.evict_expired(key);
+        }
+        value
     }
 
     async fn set(&self, key: &str, value: &[u8], ttl: Option<Duration>) {
         let mut data = self.data.write().unwrap();
-        data.insert(key.to_owned(), CacheEntry::new(value.to_vec(), ttl));
+        let replaced = data.insert(key.to_owned(), CacheEntry::new(value.to_vec(), ttl));
+        if replaced.is_some_and(|entry| entry.is_expired()) {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
     async fn remove(&self, key: &str) {
@@ -82,6 +122,10 @@ impl CacheStorage for MemoryCache {
             .cloned()
             .collect()
     }
+
+    fn stats(&self) -> Option<CacheStats> {
+        Some(MemoryCache::stats(self))
+    }
 }
 
 #[cfg(test)]
@@ -127,6 +171,36 @@ mod tests {
         assert!(cache.get("key").await.is_none());
     }
 
+    #[tokio::test]
+    async fn test_stats() {
+        let cache = MemoryCache::new();
+
+        assert!(cache.get("a").await.is_none());
+        cache.set("a", b"1", None).await;
+        assert!(cache.get("a").await.is_some());
+        assert!(cache.get("b").await.is_none());
+        assert!(cache.get("a").await.is_some());
+
+        cache
+            .set("short", b"2", Some(Duration::from_millis(10)))
+            .await;
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(cache.get("short").await.is_none());
+        assert!(cache.get("short").await.is_none());
+
+        let stats = cache.stats();
+        assert_eq!(
+            stats,
+            CacheStats {
+                hits: 2,
+                misses: 4,
+                evictions: 1,
+            }
+        );
+        assert_eq!(stats.hit_rate(), 2.0 / 6.0);
+        assert_eq!(CacheStorage::stats(&cache), Some(stats));
+    }
+
     #[tokio::test]
     async fn test_clear() {
         let cache = MemoryCache::new();
@@ -1,7 +1,11 @@
 //! Cache storage.
 
+mod file;
 mod memory;
+mod revalidate;
 mod traits;
 
+pub use file::FileCache;
 pub use memory::MemoryCache;
-pub use traits::{CacheStorage, CacheStorageExt};
+pub(crate) use revalidate::{read_offline, stale_while_revalidate, Refreshes};
+pub use traits::{CacheStats, CacheStorage, CacheStorageExt};
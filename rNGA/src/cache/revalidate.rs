@@ -0,0 +1,330 @@
+//! Stale-while-revalidate caching.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashSet,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use super::traits::{CacheStorage, CacheStorageExt};
+use crate::error::{Error, Result};
+
+/// How long entries are kept when `fallback` is enabled, so they can stand
+/// in for the network long after `hard_ttl`.
+pub(crate) const FALLBACK_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Cache keys with a background refresh in flight.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Refreshes(Arc<Mutex<HashSet<String>>>);
+
+impl Refreshes {
+    /// Mark `key` as refreshing, or `None` if it already is.
+    ///
+    /// The key is released when the returned guard is dropped.
+    fn start(&self, key: &str) -> Option<RefreshGuard> {
+        let inserted = self.0.lock().unwrap().insert(key.to_owned());
+        inserted.then(|| RefreshGuard {
+            refreshes: self.clone(),
+            key: key.to_owned(),
+        })
+    }
+
+    /// Whether a refresh of `key` is in flight.
+    #[cfg(test)]
+    fn contains(&self, key: &str) -> bool {
+        self.0.lock().unwrap().contains(key)
+    }
+}
+
+/// Releases a key in `Refreshes` when dropped.
+struct RefreshGuard {
+    refreshes: Refreshes,
+    key: String,
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        self.refreshes.0.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// Serve a value from cache, refreshing it in the background once stale.
+///
+/// Entries younger than `soft_ttl` are returned as is. Older entries are
+/// still returned immediately while `fetch` runs in a spawned task to
+/// replace them, unless a refresh of the same key is already tracked in
+/// `refreshes`. Entries past `hard_ttl` are treated as missing, so the
+/// caller waits for `fetch`.
+///
+/// With `fallback`, entries are kept for `FALLBACK_RETENTION` and an entry
+/// past `hard_ttl` is returned when `fetch` fails with a network error.
+pub(crate) async fn stale_while_revalidate<T, F, Fut>(
+    cache: Arc<dyn CacheStorage>,
+    refreshes: &Refreshes,
+    key: String,
+    soft_ttl: Duration,
+    hard_ttl: Duration,
+    fallback: bool,
+    fetch: F,
+) -> Result<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T>> + Send + 'static,
+{
+    let retention = if fallback {
+        hard_ttl.max(FALLBACK_RETENTION)
+    } else {
+        hard_ttl
+    };
+
+    let expired = match cache.get_aged::<T>(&key).await {
+        Some((value, age)) if age <= hard_ttl => {
+            let guard = if age > soft_ttl {
+                refreshes.start(&key)
+            } else {
+                None
+            };
+            if let Some(guard) = guard {
+                let refresh = fetch();
+                tokio::spawn(async move {
+                    if let Ok(fresh) = refresh.await {
+                        let _ = cache.set_stale(&key, &fresh, retention).await;
+                    }
+                    drop(guard);
+                });
+            }
+            return Ok(value);
+        }
+        cached => cached.map(|(value, _)| value),
+    };
+
+    match fetch().await {
+        Ok(value) => {
+            cache.set_stale(&key, &value, retention).await?;
+            Ok(value)
+        }
+        Err(e) => match expired {
+            Some(value) if fallback && matches!(e, Error::Network(_)) => Ok(value),
+            _ => Err(e),
+        },
+    }
+}
+
+/// Serve a value from cache without touching the network.
+///
+/// Any entry still in the cache is returned regardless of age.
+pub(crate) async fn read_offline<T>(cache: &dyn CacheStorage, key: &str) -> Result<T>
+where
+    T: DeserializeOwned + Send,
+{
+    cache
+        .get_aged::<T>(key)
+        .await
+        .map(|(value, _)| value)
+        .ok_or(Error::Offline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::MemoryCache;
+    use futures::channel::oneshot;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const SOFT_TTL: Duration = Duration::from_millis(50);
+    const HARD_TTL: Duration = Duration::from_millis(200);
+    const KEY: &str = "topic:1:1:";
+
+    /// Fetch that returns how many times it has been called.
+    fn counting_fetch(calls: &Arc<AtomicU32>) -> impl FnOnce() -> std::future::Ready<Result<u32>> {
+        let calls = calls.clone();
+        move || std::future::ready(Ok(calls.fetch_add(1, Ordering::SeqCst) + 1))
+    }
+
+    async fn get(cache: &Arc<dyn CacheStorage>, calls: &Arc<AtomicU32>) -> u32 {
+        stale_while_revalidate(
+            cache.clone(),
+            &Refreshes::default(),
+            KEY.into(),
+            SOFT_TTL,
+            HARD_TTL,
+            false,
+            counting_fetch(calls),
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Like `get`, sharing `refreshes` and keeping entries long enough that
+    /// they can only go soft-stale during a test.
+    async fn get_tracked<Fut>(
+        cache: &Arc<dyn CacheStorage>,
+        refreshes: &Refreshes,
+        fetch: impl FnOnce() -> Fut,
+    ) -> u32
+    where
+        Fut: Future<Output = Result<u32>> + Send + 'static,
+    {
+        let hard_ttl = Duration::from_secs(60);
+        stale_while_revalidate(
+            cache.clone(),
+            refreshes,
+            KEY.into(),
+            SOFT_TTL,
+            hard_ttl,
+            false,
+            fetch,
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Wait until no refresh of `KEY` is in flight.
+    async fn refreshed(refreshes: &Refreshes) {
+        while refreshes.contains(KEY) {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fresh_entry_is_served_from_cache() {
+        let cache: Arc<dyn CacheStorage> = Arc::new(MemoryCache::new());
+        let calls = Arc::new(AtomicU32::new(0));
+
+        assert_eq!(get(&cache, &calls).await, 1);
+        assert_eq!(get(&cache, &calls).await, 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_soft_stale_entry_refreshes_in_background() {
+        let cache: Arc<dyn CacheStorage> = Arc::new(MemoryCache::new());
+        let refreshes = Refreshes::default();
+        let calls = Arc::new(AtomicU32::new(0));
+
+        assert_eq!(
+            get_tracked(&cache, &refreshes, counting_fetch(&calls)).await,
+            1
+        );
+        tokio::time::sleep(SOFT_TTL * 2).await;
+
+        // The stale value is returned while the refresh runs.
+        assert_eq!(
+            get_tracked(&cache, &refreshes, counting_fetch(&calls)).await,
+            1
+        );
+        refreshed(&refreshes).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let refreshed: Option<(u32, bool)> = cache.get_stale(KEY, SOFT_TTL).await;
+        assert_eq!(refreshed, Some((2, false)));
+    }
+
+    #[tokio::test]
+    async fn test_stale_hits_share_one_refresh() {
+        let cache: Arc<dyn CacheStorage> = Arc::new(MemoryCache::new());
+        let refreshes = Refreshes::default();
+        let calls = Arc::new(AtomicU32::new(0));
+
+        get_tracked(&cache, &refreshes, counting_fetch(&calls)).await;
+        tokio::time::sleep(SOFT_TTL * 2).await;
+
+        // Hold the first refresh open until released.
+        let (release, released) = oneshot::channel::<()>();
+        let blocked = {
+            let calls = calls.clone();
+            move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let _ = released.await;
+                    Ok(2)
+                }
+            }
+        };
+        assert_eq!(get_tracked(&cache, &refreshes, blocked).await, 1);
+        assert_eq!(
+            get_tracked(&cache, &refreshes, counting_fetch(&calls)).await,
+            1
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        release.send(()).unwrap();
+        refreshed(&refreshes).await;
+        let refreshed: Option<(u32, bool)> = cache.get_stale(KEY, SOFT_TTL).await;
+        assert_eq!(refreshed, Some((2, false)));
+    }
+
+    #[tokio::test]
+    async fn test_hard_expired_entry_is_refetched() {
+        let cache: Arc<dyn CacheStorage> = Arc::new(MemoryCache::new());
+        let calls = Arc::new(AtomicU32::new(0));
+
+        assert_eq!(get(&cache, &calls).await, 1);
+        tokio::time::sleep(HARD_TTL + Duration::from_millis(50)).await;
+
+        assert_eq!(get(&cache, &calls).await, 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    /// A real network error, from connecting to a closed local port.
+    async fn network_error() -> Error {
+        let url = crate::testing::closed_base_url();
+        Error::Network(reqwest::get(url).await.unwrap_err())
+    }
+
+    #[tokio::test]
+    async fn test_fallback_serves_expired_entry_on_network_error() {
+        let cache: Arc<dyn CacheStorage> = Arc::new(MemoryCache::new());
+        let refreshes = Refreshes::default();
+        let key = String::from(KEY);
+        stale_while_revalidate(
+            cache.clone(),
+            &refreshes,
+            key.clone(),
+            SOFT_TTL,
+            HARD_TTL,
+            true,
+            || std::future::ready(Ok(1u32)),
+        )
+        .await
+        .unwrap();
+        tokio::time::sleep(HARD_TTL + Duration::from_millis(50)).await;
+
+        let error = network_error().await;
+        let value = stale_while_revalidate(
+            cache.clone(),
+            &refreshes,
+            key.clone(),
+            SOFT_TTL,
+            HARD_TTL,
+            true,
+            || std::future::ready(Err::<u32, _>(error)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(value, 1);
+
+        let error = network_error().await;
+        let result =
+            stale_while_revalidate(cache, &refreshes, key, SOFT_TTL, HARD_TTL, false, || {
+                std::future::ready(Err::<u32, _>(error))
+            })
+            .await;
+        assert!(matches!(result, Err(Error::Network(_))));
+    }
+
+    #[tokio::test]
+    async fn test_read_offline() {
+        let cache = MemoryCache::new();
+        cache.set_stale("key", &7u32, HARD_TTL).await.unwrap();
+
+        assert_eq!(read_offline::<u32>(&cache, "key").await.unwrap(), 7);
+        assert!(matches!(
+            read_offline::<u32>(&cache, "other").await,
+            Err(Error::Offline)
+        ));
+    }
+}
@@ -1,7 +1,8 @@
 //! Cache storage trait definitions.
 
 use async_trait::async_trait;
-use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::error::Result;
 
@@ -18,10 +19,40 @@ pub trait CacheStorage: Send + Sync + std::fmt::Debug {
     async fn remove(&self, key: &str);
 
     /// Clear all cached values.
-    async fn clear(&self);
+    ///
+    /// Does nothing by default, for backends that can't enumerate their entries.
+    async fn clear(&self) {}
 
     /// Scan keys with a prefix.
     async fn scan_prefix(&self, prefix: &str) -> Vec<String>;
+
+    /// Snapshot of hit/miss counters, if the backend tracks them.
+    fn stats(&self) -> Option<CacheStats> {
+        None
+    }
+}
+
+/// Cache effectiveness counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups that found a live entry.
+    pub hits: u64,
+    /// Lookups that found nothing or an expired entry.
+    pub misses: u64,
+    /// Expired entries dropped from the cache.
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that were hits, or 0 without lookups.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
 }
 
 /// Extension trait for cache storage with typed operations.
@@ -44,10 +75,58 @@ pub trait CacheStorageExt: CacheStorage {
         self.set(key, &data, ttl).await;
         Ok(())
     }
+
+    /// Get a value stored with `set_stale` along with its age.
+    async fn get_aged<T: serde::de::DeserializeOwned + Send>(
+        &self,
+        key: &str,
+    ) -> Option<(T, Duration)> {
+        let entry: StaleEntry<T> = self.get_json(key).await?;
+        let age = now_millis().saturating_sub(entry.stored_at);
+        Some((entry.value, Duration::from_millis(age)))
+    }
+
+    /// Get a value stored with `set_stale`, flagged stale once older than `soft_ttl`.
+    async fn get_stale<T: serde::de::DeserializeOwned + Send>(
+        &self,
+        key: &str,
+        soft_ttl: Duration,
+    ) -> Option<(T, bool)> {
+        let (value, age) = self.get_aged(key).await?;
+        Some((value, age > soft_ttl))
+    }
+
+    /// Set a value for `get_stale`, evicted entirely after `hard_ttl`.
+    async fn set_stale<T: serde::Serialize + Sync>(
+        &self,
+        key: &str,
+        value: &T,
+        hard_ttl: Duration,
+    ) -> Result<()> {
+        let entry = StaleEntry {
+            stored_at: now_millis(),
+            value,
+        };
+        self.set_json(key, &entry, Some(hard_ttl)).await
+    }
 }
 
 // Blanket implementation
-impl<T: CacheStorage> CacheStorageExt for T {}
+impl<T: CacheStorage + ?Sized> CacheStorageExt for T {}
+
+/// A cached value with the time it was stored.
+#[derive(Serialize, Deserialize)]
+struct StaleEntry<T> {
+    stored_at: u64,
+    value: T,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
 
 #[cfg(test)]
 mod tests {
@@ -72,4 +151,64 @@ mod tests {
         let result: Option<TestData> = cache.get_json(key).await;
         assert_eq!(result, Some(value));
     }
+
+    /// Backend that stores values but leaves `clear` to the default.
+    #[derive(Debug, Default)]
+    struct NoClear(std::sync::Mutex<std::collections::HashMap<String, Vec<u8>>>);
+
+    #[async_trait]
+    impl CacheStorage for NoClear {
+        async fn get(&self, key: &str) -> Option<Vec<u8>> {
+            self.0.lock().unwrap().get(key).cloned()
+        }
+        async fn set(&self, key: &str, value: &[u8], _ttl: Option<Duration>) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(key.to_owned(), value.to_vec());
+        }
+        async fn remove(&self, key: &str) {
+            self.0.lock().unwrap().remove(key);
+        }
+        async fn scan_prefix(&self, _prefix: &str) -> Vec<String> {
+            Vec::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clear_defaults_to_noop() {
+        let cache = NoClear::default();
+        cache.set("key", b"value", None).await;
+        cache.clear().await;
+        assert_eq!(cache.get("key").await, Some(b"value".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_get_stale() {
+        let cache = MemoryCache::new();
+        let value = TestData {
+            value: "hello".into(),
+        };
+
+        cache
+            .set_stale("key", &value, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        let fresh: Option<(TestData, bool)> = cache.get_stale("key", Duration::from_secs(30)).await;
+        assert_eq!(
+            fresh,
+            Some((
+                TestData {
+                    value: "hello".into()
+                },
+                false
+            ))
+        );
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let stale: Option<(TestData, bool)> =
+            cache.get_stale("key", Duration::from_millis(10)).await;
+        assert_eq!(stale, Some((value, true)));
+    }
 }
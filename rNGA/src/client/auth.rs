@@ -1,7 +1,9 @@
 //! Authentication state management.
 
+use super::observer::REDACTED;
+
 /// Authentication information for NGA.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct AuthInfo {
     /// Access token.
     pub token: String,
@@ -24,6 +26,15 @@ impl AuthInfo {
     }
 }
 
+impl std::fmt::Debug for AuthInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthInfo")
+            .field("token", &REDACTED)
+            .field("uid", &self.uid)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,4 +53,13 @@ mod tests {
         let zero_uid = AuthInfo::new("token123", "0");
         assert!(!zero_uid.is_valid());
     }
+
+    #[test]
+    fn test_auth_info_debug_redacts_token() {
+        let auth = AuthInfo::new("super_secret_token", "12345");
+        let debug = format!("{:?}", auth);
+        assert!(!debug.contains("super_secret_token"));
+        assert!(debug.contains("12345"));
+        assert!(debug.contains(REDACTED));
+    }
 }
@@ -1,9 +1,13 @@
 //! HTTP client configuration and request execution.
 
+use super::metrics::Metrics;
+use super::observer::{redact_form, RequestObserver};
 use crate::error::{Error, Result};
-use encoding_rs::GB18030;
+use crate::parser::xml::check_html_page;
+use encoding_rs::{Encoding, GB18030};
 use reqwest::{Client, Method, RequestBuilder, Response};
-use std::time::Duration;
+use std::future::Future;
+use std::time::{Duration, Instant};
 use url::Url;
 
 /// Decode bytes as GB18030.
@@ -12,9 +16,76 @@ fn decode_gb18030(bytes: &[u8]) -> String {
     text.into_owned()
 }
 
+/// Extract the charset label from a `Content-Type` header value.
+fn charset_from_content_type(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// Decode a response body.
+///
+/// Uses the `Content-Type` charset when present; otherwise prefers UTF-8 if the
+/// bytes are valid UTF-8 and falls back to GB18030.
+fn decode_body(bytes: &[u8], content_type: Option<&str>) -> String {
+    let declared = content_type
+        .and_then(charset_from_content_type)
+        .and_then(|label| Encoding::for_label(label.as_bytes()));
+
+    if let Some(encoding) = declared {
+        let (text, _, _) = encoding.decode(bytes);
+        return text.into_owned();
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_owned(),
+        Err(_) => decode_gb18030(bytes),
+    }
+}
+
+/// Value of a response header, if present and valid ASCII.
+fn header_str(response: &Response, name: reqwest::header::HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned)
+}
+
+/// Parse a `Retry-After` value, given either in seconds or as an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.signed_duration_since(chrono::Utc::now());
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Map a rate limiting status to `Error::RateLimited`.
+///
+/// 429 always counts; 503 only when the server says when to come back,
+/// since NGA also answers 503 during maintenance.
+fn rate_limit_error(status: reqwest::StatusCode, retry_after: &Option<String>) -> Option<Error> {
+    let retry_after = retry_after.as_deref().and_then(parse_retry_after);
+    match status.as_u16() {
+        429 => Some(Error::RateLimited { retry_after }),
+        503 if retry_after.is_some() => Some(Error::RateLimited { retry_after }),
+        _ => None,
+    }
+}
+
 /// Default NGA API base URL.
 pub const DEFAULT_BASE_URL: &str = "https://nga.178.com/";
 
+/// Base URL of post images and attachments given as relative paths.
+pub const ATTACHMENT_BASE_URL: &str = "https://img.nga.178.com/attachments/";
+
 /// Forum icon CDN path.
 pub const FORUM_ICON_PATH: &str = "http://img4.ngacn.cc/ngabbs/nga_classic/f/app/";
 
@@ -48,6 +119,22 @@ impl Device {
     }
 }
 
+impl std::str::FromStr for Device {
+    type Err = Error;
+
+    /// Parse a device name: `apple`, `android`, `desktop` or `windows-phone`,
+    /// plus a few aliases such as `ios` and `wp`.
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "apple" | "ios" => Ok(Device::Apple),
+            "android" => Ok(Device::Android),
+            "desktop" | "web" => Ok(Device::Desktop),
+            "windows-phone" | "windows_phone" | "windowsphone" | "wp" => Ok(Device::WindowsPhone),
+            _ => Err(Error::InvalidArgument(format!("Unknown device: {}", value))),
+        }
+    }
+}
+
 /// HTTP client configuration.
 #[derive(Debug, Clone)]
 pub struct HttpConfig {
@@ -61,6 +148,17 @@ pub struct HttpConfig {
     pub device: Device,
     /// Custom user agent.
     pub custom_user_agent: Option<String>,
+    /// Mirror base URLs tried in order when the primary one fails.
+    pub fallback_base_urls: Vec<String>,
+    /// Extra headers sent with every request to the base URL or a mirror.
+    pub extra_headers: Vec<(String, String)>,
+    /// Request JSON instead of XML from endpoints that support it.
+    pub prefer_json: bool,
+    /// Never touch the network; cached reads are served from cache and
+    /// everything else fails with `Error::Offline`.
+    pub offline: bool,
+    /// Serve expired cache entries when a cached read hits a network error.
+    pub cache_fallback: bool,
 }
 
 impl Default for HttpConfig {
@@ -71,6 +169,11 @@ impl Default for HttpConfig {
             read_timeout: Duration::from_secs(20),
             device: Device::default(),
             custom_user_agent: None,
+            fallback_base_urls: Vec::new(),
+            extra_headers: Vec::new(),
+            prefer_json: false,
+            offline: false,
+            cache_fallback: false,
         }
     }
 }
@@ -87,18 +190,104 @@ impl HttpConfig {
         self.device.user_agent()
     }
 
+    /// Check the whole configuration, so mistakes surface when the client is
+    /// built rather than on its first request.
+    pub fn validate(&self) -> Result<()> {
+        for base_url in self.base_urls() {
+            validate_base_url(base_url)?;
+        }
+        if self.connect_timeout.is_zero() {
+            return Err(Error::InvalidArgument(
+                "Connect timeout must be greater than zero".into(),
+            ));
+        }
+        if self.read_timeout.is_zero() {
+            return Err(Error::InvalidArgument(
+                "Read timeout must be greater than zero".into(),
+            ));
+        }
+        self.validate_headers()
+    }
+
+    /// Check that extra headers are valid and don't override reserved ones.
+    pub fn validate_headers(&self) -> Result<()> {
+        for (name, value) in &self.extra_headers {
+            if RESERVED_HEADERS
+                .iter()
+                .any(|reserved| reserved.eq_ignore_ascii_case(name))
+            {
+                return Err(Error::InvalidArgument(format!(
+                    "Header '{}' is set by the client and cannot be overridden",
+                    name
+                )));
+            }
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| Error::InvalidArgument(format!("Invalid header name '{}'", name)))?;
+            reqwest::header::HeaderValue::from_str(value).map_err(|_| {
+                Error::InvalidArgument(format!("Invalid value for header '{}'", name))
+            })?;
+        }
+        Ok(())
+    }
+
     /// Resolve a relative API path to a full URL.
     pub fn resolve_url(&self, api: &str) -> Result<Url> {
-        if api.starts_with("http://") || api.starts_with("https://") {
-            return Url::parse(api).map_err(Error::Url);
-        }
+        resolve_url_with(&self.base_url, api)
+    }
+
+    /// Base URL followed by the fallback mirrors.
+    pub fn base_urls(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.base_url.as_str())
+            .chain(self.fallback_base_urls.iter().map(String::as_str))
+    }
 
-        Url::parse(&self.base_url)
-            .and_then(|b| b.join(api))
-            .map_err(Error::Url)
+    /// Whether `url` is on the same origin as the base URL or a mirror, as
+    /// opposed to a third-party host such as an image CDN.
+    pub fn is_site_url(&self, url: &Url) -> bool {
+        self.base_urls()
+            .filter_map(|base| Url::parse(base).ok())
+            .any(|base| base.origin() == url.origin())
     }
 }
 
+/// Metrics label shared by all `HttpExecutor::get_bytes` downloads, so
+/// per-file paths don't each get their own endpoint entry.
+const DOWNLOAD_METRICS_LABEL: &str = "image";
+
+/// Headers set on every request, which extra headers may not override.
+const RESERVED_HEADERS: &[&str] = &["User-Agent", "X-User-Agent", "Referer"];
+
+/// Check that a base URL is an absolute HTTP(S) URL that API paths can be
+/// joined onto, i.e. whose path ends with `/` and that has no query.
+fn validate_base_url(base_url: &str) -> Result<()> {
+    let invalid = |reason: &str| {
+        Error::InvalidArgument(format!("Invalid base URL '{}': {}", base_url, reason))
+    };
+
+    let url = Url::parse(base_url).map_err(|e| invalid(&e.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") || !url.has_host() {
+        return Err(invalid("expected an http or https URL"));
+    }
+    if !url.path().ends_with('/') {
+        return Err(invalid("path must end with '/'"));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(invalid("query and fragment are not allowed"));
+    }
+    Ok(())
+}
+
+/// Resolve a relative API path against the given base URL.
+fn resolve_url_with(base_url: &str, api: &str) -> Result<Url> {
+    if api.starts_with("http://") || api.starts_with("https://") {
+        return Url::parse(api).map_err(Error::Url);
+    }
+
+    Url::parse(base_url)
+        .and_then(|b| b.join(api))
+        .map_err(Error::Url)
+}
+
 /// Build a reqwest client with the given configuration.
 pub fn build_client(config: &HttpConfig) -> Result<Client> {
     Client::builder()
@@ -111,14 +300,13 @@ pub fn build_client(config: &HttpConfig) -> Result<Client> {
 }
 
 /// Query parameter strategies for NGA API.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResponseFormat {
     /// XML format: `lite=xml`
     Xml,
     /// Compact XML: `__output=10`
     CompactXml,
     /// JSON format: `__output=8`
-    #[allow(dead_code)]
     Json,
 }
 
@@ -133,28 +321,98 @@ impl ResponseFormat {
     }
 }
 
+/// XML formats endpoints are known to answer with, keyed by api and `__lib`.
+///
+/// An entry without a lib covers every lib of that api. Unlisted endpoints
+/// start with `Xml`.
+const FORMAT_HINTS: &[(&str, Option<&str>, ResponseFormat)] = &[
+    ("read.php", None, ResponseFormat::Xml),
+    ("thread.php", None, ResponseFormat::Xml),
+    ("nuke.php", Some("topic_favor_v2"), ResponseFormat::Xml),
+    ("nuke.php", None, ResponseFormat::CompactXml),
+];
+
+/// XML format to try first for an endpoint.
+fn format_hint(api: &str, query: &[(&str, &str)]) -> ResponseFormat {
+    let lib = query.iter().find(|(k, _)| *k == "__lib").map(|(_, v)| *v);
+    FORMAT_HINTS
+        .iter()
+        .find(|(hint_api, hint_lib, _)| {
+            *hint_api == api && hint_lib.is_none_or(|hint_lib| Some(hint_lib) == lib)
+        })
+        .map_or(ResponseFormat::Xml, |(_, _, format)| *format)
+}
+
+/// Whether a request that failed with `error` may be retried on a mirror.
+///
+/// Only failures where the server can't have seen the request qualify.
+fn should_fail_over(error: &Error) -> bool {
+    match error {
+        Error::Network(e) => e.is_connect(),
+        Error::Maintenance => true,
+        _ => false,
+    }
+}
+
 /// HTTP request executor.
 pub struct HttpExecutor<'a> {
     client: &'a Client,
     config: &'a HttpConfig,
+    observer: Option<&'a dyn RequestObserver>,
+    metrics: Option<&'a Metrics>,
+    timeout: Option<Duration>,
 }
 
 impl<'a> HttpExecutor<'a> {
     /// Create a new executor.
     pub fn new(client: &'a Client, config: &'a HttpConfig) -> Self {
-        Self { client, config }
+        Self {
+            client,
+            config,
+            observer: None,
+            metrics: None,
+            timeout: None,
+        }
+    }
+
+    /// Record request counts and latencies in `metrics`.
+    pub fn with_metrics(mut self, metrics: Option<&'a Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Notify an observer of every request and response.
+    pub fn with_observer(mut self, observer: Option<&'a dyn RequestObserver>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    /// Override the total timeout of each request.
+    ///
+    /// Falls back to the configured connect and read timeouts when unset.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
     }
 
     /// Build a request with common headers.
     fn build_request(&self, method: Method, url: Url, api: &str) -> RequestBuilder {
         let ua = self.config.user_agent_for(api);
         let referer = url.to_string();
+        let is_site = self.config.is_site_url(&url);
 
-        self.client
+        let mut request = self
+            .client
             .request(method, url)
             .header("User-Agent", ua)
             .header("X-User-Agent", ua)
-            .header("Referer", referer)
+            .header("Referer", referer);
+        if is_site {
+            for (name, value) in &self.config.extra_headers {
+                request = request.header(name.as_str(), value.as_str());
+            }
+        }
+        request
     }
 
     /// Execute a POST request with form data and return the response text.
@@ -178,7 +436,24 @@ impl<'a> HttpExecutor<'a> {
         auth: Option<(&str, &str)>,
         format: ResponseFormat,
     ) -> Result<String> {
-        let url = self.config.resolve_url(api)?;
+        self.post_form_at(&self.config.base_url, api, query, form, auth, format)
+            .await
+    }
+
+    /// Execute a POST request against a specific base URL.
+    async fn post_form_at(
+        &self,
+        base_url: &str,
+        api: &str,
+        query: &[(&str, &str)],
+        form: &[(&str, &str)],
+        auth: Option<(&str, &str)>,
+        format: ResponseFormat,
+    ) -> Result<String> {
+        if self.config.offline {
+            return Err(Error::Offline);
+        }
+        let url = resolve_url_with(base_url, api)?;
 
         let mut full_query: Vec<(&str, &str)> = query
             .iter()
@@ -197,16 +472,43 @@ impl<'a> HttpExecutor<'a> {
             full_form.push(("access_uid", ""));
         }
 
-        let request = self
+        if let Some(observer) = self.observer {
+            observer.on_request(api, &full_query, &redact_form(&full_form));
+        }
+
+        let mut request = self
             .build_request(Method::POST, url, api)
             .query(&full_query)
             .form(&full_form);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
 
-        let response = request.send().await.map_err(Error::Network)?;
-        self.handle_response(response).await
+        self.timed(api, async {
+            let response = request.send().await.map_err(Error::Network)?;
+            self.handle_response(api, response).await
+        })
+        .await
+    }
+
+    /// Await `request`, recording its latency under `api` if metrics are enabled.
+    async fn timed<T>(&self, api: &str, request: impl Future<Output = Result<T>>) -> Result<T> {
+        let Some(metrics) = self.metrics else {
+            return request.await;
+        };
+
+        let started = Instant::now();
+        let result = request.await;
+        metrics.record(api, started.elapsed(), result.is_ok());
+        result
     }
 
     /// Execute a POST request with XML response and automatic retry.
+    ///
+    /// Mirrors from `fallback_base_urls` are tried in order when a request
+    /// can't connect or hits a maintenance page. Other network errors, such
+    /// as a timeout after the body was sent, are returned as is: the mirrors
+    /// share one backend, so resending a write could post it twice.
     pub async fn post_form_xml(
         &self,
         api: &str,
@@ -214,15 +516,50 @@ impl<'a> HttpExecutor<'a> {
         form: &[(&str, &str)],
         auth: Option<(&str, &str)>,
     ) -> Result<String> {
+        let mut last_error = None;
+
+        for base_url in self.config.base_urls() {
+            match self
+                .post_form_xml_at(base_url, api, query, form, auth)
+                .await
+            {
+                Err(e) if should_fail_over(&e) => last_error = Some(e),
+                result => return result,
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| Error::Internal("No base URL configured".into())))
+    }
+
+    /// Execute an XML request against one base URL, retrying other formats.
+    ///
+    /// Starts with the endpoint's hinted format, so a well-formed answer
+    /// costs a single request. Maintenance and verification pages are
+    /// reported as their own errors rather than retried.
+    async fn post_form_xml_at(
+        &self,
+        base_url: &str,
+        api: &str,
+        query: &[(&str, &str)],
+        form: &[(&str, &str)],
+        auth: Option<(&str, &str)>,
+    ) -> Result<String> {
+        let first = format_hint(api, query);
+        let second = match first {
+            ResponseFormat::CompactXml => ResponseFormat::Xml,
+            _ => ResponseFormat::CompactXml,
+        };
+
         let text = self
-            .post_form_with_format(api, query, form, auth, ResponseFormat::Xml)
+            .post_form_at(base_url, api, query, form, auth, first)
             .await?;
+        check_html_page(&text)?;
         if !text.is_empty() && sxd_document::parser::parse(&text).is_ok() {
             return Ok(text);
         }
 
         let text = self
-            .post_form_with_format(api, query, form, auth, ResponseFormat::CompactXml)
+            .post_form_at(base_url, api, query, form, auth, second)
             .await?;
         if !text.is_empty() && sxd_document::parser::parse(&text).is_ok() {
             return Ok(text);
@@ -230,7 +567,7 @@ impl<'a> HttpExecutor<'a> {
 
         if auth.is_some() {
             let text = self
-                .post_form_with_format(api, query, form, None, ResponseFormat::Xml)
+                .post_form_at(base_url, api, query, form, None, ResponseFormat::Xml)
                 .await?;
             if !text.is_empty() && sxd_document::parser::parse(&text).is_ok() {
                 return Ok(text);
@@ -243,12 +580,26 @@ impl<'a> HttpExecutor<'a> {
     }
 
     /// Handle response, decoding with proper charset.
-    async fn handle_response(&self, response: Response) -> Result<String> {
+    async fn handle_response(&self, api: &str, response: Response) -> Result<String> {
         let status = response.status();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned);
+        let retry_after = header_str(&response, reqwest::header::RETRY_AFTER);
 
         let bytes = response.bytes().await.map_err(Error::Network)?;
 
-        let text = decode_gb18030(&bytes);
+        let text = decode_body(&bytes, content_type.as_deref());
+
+        if let Some(observer) = self.observer {
+            observer.on_response(api, status.as_u16(), &text);
+        }
+
+        if let Some(error) = rate_limit_error(status, &retry_after) {
+            return Err(error);
+        }
 
         if text.is_empty() && !status.is_success() {
             return Err(Error::nga(
@@ -260,8 +611,47 @@ impl<'a> HttpExecutor<'a> {
         Ok(text)
     }
 
+    /// Download a resource, returning its bytes and content type.
+    ///
+    /// `url` may be absolute, scheme-relative or relative to the base URL.
+    pub async fn get_bytes(&self, url: &str) -> Result<(Vec<u8>, Option<String>)> {
+        if self.config.offline {
+            return Err(Error::Offline);
+        }
+        let url = self.config.resolve_url(url)?;
+
+        let mut request = self.build_request(Method::GET, url, DOWNLOAD_METRICS_LABEL);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+
+        self.timed(DOWNLOAD_METRICS_LABEL, async {
+            let response = request.send().await.map_err(Error::Network)?;
+            let status = response.status();
+            let retry_after = header_str(&response, reqwest::header::RETRY_AFTER);
+            if let Some(error) = rate_limit_error(status, &retry_after) {
+                return Err(error);
+            }
+            if !status.is_success() {
+                return Err(Error::nga(
+                    status.as_u16().to_string(),
+                    status.canonical_reason().unwrap_or("Unknown error"),
+                ));
+            }
+
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned);
+            let bytes = response.bytes().await.map_err(Error::Network)?;
+
+            Ok((bytes.to_vec(), content_type))
+        })
+        .await
+    }
+
     /// Execute a JSON request.
-    #[allow(dead_code)]
     pub async fn post_json(
         &self,
         api: &str,
@@ -278,12 +668,24 @@ impl<'a> HttpExecutor<'a> {
 }
 
 /// Parse JSON response from NGA.
-#[allow(dead_code)]
 fn parse_json_response(text: &str) -> Result<serde_json::Value> {
-    let mut value: serde_json::Value = serde_json::from_str(&text)
+    let mut value: serde_json::Value = serde_json::from_str(text)
         .or_else(|_| serde_json::from_str(text))
         .map_err(Error::Json)?;
 
+    if let Some(error) = value.get("error") {
+        let message = match error {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Object(map) => map
+                .values()
+                .find_map(|v| v.as_str())
+                .unwrap_or_default()
+                .to_owned(),
+            other => other.to_string(),
+        };
+        return Err(Error::from_nga("-1", message));
+    }
+
     if let Some(data) = value.get_mut("data") {
         Ok(data.take())
     } else {
@@ -294,6 +696,397 @@ fn parse_json_response(text: &str) -> Result<serde_json::Value> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client::REDACTED;
+    use crate::testing::{closed_base_url, serve, serve_bodies, serve_silent, Routes};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        requests: Mutex<Vec<(String, String)>>,
+        queries: Mutex<Vec<String>>,
+        responses: Mutex<Vec<(String, u16, String)>>,
+    }
+
+    impl RequestObserver for RecordingObserver {
+        fn on_request(&self, api: &str, query: &[(&str, &str)], form: &[(&str, &str)]) {
+            let join = |pairs: &[(&str, &str)]| {
+                pairs
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect::<Vec<_>>()
+                    .join("&")
+            };
+            self.requests
+                .lock()
+                .unwrap()
+                .push((api.to_owned(), join(form)));
+            self.queries.lock().unwrap().push(join(query));
+        }
+
+        fn on_response(&self, api: &str, status: u16, body: &str) {
+            self.responses
+                .lock()
+                .unwrap()
+                .push((api.to_owned(), status, body.to_owned()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_bytes_metrics_label() {
+        let base_url = serve_bodies(vec!["avatar"]);
+        let config = HttpConfig::default();
+        let client = build_client(&config).unwrap();
+        let metrics = Metrics::new();
+
+        let (bytes, _) = HttpExecutor::new(&client, &config)
+            .with_metrics(Some(&metrics))
+            .get_bytes(&format!("{}avatars/1/2/3.jpg", base_url))
+            .await
+            .unwrap();
+        assert_eq!(bytes, b"avatar");
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot.endpoints.keys().collect::<Vec<_>>(),
+            vec![DOWNLOAD_METRICS_LABEL]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failover_on_network_error() {
+        let mirror = serve_bodies(vec!["<root><ok>1</ok></root>"]);
+        let config = HttpConfig {
+            base_url: closed_base_url(),
+            fallback_base_urls: vec![mirror],
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+
+        let text = HttpExecutor::new(&client, &config)
+            .post_form_xml("thread.php", &[], &[], None)
+            .await
+            .unwrap();
+        assert_eq!(text, "<root><ok>1</ok></root>");
+    }
+
+    #[tokio::test]
+    async fn test_no_failover_after_request_sent() {
+        // Read the whole request, then never answer.
+        let config = HttpConfig {
+            base_url: serve_silent(),
+            fallback_base_urls: vec![serve_bodies(vec!["<root><ok>1</ok></root>"])],
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+        let observer = RecordingObserver::default();
+
+        let result = HttpExecutor::new(&client, &config)
+            .with_observer(Some(&observer))
+            .with_timeout(Some(Duration::from_millis(200)))
+            .post_form_xml("post.php", &[], &[("post_content", "hi")], None)
+            .await;
+        match result {
+            Err(Error::Network(e)) => assert!(e.is_timeout()),
+            other => panic!("expected timeout, got {:?}", other),
+        }
+        assert_eq!(observer.requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_response() {
+        let config = HttpConfig {
+            base_url: serve(
+                Routes::InOrder(vec!["<html><body>Too many requests</body></html>"]),
+                "429 Too Many Requests",
+                "Retry-After: 7\r\n",
+            ),
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+
+        let result = HttpExecutor::new(&client, &config)
+            .post_form_xml("thread.php", &[], &[], None)
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::RateLimited { retry_after: Some(d) }) if d == Duration::from_secs(7)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_service_unavailable_with_retry_after() {
+        let config = HttpConfig {
+            base_url: serve(
+                Routes::InOrder(vec!["busy"]),
+                "503 Service Unavailable",
+                "Retry-After: 30\r\n",
+            ),
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+
+        let result = HttpExecutor::new(&client, &config)
+            .post_form_xml("thread.php", &[], &[], None)
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::RateLimited {
+                retry_after: Some(_)
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after(" 120 "), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn test_device_from_str() {
+        assert_eq!("apple".parse::<Device>().unwrap(), Device::Apple);
+        assert_eq!("Android".parse::<Device>().unwrap(), Device::Android);
+        assert_eq!("desktop".parse::<Device>().unwrap(), Device::Desktop);
+        assert_eq!(
+            "windows-phone".parse::<Device>().unwrap(),
+            Device::WindowsPhone
+        );
+        assert!(matches!(
+            "toaster".parse::<Device>(),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_hint() {
+        assert_eq!(format_hint("read.php", &[]), ResponseFormat::Xml);
+        assert_eq!(
+            format_hint("nuke.php", &[("__lib", "noti")]),
+            ResponseFormat::CompactXml
+        );
+        assert_eq!(
+            format_hint("nuke.php", &[("__lib", "topic_favor_v2")]),
+            ResponseFormat::Xml
+        );
+        assert_eq!(format_hint("other.php", &[]), ResponseFormat::Xml);
+    }
+
+    #[tokio::test]
+    async fn test_hinted_format_single_request() {
+        let config = HttpConfig {
+            base_url: serve_bodies(vec!["<root><data/></root>"]),
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+        let observer = RecordingObserver::default();
+
+        HttpExecutor::new(&client, &config)
+            .with_observer(Some(&observer))
+            .post_form_xml("nuke.php", &[("__lib", "noti")], &[], None)
+            .await
+            .unwrap();
+
+        let queries = observer.queries.lock().unwrap();
+        assert_eq!(queries.len(), 1);
+        assert!(queries[0].contains("__output=10"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_raw_passes_body_through() {
+        let body = "<root><__MESSAGE><item>unparsed";
+        let client = crate::NGAClient::builder()
+            .base_url(serve_bodies(vec![body]))
+            .build()
+            .unwrap();
+
+        let text = client
+            .fetch_raw("thread.php", &[("fid", "7")], &[])
+            .await
+            .unwrap();
+        assert_eq!(text, body);
+    }
+
+    #[tokio::test]
+    async fn test_failover_on_maintenance_page() {
+        let primary = serve_bodies(vec!["<html><body>服务器维护中，请稍后访问</body></html>"]);
+        let mirror = serve_bodies(vec!["<root/>"]);
+        let config = HttpConfig {
+            base_url: primary,
+            fallback_base_urls: vec![mirror],
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+
+        let text = HttpExecutor::new(&client, &config)
+            .post_form_xml("thread.php", &[], &[], None)
+            .await
+            .unwrap();
+        assert_eq!(text, "<root/>");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_override() {
+        let config = HttpConfig {
+            base_url: serve_silent(),
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+
+        let result = HttpExecutor::new(&client, &config)
+            .with_timeout(Some(Duration::from_millis(100)))
+            .post_form("thread.php", &[], &[], None)
+            .await;
+        match result {
+            Err(Error::Network(e)) => assert!(e.is_timeout()),
+            other => panic!("expected timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verification_page_error() {
+        let config = HttpConfig {
+            base_url: serve_bodies(vec!["<html><body>请输入验证码</body></html>"]),
+            fallback_base_urls: vec![closed_base_url()],
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+
+        let result = HttpExecutor::new(&client, &config)
+            .post_form_xml("thread.php", &[], &[], None)
+            .await;
+        assert!(matches!(result, Err(Error::VerificationRequired)));
+    }
+
+    #[tokio::test]
+    async fn test_failover_exhausted() {
+        let config = HttpConfig {
+            base_url: closed_base_url(),
+            fallback_base_urls: vec![closed_base_url()],
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+
+        let result = HttpExecutor::new(&client, &config)
+            .post_form_xml("thread.php", &[], &[], None)
+            .await;
+        assert!(matches!(result, Err(Error::Network(_))));
+    }
+
+    #[tokio::test]
+    async fn test_observer_sees_request_and_response() {
+        let config = HttpConfig {
+            base_url: serve_bodies(vec!["<root/>"]),
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+        let observer = RecordingObserver::default();
+
+        let text = HttpExecutor::new(&client, &config)
+            .with_observer(Some(&observer))
+            .post_form("nuke.php", &[], &[("fid", "7")], Some(("secret", "42")))
+            .await
+            .unwrap();
+        assert_eq!(text, "<root/>");
+
+        let requests = observer.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let (api, form) = &requests[0];
+        assert_eq!(api, "nuke.php");
+        assert_eq!(
+            form,
+            &format!("fid=7&access_token={}&access_uid=42", REDACTED)
+        );
+
+        let responses = observer.responses.lock().unwrap();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0], ("nuke.php".into(), 200, "<root/>".into()));
+    }
+
+    #[test]
+    fn test_decode_utf8_body() {
+        let body = "<root>中文</root>".as_bytes();
+        assert_eq!(decode_body(body, None), "<root>中文</root>");
+        assert_eq!(
+            decode_body(body, Some("text/xml; charset=UTF-8")),
+            "<root>中文</root>"
+        );
+    }
+
+    #[test]
+    fn test_decode_gb18030_body() {
+        let (body, _, _) = GB18030.encode("<root>中文</root>");
+        assert!(std::str::from_utf8(&body).is_err());
+
+        assert_eq!(decode_body(&body, None), "<root>中文</root>");
+        assert_eq!(
+            decode_body(&body, Some("text/xml; charset=\"GBK\"")),
+            "<root>中文</root>"
+        );
+    }
+
+    #[test]
+    fn test_charset_from_content_type() {
+        assert_eq!(
+            charset_from_content_type("text/html; Charset=gbk"),
+            Some("gbk")
+        );
+        assert_eq!(charset_from_content_type("text/xml"), None);
+    }
+
+    #[test]
+    fn test_extra_headers_applied() {
+        let config = HttpConfig {
+            extra_headers: vec![("X-Forwarded-For".into(), "10.0.0.1".into())],
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+        let url = config.resolve_url("thread.php").unwrap();
+
+        let request = HttpExecutor::new(&client, &config)
+            .build_request(Method::GET, url, "thread.php")
+            .build()
+            .unwrap();
+        let headers = request.headers();
+        assert_eq!(headers["X-Forwarded-For"], "10.0.0.1");
+        assert_eq!(headers["User-Agent"], config.user_agent_for("thread.php"));
+    }
+
+    #[test]
+    fn test_extra_headers_only_sent_to_site() {
+        let config = HttpConfig {
+            fallback_base_urls: vec!["https://ngabbs.com/".into()],
+            extra_headers: vec![("Authorization".into(), "secret".into())],
+            ..HttpConfig::default()
+        };
+        let client = build_client(&config).unwrap();
+        let executor = HttpExecutor::new(&client, &config);
+        let has_header = |url: &str| {
+            executor
+                .build_request(Method::GET, Url::parse(url).unwrap(), "image")
+                .build()
+                .unwrap()
+                .headers()
+                .contains_key("Authorization")
+        };
+
+        assert!(has_header("https://nga.178.com/thread.php"));
+        assert!(has_header("https://ngabbs.com/read.php"));
+        assert!(!has_header("https://img.nga.178.com/attachments/a.jpg"));
+        assert!(!has_header("http://nga.178.com/thread.php"));
+    }
+
+    #[test]
+    fn test_parse_json_response() {
+        let data = parse_json_response(r#"{"data": [1, 2]}"#).unwrap();
+        assert_eq!(data, serde_json::json!([1, 2]));
+
+        let err = parse_json_response(r#"{"error": {"0": "no permission"}}"#).unwrap_err();
+        assert!(matches!(err, Error::NGAApi { message, .. } if message == "no permission"));
+    }
 
     #[test]
     fn test_resolve_url() {
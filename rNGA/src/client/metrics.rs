@@ -0,0 +1,179 @@
+//! Request metrics.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds.
+///
+/// Requests slower than the last bound land in a final overflow bucket.
+pub const LATENCY_BUCKETS_MS: &[u64] = &[50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000];
+
+/// Request counters and latency histograms, per API endpoint.
+///
+/// Shared by every clone of a client built with `NGAClientBuilder::metrics`.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    endpoints: Mutex<HashMap<String, Arc<EndpointMetrics>>>,
+}
+
+#[derive(Debug)]
+struct EndpointMetrics {
+    requests: AtomicU64,
+    errors: AtomicU64,
+    total_micros: AtomicU64,
+    buckets: Vec<AtomicU64>,
+}
+
+impl Default for EndpointMetrics {
+    fn default() -> Self {
+        Self {
+            requests: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            total_micros: AtomicU64::new(0),
+            buckets: (0..=LATENCY_BUCKETS_MS.len())
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+        }
+    }
+}
+
+impl Metrics {
+    /// Create an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one request to `api` that took `elapsed`.
+    pub(crate) fn record(&self, api: &str, elapsed: Duration, success: bool) {
+        let endpoint = {
+            let mut endpoints = self.endpoints.lock().unwrap();
+            endpoints.entry(api.to_owned()).or_default().clone()
+        };
+
+        let millis = elapsed.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| millis <= bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+
+        endpoint.requests.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            endpoint.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        endpoint
+            .total_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+        endpoint.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Copy of the current counters.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let endpoints = self.endpoints.lock().unwrap();
+        let endpoints: BTreeMap<String, EndpointSnapshot> = endpoints
+            .iter()
+            .map(|(api, m)| {
+                let snapshot = EndpointSnapshot {
+                    requests: m.requests.load(Ordering::Relaxed),
+                    errors: m.errors.load(Ordering::Relaxed),
+                    total_latency: Duration::from_micros(m.total_micros.load(Ordering::Relaxed)),
+                    buckets: m
+                        .buckets
+                        .iter()
+                        .map(|b| b.load(Ordering::Relaxed))
+                        .collect(),
+                };
+                (api.clone(), snapshot)
+            })
+            .collect();
+
+        MetricsSnapshot {
+            total_requests: endpoints.values().map(|e| e.requests).sum(),
+            total_errors: endpoints.values().map(|e| e.errors).sum(),
+            endpoints,
+        }
+    }
+}
+
+/// Point-in-time copy of `Metrics`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Requests across all endpoints.
+    pub total_requests: u64,
+    /// Failed requests across all endpoints.
+    pub total_errors: u64,
+    /// Counters per API endpoint, such as `read.php`.
+    pub endpoints: BTreeMap<String, EndpointSnapshot>,
+}
+
+/// Counters for one API endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointSnapshot {
+    /// Requests sent.
+    pub requests: u64,
+    /// Requests that failed, including API error responses.
+    pub errors: u64,
+    /// Sum of all request latencies.
+    pub total_latency: Duration,
+    /// Request counts per `LATENCY_BUCKETS_MS` bucket, plus the overflow bucket.
+    pub buckets: Vec<u64>,
+}
+
+impl EndpointSnapshot {
+    /// Average request latency, or zero without requests.
+    pub fn mean_latency(&self) -> Duration {
+        if self.requests == 0 {
+            return Duration::ZERO;
+        }
+        self.total_latency / self.requests as u32
+    }
+
+    /// Upper bound of the bucket holding the `quantile` (0.0 to 1.0) latency.
+    ///
+    /// Returns `None` without requests or when it falls in the overflow bucket.
+    pub fn percentile(&self, quantile: f64) -> Option<Duration> {
+        if self.requests == 0 {
+            return None;
+        }
+        let rank = ((quantile.clamp(0.0, 1.0) * self.requests as f64).ceil() as u64).max(1);
+
+        let mut seen = 0;
+        for (i, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return LATENCY_BUCKETS_MS
+                    .get(i)
+                    .map(|&ms| Duration::from_millis(ms));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_percentiles() {
+        let metrics = Metrics::new();
+        for ms in [10, 20, 30, 200, 20_000] {
+            metrics.record("read.php", Duration::from_millis(ms), ms < 10_000);
+        }
+        metrics.record("thread.php", Duration::from_millis(80), true);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.total_requests, 6);
+        assert_eq!(snapshot.total_errors, 1);
+
+        let read = &snapshot.endpoints["read.php"];
+        assert_eq!(read.requests, 5);
+        assert_eq!(read.mean_latency(), Duration::from_millis(4_052));
+        assert_eq!(read.percentile(0.5), Some(Duration::from_millis(50)));
+        assert_eq!(read.percentile(0.8), Some(Duration::from_millis(250)));
+        assert_eq!(read.percentile(1.0), None);
+
+        assert_eq!(EndpointSnapshot::default().percentile(0.5), None);
+    }
+}
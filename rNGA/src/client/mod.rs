@@ -2,12 +2,16 @@
 
 mod auth;
 mod http;
+mod metrics;
+mod observer;
 
 pub use auth::AuthInfo;
-pub use http::{Device, HttpConfig, FORUM_ICON_PATH};
+pub use http::{Device, HttpConfig, ATTACHMENT_BASE_URL, DEFAULT_BASE_URL, FORUM_ICON_PATH};
+pub use metrics::{EndpointSnapshot, Metrics, MetricsSnapshot, LATENCY_BUCKETS_MS};
+pub use observer::{RequestObserver, REDACTED};
 
 use crate::api::{ForumApi, MessageApi, NotificationApi, PostApi, TopicApi, UserApi};
-use crate::cache::CacheStorage;
+use crate::cache::{CacheStats, CacheStorage, Refreshes};
 use crate::error::{Error, Result};
 use http::{build_client, HttpExecutor};
 use std::sync::Arc;
@@ -18,14 +22,18 @@ pub struct NGAClientBuilder {
     auth: Option<AuthInfo>,
     http_config: HttpConfig,
     cache: Option<Arc<dyn CacheStorage>>,
+    observer: Option<Arc<dyn RequestObserver>>,
+    metrics: bool,
 }
 
 impl std::fmt::Debug for NGAClientBuilder {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("NGAClientBuilder")
-            .field("auth", &self.auth.as_ref().map(|a| &a.uid))
+            .field("auth", &self.auth)
             .field("http_config", &self.http_config)
             .field("cache", &self.cache.as_ref().map(|_| "..."))
+            .field("observer", &self.observer.as_ref().map(|_| "..."))
+            .field("metrics", &self.metrics)
             .finish()
     }
 }
@@ -43,9 +51,44 @@ impl NGAClientBuilder {
             auth: None,
             http_config: HttpConfig::default(),
             cache: None,
+            observer: None,
+            metrics: false,
         }
     }
 
+    /// Create a builder configured from environment variables.
+    ///
+    /// Reads `NGA_TOKEN`, `NGA_UID`, `NGA_BASE_URL` and `NGA_DEVICE`; unset
+    /// or empty variables keep their defaults.
+    ///
+    /// Set variables are never silently ignored: this fails with
+    /// `Error::InvalidArgument` if only one of `NGA_TOKEN` and `NGA_UID` is
+    /// set, or if `NGA_DEVICE` names an unknown device.
+    pub fn from_env() -> Result<Self> {
+        let var = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+        let mut builder = Self::new();
+
+        match (var("NGA_TOKEN"), var("NGA_UID")) {
+            (Some(token), Some(uid)) => builder = builder.auth(token, uid),
+            (None, None) => {}
+            _ => {
+                return Err(Error::InvalidArgument(
+                    "NGA_TOKEN and NGA_UID must be set together".into(),
+                ))
+            }
+        }
+
+        if let Some(url) = var("NGA_BASE_URL") {
+            builder = builder.base_url(url);
+        }
+
+        if let Some(device) = var("NGA_DEVICE") {
+            builder = builder.device(device.parse()?);
+        }
+
+        Ok(builder)
+    }
+
     /// Set authentication.
     pub fn auth(mut self, token: impl Into<String>, uid: impl Into<String>) -> Self {
         self.auth = Some(AuthInfo::new(token, uid));
@@ -64,6 +107,12 @@ impl NGAClientBuilder {
         self
     }
 
+    /// Set mirror base URLs to fail over to when the base URL is unreachable.
+    pub fn mirrors(mut self, urls: Vec<String>) -> Self {
+        self.http_config.fallback_base_urls = urls;
+        self
+    }
+
     /// Set device type.
     pub fn device(mut self, device: Device) -> Self {
         self.http_config.device = device;
@@ -76,6 +125,43 @@ impl NGAClientBuilder {
         self
     }
 
+    /// Add a header sent with every request to the base URL or a mirror.
+    ///
+    /// Headers the client sets itself (`User-Agent`, `X-User-Agent` and
+    /// `Referer`) are reserved; `build` fails if one is given here.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.http_config
+            .extra_headers
+            .push((name.into(), value.into()));
+        self
+    }
+
+    /// Request JSON instead of XML from endpoints that support it.
+    ///
+    /// Currently used by `ForumApi::list`.
+    pub fn prefer_json(mut self, prefer: bool) -> Self {
+        self.http_config.prefer_json = prefer;
+        self
+    }
+
+    /// Serve reads from the cache only, without touching the network.
+    ///
+    /// Cached reads (currently topic detail pages) return the cached value
+    /// regardless of age; everything else fails with `Error::Offline`.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.http_config.offline = offline;
+        self
+    }
+
+    /// Fall back to expired cache entries when the network fails.
+    ///
+    /// Cached entries are then kept for a week instead of until their
+    /// normal expiry.
+    pub fn cache_fallback(mut self, fallback: bool) -> Self {
+        self.http_config.cache_fallback = fallback;
+        self
+    }
+
     /// Set connection timeout.
     pub fn connect_timeout(mut self, timeout: Duration) -> Self {
         self.http_config.connect_timeout = timeout;
@@ -94,8 +180,21 @@ impl NGAClientBuilder {
         self
     }
 
+    /// Set an observer for outgoing requests and raw responses.
+    pub fn observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Collect request counts and latencies, read with `NGAClient::metrics_snapshot`.
+    pub fn metrics(mut self, enabled: bool) -> Self {
+        self.metrics = enabled;
+        self
+    }
+
     /// Build NGAClient.
     pub fn build(self) -> Result<NGAClient> {
+        self.http_config.validate()?;
         let http_client = build_client(&self.http_config)?;
 
         Ok(NGAClient {
@@ -104,6 +203,9 @@ impl NGAClientBuilder {
                 config: self.http_config,
                 auth: self.auth,
                 cache: self.cache,
+                observer: self.observer,
+                metrics: self.metrics.then(|| Arc::new(Metrics::new())),
+                refreshes: Refreshes::default(),
             }),
         })
     }
@@ -117,6 +219,11 @@ pub(crate) struct NGAClientInner {
     /// Cache storage for API responses
     #[allow(dead_code)]
     pub cache: Option<Arc<dyn CacheStorage>>,
+    pub observer: Option<Arc<dyn RequestObserver>>,
+    /// Request metrics, when enabled.
+    pub metrics: Option<Arc<Metrics>>,
+    /// Cache keys being refreshed in the background.
+    pub refreshes: Refreshes,
 }
 
 impl NGAClientInner {
@@ -141,6 +248,8 @@ impl NGAClientInner {
     /// Create HTTP executor.
     pub fn executor(&self) -> HttpExecutor<'_> {
         HttpExecutor::new(&self.http, &self.config)
+            .with_observer(self.observer.as_deref())
+            .with_metrics(self.metrics.as_deref())
     }
 
     /// Execute authenticated POST request.
@@ -162,13 +271,26 @@ impl NGAClientInner {
         api: &str,
         query: &[(&str, &str)],
         form: &[(&str, &str)],
+    ) -> Result<String> {
+        self.post_with_timeout(api, query, form, None).await
+    }
+
+    /// Execute a POST request with a per-request timeout override.
+    pub async fn post_with_timeout(
+        &self,
+        api: &str,
+        query: &[(&str, &str)],
+        form: &[(&str, &str)],
+        timeout: Option<Duration>,
     ) -> Result<String> {
         let auth = self.auth_tuple_opt();
-        self.executor().post_form_xml(api, query, form, auth).await
+        self.executor()
+            .with_timeout(timeout)
+            .post_form_xml(api, query, form, auth)
+            .await
     }
 
     /// Execute a JSON POST request.
-    #[allow(dead_code)]
     pub async fn post_json(
         &self,
         api: &str,
@@ -222,6 +344,42 @@ impl NGAClient {
         MessageApi::new(self.inner.clone())
     }
 
+    /// Fetch the raw decoded response body of an API endpoint.
+    ///
+    /// The body is returned as NGA sent it, without XML validation, error
+    /// checking or mirror failover. Useful for debugging parse failures.
+    pub async fn fetch_raw(
+        &self,
+        api: &str,
+        query: &[(&str, &str)],
+        form: &[(&str, &str)],
+    ) -> Result<String> {
+        self.inner
+            .executor()
+            .post_form(api, query, form, self.inner.auth_tuple_opt())
+            .await
+    }
+
+    /// Snapshot of cache counters, if the configured cache tracks them.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.inner.cache.as_ref().and_then(|cache| cache.stats())
+    }
+
+    /// Remove every entry from the configured cache, if any.
+    pub async fn clear_cache(&self) {
+        if let Some(cache) = &self.inner.cache {
+            cache.clear().await;
+        }
+    }
+
+    /// Snapshot of request metrics, if enabled with `NGAClientBuilder::metrics`.
+    pub fn metrics_snapshot(&self) -> Option<MetricsSnapshot> {
+        self.inner
+            .metrics
+            .as_ref()
+            .map(|metrics| metrics.snapshot())
+    }
+
     /// Check if the client is authenticated.
     pub fn is_authenticated(&self) -> bool {
         self.inner.auth.is_some()
@@ -232,6 +390,11 @@ impl NGAClient {
         self.inner.auth.as_ref()
     }
 
+    /// Base URL requests are sent to, ending with `/`.
+    pub fn base_url(&self) -> &str {
+        &self.inner.config.base_url
+    }
+
     /// Get the current user ID if authenticated.
     pub fn current_uid(&self) -> Option<&str> {
         self.inner.auth.as_ref().map(|a| a.uid.as_str())
@@ -246,3 +409,159 @@ impl std::fmt::Debug for NGAClient {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Sets environment variables for the duration of a test.
+    struct EnvGuard {
+        keys: Vec<&'static str>,
+        _lock: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl EnvGuard {
+        fn set(vars: &[(&'static str, &str)]) -> Self {
+            let lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            for key in ["NGA_TOKEN", "NGA_UID", "NGA_BASE_URL", "NGA_DEVICE"] {
+                std::env::remove_var(key);
+            }
+            for (key, value) in vars {
+                std::env::set_var(key, value);
+            }
+            Self {
+                keys: vars.iter().map(|(k, _)| *k).collect(),
+                _lock: lock,
+            }
+        }
+    }
+
+    impl Drop for EnvGuard {
+        fn drop(&mut self) {
+            for key in &self.keys {
+                std::env::remove_var(key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_env_auth() {
+        let _env = EnvGuard::set(&[("NGA_TOKEN", "env_token"), ("NGA_UID", "42")]);
+
+        let builder = NGAClientBuilder::from_env().unwrap();
+        let auth = builder.auth.as_ref().unwrap();
+        assert_eq!(auth.token, "env_token");
+        assert_eq!(auth.uid, "42");
+        assert_eq!(builder.http_config.device, Device::default());
+    }
+
+    #[test]
+    fn test_from_env_device() {
+        let _env = EnvGuard::set(&[
+            ("NGA_DEVICE", "android"),
+            ("NGA_BASE_URL", "https://ngabbs.com/"),
+        ]);
+
+        let builder = NGAClientBuilder::from_env().unwrap();
+        assert!(builder.auth.is_none());
+        assert_eq!(builder.http_config.device, Device::Android);
+        assert_eq!(builder.http_config.base_url, "https://ngabbs.com/");
+    }
+
+    #[test]
+    fn test_from_env_invalid_device() {
+        let _env = EnvGuard::set(&[("NGA_DEVICE", "toaster")]);
+
+        let result = NGAClientBuilder::from_env();
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_from_env_half_auth() {
+        for var in ["NGA_TOKEN", "NGA_UID"] {
+            let _env = EnvGuard::set(&[(var, "value")]);
+
+            let result = NGAClientBuilder::from_env();
+            assert!(matches!(result, Err(Error::InvalidArgument(_))));
+        }
+    }
+
+    #[test]
+    fn test_reserved_header_rejected() {
+        let result = NGAClient::builder().header("referer", "x").build();
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+
+        let result = NGAClient::builder().header("Bad Name", "x").build();
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+
+        assert!(NGAClient::builder().header("X-Bypass", "1").build().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_base_url_rejected() {
+        for url in [
+            "",
+            "not a url",
+            "ftp://nga.178.com/",
+            "https://nga.178.com/bbs",
+            "https://nga.178.com/?x=1",
+        ] {
+            let result = NGAClient::builder().base_url(url).build();
+            assert!(
+                matches!(result, Err(Error::InvalidArgument(_))),
+                "{:?}",
+                url
+            );
+        }
+
+        let result = NGAClient::builder().mirrors(vec!["garbage".into()]).build();
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+
+        for url in ["https://nga.178.com", "http://127.0.0.1:8080/bbs/"] {
+            assert!(
+                NGAClient::builder().base_url(url).build().is_ok(),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_zero_timeout_rejected() {
+        let result = NGAClient::builder().connect_timeout(Duration::ZERO).build();
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+
+        let result = NGAClient::builder().read_timeout(Duration::ZERO).build();
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[tokio::test]
+    async fn test_clear_cache() {
+        let cache = Arc::new(crate::cache::MemoryCache::new());
+        let client = NGAClient::builder().cache(cache.clone()).build().unwrap();
+
+        cache.set("topic:1", b"cached", None).await;
+        client.clear_cache().await;
+        assert!(cache.get("topic:1").await.is_none());
+
+        NGAClient::builder().build().unwrap().clear_cache().await;
+    }
+
+    #[test]
+    fn test_metrics_disabled_by_default() {
+        assert!(NGAClient::builder()
+            .build()
+            .unwrap()
+            .metrics_snapshot()
+            .is_none());
+        assert!(NGAClient::builder()
+            .metrics(true)
+            .build()
+            .unwrap()
+            .metrics_snapshot()
+            .is_some());
+    }
+}
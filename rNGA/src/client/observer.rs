@@ -0,0 +1,58 @@
+//! Request observation hooks.
+
+/// Placeholder written in place of sensitive form values.
+pub const REDACTED: &str = "***";
+
+/// Form fields whose values are never passed to observers.
+const SENSITIVE_FIELDS: &[&str] = &["access_token"];
+
+/// Observer for outgoing requests and raw responses.
+///
+/// Useful for debugging parse failures or collecting metrics. The access
+/// token is always redacted from the form data passed to `on_request`.
+pub trait RequestObserver: Send + Sync {
+    /// Called before a request is sent.
+    fn on_request(&self, _api: &str, _query: &[(&str, &str)], _form: &[(&str, &str)]) {}
+
+    /// Called with the decoded response body.
+    fn on_response(&self, _api: &str, _status: u16, _body: &str) {}
+}
+
+/// Replace sensitive form values with a placeholder.
+pub(crate) fn redact_form<'a>(form: &[(&'a str, &'a str)]) -> Vec<(&'a str, &'a str)> {
+    form.iter()
+        .map(|&(k, v)| {
+            if SENSITIVE_FIELDS.contains(&k) && !v.is_empty() {
+                (k, REDACTED)
+            } else {
+                (k, v)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_form() {
+        let form = [
+            ("access_token", "secret"),
+            ("access_uid", "42"),
+            ("fid", "7"),
+        ];
+        let redacted = redact_form(&form);
+        assert_eq!(
+            redacted,
+            vec![
+                ("access_token", REDACTED),
+                ("access_uid", "42"),
+                ("fid", "7")
+            ]
+        );
+
+        let anonymous = redact_form(&[("access_token", "")]);
+        assert_eq!(anonymous, vec![("access_token", "")]);
+    }
+}
@@ -1,7 +1,10 @@
 //! Error types.
 
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::models::TopicId;
+
 /// The main error type for rNGA operations.
 #[derive(Debug, Error)]
 pub enum Error {
@@ -17,10 +20,26 @@ pub enum Error {
     #[error("Parse error: {0}")]
     Parse(String),
 
+    /// NGA rejected a post for being sent too soon after the last one.
+    #[error("Flood control: retry after {}s", retry_after.as_secs())]
+    FloodControl { retry_after: Duration },
+
+    /// NGA answered with HTTP 429, or 503 with a `Retry-After` header.
+    #[error("Rate limited{}", retry_after.map(|d| format!(": retry after {}s", d.as_secs())).unwrap_or_default())]
+    RateLimited { retry_after: Option<Duration> },
+
     /// Operation requires authentication but none was provided.
     #[error("Authentication required")]
     AuthRequired,
 
+    /// The topic was deleted or never existed.
+    #[error("Topic {0} not found or deleted")]
+    TopicNotFound(TopicId),
+
+    /// The user has no avatar to fetch.
+    #[error("User has no avatar")]
+    NoAvatar,
+
     /// A required field was missing in the response.
     #[error("Missing field: {0}")]
     MissingField(String),
@@ -29,6 +48,18 @@ pub enum Error {
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
 
+    /// NGA is serving a maintenance page instead of API output.
+    #[error("NGA is under maintenance")]
+    Maintenance,
+
+    /// NGA demands a captcha or other human verification.
+    #[error("Verification required")]
+    VerificationRequired,
+
+    /// Offline mode is on and the resource isn't in the cache.
+    #[error("Offline: not available in cache")]
+    Offline,
+
     /// Cache storage error.
     #[error("Cache error: {0}")]
     Cache(String),
@@ -67,6 +98,15 @@ impl Error {
         }
     }
 
+    /// Create NGA API error, recognizing flood control messages.
+    pub fn from_nga(code: impl Into<String>, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match flood_control_delay(&message) {
+            Some(retry_after) => Error::FloodControl { retry_after },
+            None => Error::nga(code, message),
+        }
+    }
+
     /// Create a parse error.
     pub fn parse(msg: impl Into<String>) -> Self {
         Error::Parse(msg.into())
@@ -80,12 +120,24 @@ impl Error {
     /// Check if this error is potentially retryable.
     pub fn is_retryable(&self) -> bool {
         match self {
-            Error::Network(_) => true,
+            Error::Network(_)
+            | Error::Maintenance
+            | Error::FloodControl { .. }
+            | Error::RateLimited { .. } => true,
             Error::NGAApi { code, .. } => code == "-4",
             _ => false,
         }
     }
 
+    /// How long the server asked to wait before retrying, if it said.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::FloodControl { retry_after } => Some(*retry_after),
+            Error::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+
     /// Check if this is an authentication error.
     pub fn is_auth_error(&self) -> bool {
         match self {
@@ -96,6 +148,45 @@ impl Error {
     }
 }
 
+/// Extract the wait time from a flood control message.
+fn flood_control_delay(message: &str) -> Option<Duration> {
+    use lazy_static::lazy_static;
+    use regex::Regex;
+
+    // Phrases from NGA's flood messages only; a bare "wait" also shows up in
+    // unrelated errors that must not be retried.
+    const MARKERS: &[&str] = &[
+        "太快",
+        "过快",
+        "间隔过短",
+        "发帖间隔",
+        "频繁",
+        "too fast",
+        "too often",
+    ];
+
+    lazy_static! {
+        static ref DELAY_RE: Regex =
+            Regex::new(r"(?i)(\d+)\s*(秒|分钟|seconds?|secs?|minutes?|mins?)").unwrap();
+    }
+
+    let lower = message.to_lowercase();
+    if !MARKERS.iter().any(|m| lower.contains(m)) {
+        return None;
+    }
+
+    let caps = DELAY_RE.captures(message)?;
+    let amount: u64 = caps[1].parse().ok()?;
+    let unit = caps[2].to_lowercase();
+    let secs = if unit == "分钟" || unit.starts_with("min") {
+        amount * 60
+    } else {
+        amount
+    };
+
+    Some(Duration::from_secs(secs))
+}
+
 /// Result type alias for rNGA operations.
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -109,9 +200,46 @@ mod tests {
         assert_eq!(format!("{}", e), "NGA API error [1]: test message");
     }
 
+    #[test]
+    fn test_flood_control() {
+        let e = Error::from_nga("post", "发帖间隔过短，请等待 30 秒后再试");
+        assert!(
+            matches!(e, Error::FloodControl { retry_after } if retry_after == Duration::from_secs(30))
+        );
+        assert!(e.is_retryable());
+
+        let e = Error::from_nga("post", "You are posting too fast, please wait 2 minutes");
+        assert!(
+            matches!(e, Error::FloodControl { retry_after } if retry_after == Duration::from_secs(120))
+        );
+
+        let e = Error::from_nga("post", "帖子内容不能少于 6 个字");
+        assert!(matches!(e, Error::NGAApi { .. }));
+
+        let e = Error::from_nga("post", "Attachment still uploading, please wait 10 seconds");
+        assert!(matches!(e, Error::NGAApi { .. }));
+    }
+
     #[test]
     fn test_retryable() {
         assert!(Error::nga("-4", "blocked").is_retryable());
         assert!(!Error::nga("1", "not blocked").is_retryable());
+        assert!(Error::Maintenance.is_retryable());
+        assert!(!Error::VerificationRequired.is_retryable());
+        assert!(Error::RateLimited { retry_after: None }.is_retryable());
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let e = Error::RateLimited {
+            retry_after: Some(Duration::from_secs(5)),
+        };
+        assert_eq!(e.retry_after(), Some(Duration::from_secs(5)));
+        assert_eq!(e.to_string(), "Rate limited: retry after 5s");
+
+        let e = Error::RateLimited { retry_after: None };
+        assert_eq!(e.retry_after(), None);
+        assert_eq!(e.to_string(), "Rate limited");
+        assert_eq!(Error::Maintenance.retry_after(), None);
     }
 }
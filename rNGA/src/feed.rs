@@ -0,0 +1,211 @@
+//! RSS feed and OPML subscription list generation.
+
+use html_escape::{encode_double_quoted_attribute, encode_text};
+
+use crate::api::TopicListResult;
+use crate::models::{Forum, Topic};
+
+impl TopicListResult {
+    /// Render the topic list as an RSS 2.0 document.
+    ///
+    /// Item links point at the site at `base_url`, and `forum_url` is used
+    /// as the channel link.
+    pub fn to_rss(&self, base_url: &str, forum_url: &str) -> String {
+        let title = self
+            .forum
+            .as_ref()
+            .map(|f| f.name.as_str())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("NGA");
+
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<rss version=\"2.0\">\n<channel>\n");
+        out.push_str(&format!("<title>{}</title>\n", encode_text(title)));
+        out.push_str(&format!("<link>{}</link>\n", encode_text(forum_url)));
+        out.push_str(&format!(
+            "<description>{}</description>\n",
+            encode_text(title)
+        ));
+
+        for topic in &self.topics {
+            out.push_str(&rss_item(topic, base_url));
+        }
+
+        out.push_str("</channel>\n</rss>\n");
+        out
+    }
+}
+
+/// Render a single topic as an RSS `<item>`.
+fn rss_item(topic: &Topic, base_url: &str) -> String {
+    let url = topic.url(base_url);
+    let mut item = String::from("<item>\n");
+    item.push_str(&format!(
+        "<title>{}</title>\n",
+        encode_text(&topic.subject.full_text())
+    ));
+    item.push_str(&format!("<link>{}</link>\n", encode_text(&url)));
+    item.push_str(&format!(
+        "<guid isPermaLink=\"true\">{}</guid>\n",
+        encode_text(&url)
+    ));
+    item.push_str(&format!(
+        "<author>{}</author>\n",
+        encode_text(topic.author.name.display())
+    ));
+    if let Some(date) = topic.posted_at() {
+        item.push_str(&format!("<pubDate>{}</pubDate>\n", date.to_rfc2822()));
+    }
+    item.push_str("</item>\n");
+    item
+}
+
+/// Render forums as an OPML 2.0 subscription list for feed readers.
+///
+/// Each forum with an ID becomes one `<outline>` linking to its page on the
+/// site at `base_url`. `feed_url` gives the URL its RSS feed is served at,
+/// such as hosted `TopicListResult::to_rss` output; forums without one get a
+/// plain outline with no `xmlUrl`.
+pub fn forums_to_opml(
+    title: &str,
+    base_url: &str,
+    forums: &[Forum],
+    feed_url: impl Fn(&Forum) -> Option<String>,
+) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<opml version=\"2.0\">\n<head>\n");
+    out.push_str(&format!("<title>{}</title>\n", encode_text(title)));
+    out.push_str("</head>\n<body>\n");
+
+    for forum in forums {
+        let Some(html_url) = forum.url(base_url) else {
+            continue;
+        };
+        let name = encode_double_quoted_attribute(&forum.name);
+        let html_url = encode_double_quoted_attribute(&html_url);
+        match feed_url(forum) {
+            Some(xml_url) => out.push_str(&format!(
+                "<outline type=\"rss\" text=\"{}\" title=\"{}\" xmlUrl=\"{}\" htmlUrl=\"{}\"/>\n",
+                name,
+                name,
+                encode_double_quoted_attribute(&xml_url),
+                html_url
+            )),
+            None => out.push_str(&format!(
+                "<outline text=\"{}\" title=\"{}\" htmlUrl=\"{}\"/>\n",
+                name, name, html_url
+            )),
+        }
+    }
+
+    out.push_str("</body>\n</opml>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Forum, ForumIdKind, Subject, User, UserName};
+    use crate::parser::XmlDocument;
+
+    fn topic(id: &str, subject: &str, author: &str) -> Topic {
+        Topic {
+            subject: Subject {
+                tags: vec!["Tag".into()],
+                content: subject.into(),
+            },
+            author: User {
+                name: UserName::regular(author),
+                ..Default::default()
+            },
+            post_date: 1_700_000_000,
+            ..Topic::with_id(id)
+        }
+    }
+
+    #[test]
+    fn test_to_rss() {
+        let result = TopicListResult {
+            topics: vec![
+                topic("1", "Hello & welcome", "alice"),
+                topic("2", "<script>", "bob"),
+            ],
+            forum: Some(Forum::minimal(ForumIdKind::fid("310"), "Test Forum")),
+            subforums: Vec::new(),
+            total_topics: 2,
+            total_pages: 1,
+            page: 1,
+        };
+
+        let rss = result.to_rss(
+            "https://bbs.nga.cn/",
+            "https://bbs.nga.cn/thread.php?fid=310",
+        );
+        let doc = XmlDocument::parse(&rss).unwrap();
+
+        assert_eq!(doc.select("/rss/channel/item").unwrap().len(), 2);
+        assert_eq!(doc.string("/rss/channel/title").unwrap(), "Test Forum");
+        assert_eq!(
+            doc.string("/rss/channel/item[1]/title").unwrap(),
+            "[Tag] Hello & welcome"
+        );
+        assert_eq!(
+            doc.string("/rss/channel/item[2]/link").unwrap(),
+            "https://bbs.nga.cn/read.php?tid=2"
+        );
+        assert_eq!(doc.string("/rss/channel/item[2]/author").unwrap(), "bob");
+        assert_eq!(
+            doc.string("/rss/channel/item[1]/pubDate").unwrap(),
+            "Tue, 14 Nov 2023 22:13:20 +0000"
+        );
+    }
+
+    #[test]
+    fn test_forums_to_opml() {
+        let forums = vec![
+            Forum::minimal(ForumIdKind::fid("310"), "Tea & \"Chat\""),
+            Forum::minimal(ForumIdKind::stid("1234"), "Sub <board>"),
+            Forum {
+                name: "No ID".into(),
+                ..Default::default()
+            },
+        ];
+
+        let opml = forums_to_opml("NGA", "https://bbs.nga.cn/", &forums, |forum| {
+            let id = forum.id_str()?;
+            (id != "310").then(|| format!("https://feeds.example.com/{}.xml", id))
+        });
+        let doc = XmlDocument::parse(&opml).unwrap();
+
+        assert_eq!(doc.select("/opml/body/outline").unwrap().len(), 2);
+        assert_eq!(doc.string("/opml/head/title").unwrap(), "NGA");
+        assert_eq!(
+            doc.string("string(/opml/body/outline[1]/@text)").unwrap(),
+            "Tea & \"Chat\""
+        );
+        assert_eq!(
+            doc.string("string(/opml/body/outline[2]/@xmlUrl)").unwrap(),
+            "https://feeds.example.com/1234.xml"
+        );
+        assert_eq!(
+            doc.string("string(/opml/body/outline[2]/@htmlUrl)")
+                .unwrap(),
+            "https://bbs.nga.cn/thread.php?stid=1234"
+        );
+        assert_eq!(
+            doc.string("string(/opml/body/outline[1]/@htmlUrl)")
+                .unwrap(),
+            "https://bbs.nga.cn/thread.php?fid=310"
+        );
+        assert!(doc
+            .select("/opml/body/outline[1]/@xmlUrl")
+            .unwrap()
+            .is_empty());
+        assert!(doc
+            .select("/opml/body/outline[1]/@type")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -4,26 +4,34 @@ pub mod api;
 pub mod cache;
 pub mod client;
 pub mod error;
+pub mod feed;
 pub mod models;
 pub mod parser;
+pub mod sticker;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
 
 // Re-export main types
-pub use client::{AuthInfo, Device, HttpConfig, NGAClient, NGAClientBuilder};
+pub use client::{
+    AuthInfo, Device, HttpConfig, MetricsSnapshot, NGAClient, NGAClientBuilder, RequestObserver,
+};
 pub use error::{Error, Result};
 
 // Re-export commonly used models
 pub use models::{
-    Attachment, AttachmentKind, Category, FavoriteFolder, FavoriteForumOp, FavoriteTopicOp, Forum,
-    ForumId, ForumIdKind, LightPost, Notification, NotificationCounts, NotificationType, Post,
-    PostContent, PostId, SearchTimeRange, ShortMessage, ShortMessagePost, Span, SpanKind,
-    SubforumFilterOp, Subject, Topic, TopicId, TopicOrder, TopicSnapshot, TopicType, User, UserId,
-    UserName, Vote, VoteState,
+    Attachment, AttachmentKind, Category, Debate, DebateSide, FavoriteFolder, FavoriteForumOp,
+    FavoriteTopicOp, Forum, ForumDetail, ForumId, ForumIdKind, LightPost, MyProfile, Notification,
+    NotificationCounts, NotificationType, OnlineStatus, Post, PostContent, PostId, ReadOptions,
+    SearchTimeRange, ShortMessage, ShortMessagePost, Span, SpanKind, SubforumFilterOp, Subject,
+    Topic, TopicId, TopicOrder, TopicSnapshot, TopicType, ToplistScope, User, UserId, UserName,
+    UserNameKind, Vote, VoteState,
 };
 
 // Re-export API types
 pub use api::{
-    ConversationResult, MessageListResult, NotificationListResult, Subforum, TopicDetailsResult,
-    TopicListResult, UserPostsResult, UserSearchResult, VoteResult,
+    Activity, ConversationResult, Image, MessageListResult, NotificationListResult, Subforum,
+    TopicDetailsResult, TopicListResult, TopicPostStream, UserPostsResult, UserSearchListResult,
+    UserSearchResult, VoteResult,
 };
 
 #[cfg(test)]
@@ -1,9 +1,13 @@
 //! Post content and span models.
 
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::PostId;
+use crate::client::DEFAULT_BASE_URL;
 
 /// Parsed post content with structured spans.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct PostContent {
     /// Structured content spans.
     pub spans: Vec<Span>,
@@ -35,20 +39,369 @@ impl PostContent {
     }
 
     /// Extract plain text from all spans.
+    ///
+    /// `[collapse]` blocks are rendered as their title only, so hidden
+    /// content doesn't leak into previews.
     pub fn to_plain_text(&self) -> String {
         self.spans.iter().map(|s| s.to_plain_text()).collect()
     }
+
+    /// Extract plain text with `[collapse]` blocks expanded.
+    pub fn to_plain_text_expanded(&self) -> String {
+        self.spans
+            .iter()
+            .map(|s| s.render_plain(false, true))
+            .collect()
+    }
+
+    /// Extract plain text, rendering stickers as `[name]`.
+    pub fn to_plain_text_with_stickers(&self) -> String {
+        self.spans
+            .iter()
+            .map(|s| s.render_plain(true, false))
+            .collect()
+    }
+
+    /// Render as Markdown.
+    ///
+    /// Bold, italic, strikethrough, links, images, code, quotes and dividers
+    /// map to their Markdown forms and other tags keep only their text.
+    /// `[collapse]` blocks are expanded under their title in bold.
+    pub fn to_markdown(&self) -> String {
+        markdown_spans(&self.spans)
+    }
+
+    /// IDs of posts referenced by `[pid=...]` quote markers, in order of appearance.
+    pub fn quoted_post_ids(&self) -> Vec<PostId> {
+        let mut ids = Vec::new();
+        collect_quoted_ids(&self.spans, &mut ids);
+        ids
+    }
+
+    /// Hyperlinks as `(text, url)` pairs, in order of appearance, including links in quotes.
+    ///
+    /// A link without display text uses its URL as the text.
+    pub fn links(&self) -> Vec<(String, String)> {
+        let mut links = Vec::new();
+        collect_links(&self.spans, &mut links);
+        links
+    }
+
+    /// Like [`links`](Self::links), but with NGA redirect wrappers removed.
+    ///
+    /// Links through NGA's jump pages (`...?__lib=jump&url=http%3A%2F%2F...`,
+    /// `/misc?url=...`) are replaced by their decoded destination; direct
+    /// links are returned unchanged.
+    pub fn links_unwrapped(&self) -> Vec<(String, String)> {
+        self.links()
+            .into_iter()
+            .map(|(text, url)| {
+                let target = unwrap_redirect(&url);
+                let text = if text == url { target.clone() } else { text };
+                (text, target)
+            })
+            .collect()
+    }
+
+    /// Sources of `[img]` tags, in order of appearance, including images in quotes.
+    ///
+    /// Sources are returned as written, often relative like `./mon_202401/a.jpg`.
+    pub fn image_urls(&self) -> Vec<String> {
+        let mut urls = Vec::new();
+        collect_images(&self.spans, &mut urls);
+        urls
+    }
+
+    /// Number of words in the text, counting each CJK character as a word.
+    ///
+    /// Text nested in tags is counted; stickers are not.
+    pub fn word_count(&self) -> usize {
+        self.count_words(false)
+    }
+
+    /// Number of words, skipping text inside `[quote]` blocks.
+    pub fn word_count_excluding_quotes(&self) -> usize {
+        self.count_words(true)
+    }
+
+    /// Estimated time to read the content at `wpm` words per minute.
+    pub fn reading_time(&self, wpm: u32) -> Duration {
+        let minutes = self.word_count() as f64 / wpm.max(1) as f64;
+        Duration::from_secs_f64(minutes * 60.0)
+    }
+
+    /// Plain-text preview of at most `max_chars` characters.
+    ///
+    /// Whitespace is collapsed to single spaces. Longer text is cut at the
+    /// last sentence end or word boundary in the second half of the limit,
+    /// or at the limit itself after CJK text, and ends with `…`.
+    pub fn summary(&self, max_chars: usize) -> String {
+        let text = self
+            .to_plain_text()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if text.chars().count() <= max_chars {
+            return text;
+        }
+        if max_chars == 0 {
+            return "…".to_owned();
+        }
+
+        let chars: Vec<char> = text.chars().take(max_chars + 1).collect();
+        let min_cut = max_chars / 2;
+        let cut = (min_cut..max_chars)
+            .rev()
+            .find(|&i| is_sentence_end(chars[i]))
+            .map(|i| i + 1)
+            .or_else(|| {
+                if is_cjk(chars[max_chars - 1]) || chars[max_chars].is_whitespace() {
+                    return Some(max_chars);
+                }
+                (min_cut..max_chars)
+                    .rev()
+                    .find(|&i| chars[i].is_whitespace())
+            })
+            .unwrap_or(max_chars);
+
+        let mut summary: String = chars[..cut].iter().collect();
+        summary.truncate(summary.trim_end().len());
+        summary.push('…');
+        summary
+    }
+
+    /// Text of all spans, expanding collapses and skipping `[quote]` blocks.
+    pub(crate) fn text_without_quotes(&self) -> String {
+        let mut text = String::new();
+        collect_text(&self.spans, true, &mut text);
+        text
+    }
+
+    fn count_words(&self, skip_quotes: bool) -> usize {
+        let mut text = String::new();
+        collect_text(&self.spans, skip_quotes, &mut text);
+        count_words(&text)
+    }
+}
+
+fn collect_text(spans: &[Span], skip_quotes: bool, out: &mut String) {
+    for span in spans {
+        match &span.kind {
+            SpanKind::Plain { text } => out.push_str(text),
+            SpanKind::LineBreak => out.push('\n'),
+            SpanKind::Sticker { .. } | SpanKind::Hidden { .. } => {}
+            SpanKind::Tagged { tag, .. } if skip_quotes && tag == "quote" => {}
+            SpanKind::Divider { spans }
+            | SpanKind::Tagged { spans, .. }
+            | SpanKind::Collapse { spans, .. } => collect_text(spans, skip_quotes, out),
+        }
+    }
+}
+
+/// Count whitespace-separated words, with each CJK character counted alone.
+fn count_words(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            count += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() {
+            if !in_word {
+                count += 1;
+                in_word = true;
+            }
+        } else if c.is_whitespace() {
+            in_word = false;
+        }
+    }
+
+    count
+}
+
+fn is_sentence_end(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '。' | '！' | '？' | '…')
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF // Hiragana, Katakana
+            | 0x3400..=0x4DBF // CJK Extension A
+            | 0x4E00..=0x9FFF // CJK Unified Ideographs
+            | 0xAC00..=0xD7AF // Hangul Syllables
+            | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+            | 0x20000..=0x2A6DF // CJK Extension B
+    )
+}
+
+fn collect_quoted_ids(spans: &[Span], ids: &mut Vec<PostId>) {
+    for span in spans {
+        match &span.kind {
+            SpanKind::Tagged { spans, .. } => {
+                if let Some(pid) = span.first_attr().filter(|_| span.is_tag("pid")) {
+                    let pid = PostId::new(pid);
+                    if !pid.is_empty() && pid.as_str() != "0" && !ids.contains(&pid) {
+                        ids.push(pid);
+                    }
+                }
+                collect_quoted_ids(spans, ids);
+            }
+            SpanKind::Divider { spans } | SpanKind::Collapse { spans, .. } => {
+                collect_quoted_ids(spans, ids)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_links(spans: &[Span], links: &mut Vec<(String, String)>) {
+    for span in spans {
+        match &span.kind {
+            SpanKind::Tagged { spans, .. } if span.is_tag("url") => {
+                let text: String = spans.iter().map(Span::to_plain_text).collect();
+                let text = text.trim();
+                let url = span
+                    .first_attr()
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty())
+                    .unwrap_or(text);
+                if url.is_empty() {
+                    continue;
+                }
+                let text = if text.is_empty() { url } else { text };
+                links.push((text.to_owned(), url.to_owned()));
+            }
+            SpanKind::Tagged { spans, .. }
+            | SpanKind::Divider { spans }
+            | SpanKind::Collapse { spans, .. } => collect_links(spans, links),
+            _ => {}
+        }
+    }
+}
+
+/// Hosts whose redirect pages wrap external links.
+const NGA_HOSTS: &[&str] = &["nga.178.com", "nga.cn", "ngabbs.com", "ngacn.cc"];
+
+/// Follow NGA redirect wrappers in `url` to the real destination.
+fn unwrap_redirect(url: &str) -> String {
+    let mut url = url.trim().to_owned();
+    // Wrappers are occasionally nested; a few rounds is plenty.
+    for _ in 0..3 {
+        match redirect_target(&url) {
+            Some(target) => url = target,
+            None => break,
+        }
+    }
+    url
+}
+
+/// Destination of a single NGA redirect link, or `None` for other links.
+fn redirect_target(url: &str) -> Option<String> {
+    // Relative links like `/misc?url=...` point at NGA itself.
+    let parsed = url::Url::parse(DEFAULT_BASE_URL).ok()?.join(url).ok()?;
+    let host = parsed.host_str()?;
+    let is_nga = NGA_HOSTS
+        .iter()
+        .any(|h| host == *h || host.ends_with(&format!(".{}", h)));
+    if !is_nga {
+        return None;
+    }
+
+    parsed
+        .query_pairs()
+        .find(|(key, _)| key == "url")
+        .map(|(_, target)| target.trim().to_owned())
+        .filter(|target| target.starts_with("http://") || target.starts_with("https://"))
+}
+
+fn collect_images(spans: &[Span], urls: &mut Vec<String>) {
+    for span in spans {
+        match &span.kind {
+            SpanKind::Tagged { spans, .. } if span.is_tag("img") => {
+                let src: String = spans.iter().map(Span::to_plain_text).collect();
+                let src = src.trim();
+                if !src.is_empty() {
+                    urls.push(src.to_owned());
+                }
+            }
+            SpanKind::Tagged { spans, .. }
+            | SpanKind::Divider { spans }
+            | SpanKind::Collapse { spans, .. } => collect_images(spans, urls),
+            _ => {}
+        }
+    }
+}
+
+fn markdown_spans(spans: &[Span]) -> String {
+    spans.iter().map(markdown_span).collect()
+}
+
+fn markdown_span(span: &Span) -> String {
+    let (tag, spans) = match &span.kind {
+        SpanKind::Divider { spans } if spans.is_empty() => return "\n---\n".to_owned(),
+        SpanKind::Divider { spans } => {
+            return format!("\n### {}\n", markdown_spans(spans).trim());
+        }
+        SpanKind::Collapse { title, spans } => {
+            let title = title.as_deref().unwrap_or("...");
+            return format!("**{}**\n{}", title, markdown_spans(spans));
+        }
+        SpanKind::Tagged { tag, spans, .. } => (tag.as_str(), spans),
+        SpanKind::Hidden { reason } => return format!("*{}*", reason),
+        _ => return span.to_plain_text(),
+    };
+
+    let inner = markdown_spans(spans);
+    let target = || {
+        span.first_attr()
+            .map(str::trim)
+            .filter(|attr| !attr.is_empty())
+            .unwrap_or(inner.trim())
+            .to_owned()
+    };
+    match tag {
+        "b" if !inner.trim().is_empty() => format!("**{}**", inner),
+        "i" if !inner.trim().is_empty() => format!("*{}*", inner),
+        "del" if !inner.trim().is_empty() => format!("~~{}~~", inner),
+        "url" => {
+            let url = target();
+            let text = match inner.trim() {
+                "" => url.as_str(),
+                text => text,
+            };
+            format!("[{}]({})", text, url)
+        }
+        "img" => format!("![]({})", target()),
+        "code" => format!("\n```\n{}\n```\n", inner.trim_matches('\n')),
+        "at" => format!("@{}", span.first_attr().unwrap_or_default()),
+        "quote" => {
+            let block: Vec<String> = inner
+                .trim_matches('\n')
+                .lines()
+                .map(|line| format!("> {}", line).trim_end().to_owned())
+                .collect();
+            format!("\n{}\n\n", block.join("\n"))
+        }
+        _ => inner,
+    }
 }
 
 /// A span of content with specific formatting or type.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Span {
     /// The kind of span with its data.
     pub kind: SpanKind,
+    /// Byte range `[start, end)` of this span in `PostContent::raw`.
+    ///
+    /// Only recorded by `parse_content_with_spans`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<(usize, usize)>,
 }
 
 /// Different kinds of content spans.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SpanKind {
     /// Plain text.
     Plain { text: String },
@@ -65,6 +418,15 @@ pub enum SpanKind {
         complex_attributes: Vec<String>,
         spans: Vec<Span>,
     },
+    /// `[collapse]` block, hidden behind a toggle on NGA.
+    Collapse {
+        title: Option<String>,
+        spans: Vec<Span>,
+    },
+    /// Content withheld by the server until a condition is met, such as
+    /// replying to the topic; only the reason is available. Once the server
+    /// sends the content, `[hide]` and `[randomblock]` parse as `Tagged`.
+    Hidden { reason: String },
 }
 
 impl Span {
@@ -72,6 +434,7 @@ impl Span {
     pub fn plain(text: impl Into<String>) -> Self {
         Self {
             kind: SpanKind::Plain { text: text.into() },
+            source: None,
         }
     }
 
@@ -79,6 +442,7 @@ impl Span {
     pub fn line_break() -> Self {
         Self {
             kind: SpanKind::LineBreak,
+            source: None,
         }
     }
 
@@ -86,6 +450,7 @@ impl Span {
     pub fn sticker(name: impl Into<String>) -> Self {
         Self {
             kind: SpanKind::Sticker { name: name.into() },
+            source: None,
         }
     }
 
@@ -93,6 +458,25 @@ impl Span {
     pub fn divider(spans: Vec<Span>) -> Self {
         Self {
             kind: SpanKind::Divider { spans },
+            source: None,
+        }
+    }
+
+    /// Create a collapse span.
+    pub fn collapse(title: Option<String>, spans: Vec<Span>) -> Self {
+        Self {
+            kind: SpanKind::Collapse { title, spans },
+            source: None,
+        }
+    }
+
+    /// Create a hidden-content span.
+    pub fn hidden(reason: impl Into<String>) -> Self {
+        Self {
+            kind: SpanKind::Hidden {
+                reason: reason.into(),
+            },
+            source: None,
         }
     }
 
@@ -110,17 +494,38 @@ impl Span {
                 complex_attributes,
                 spans,
             },
+            source: None,
         }
     }
 
     /// Extract plain text from this span.
     pub fn to_plain_text(&self) -> String {
+        self.render_plain(false, false)
+    }
+
+    fn render_plain(&self, stickers: bool, expand: bool) -> String {
+        let render = |spans: &[Span]| -> String {
+            spans
+                .iter()
+                .map(|s| s.render_plain(stickers, expand))
+                .collect()
+        };
+
         match &self.kind {
             SpanKind::Plain { text } => text.clone(),
             SpanKind::LineBreak => "\n".to_owned(),
+            SpanKind::Sticker { name } if stickers => format!("[{}]", name),
             SpanKind::Sticker { .. } => String::new(),
-            SpanKind::Divider { spans } => spans.iter().map(|s| s.to_plain_text()).collect(),
-            SpanKind::Tagged { spans, .. } => spans.iter().map(|s| s.to_plain_text()).collect(),
+            SpanKind::Divider { spans } | SpanKind::Tagged { spans, .. } => render(spans),
+            SpanKind::Collapse { title, spans } if expand => match title {
+                Some(title) => format!("{}\n{}", title, render(spans)),
+                None => render(spans),
+            },
+            SpanKind::Collapse { title, .. } => match title {
+                Some(title) => format!("[{}]", title),
+                None => "[...]".to_owned(),
+            },
+            SpanKind::Hidden { reason } => format!("[{}]", reason),
         }
     }
 
@@ -174,6 +579,27 @@ impl Subject {
         }
     }
 
+    /// Content with a leading run of `[tag]`s already listed in `tags`
+    /// removed, for display next to the tags.
+    ///
+    /// Tags later in the title are the author's own wording and are kept.
+    pub fn content_without_tags(&self) -> String {
+        let mut rest = self.content.trim_start();
+        while let Some(after) = self.tags.iter().find_map(|tag| {
+            rest.strip_prefix('[')?
+                .strip_prefix(tag.as_str())?
+                .strip_prefix(']')
+        }) {
+            rest = after.trim_start();
+        }
+
+        if rest.is_empty() {
+            self.content.clone()
+        } else {
+            rest.to_owned()
+        }
+    }
+
     /// Full display text including tags.
     pub fn full_text(&self) -> String {
         if self.tags.is_empty() {
@@ -189,12 +615,179 @@ impl Subject {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_subject_content_without_tags() {
+        let subject = Subject::new(
+            vec!["攻略".into(), "[x]".into()],
+            "[攻略]　[[x]] 新版本配装 [[x]]　指南".into(),
+        );
+        assert_eq!(subject.content_without_tags(), "新版本配装 [[x]]　指南");
+
+        let subject = Subject::new(vec!["讨论".into()], "[讨论] 关于[讨论]区规则".into());
+        assert_eq!(subject.content_without_tags(), "关于[讨论]区规则");
+
+        assert_eq!(
+            Subject::plain("[Hi] there").content_without_tags(),
+            "[Hi] there"
+        );
+    }
+
     #[test]
     fn test_post_content_plain_text() {
         let content = PostContent::plain("Hello world");
         assert_eq!(content.to_plain_text(), "Hello world");
     }
 
+    #[test]
+    fn test_links() {
+        let content = crate::parser::parse_content(
+            "See [url=http://x]click[/url] and [url]http://y[/url].\
+[quote]Quoted [url=http://z][/url][/quote]",
+        );
+        assert_eq!(
+            content.links(),
+            vec![
+                ("click".to_owned(), "http://x".to_owned()),
+                ("http://y".to_owned(), "http://y".to_owned()),
+                ("http://z".to_owned(), "http://z".to_owned()),
+            ]
+        );
+        assert!(PostContent::plain("no links").links().is_empty());
+    }
+
+    #[test]
+    fn test_links_unwrapped() {
+        let content = crate::parser::parse_content(
+            "[url=https://bbs.nga.cn/nuke.php?__lib=jump&url=https%3A%2F%2Fexample.com%2Fa%3Fb%3D1]\
+wrapped[/url] [url]/misc?url=http%3A%2F%2Fexample.org%2F[/url] \
+[url=https://example.net/page?url=https%3A%2F%2Fother.com]direct[/url]",
+        );
+        assert_eq!(
+            content.links_unwrapped(),
+            vec![
+                ("wrapped".to_owned(), "https://example.com/a?b=1".to_owned()),
+                (
+                    "http://example.org/".to_owned(),
+                    "http://example.org/".to_owned()
+                ),
+                (
+                    "direct".to_owned(),
+                    "https://example.net/page?url=https%3A%2F%2Fother.com".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_image_urls() {
+        let content = crate::parser::parse_content(
+            "[img]./mon_202401/a.jpg[/img] text \
+[quote][img] https://x.com/b.png [/img][/quote][collapse=more][img]c.gif[/img][/collapse][img][/img]",
+        );
+        assert_eq!(
+            content.image_urls(),
+            vec!["./mon_202401/a.jpg", "https://x.com/b.png", "c.gif"]
+        );
+    }
+
+    #[test]
+    fn test_span_equality() {
+        let text = "Hi [b]there[/b][s:ac:blink]<br/>[quote]x[/quote]";
+        let first = crate::parser::parse_content(text);
+        let second = crate::parser::parse_content(text);
+        assert_eq!(first, second);
+
+        assert_eq!(
+            first.spans,
+            vec![
+                Span::plain("Hi "),
+                Span::tagged("b", vec![], vec![], vec![Span::plain("there")]),
+                Span::sticker("ac:blink"),
+                Span::line_break(),
+                Span::tagged("quote", vec![], vec![], vec![Span::plain("x")]),
+            ]
+        );
+        assert_ne!(first, crate::parser::parse_content("Hi [i]there[/i]"));
+
+        let json = serde_json::to_string(&first).unwrap();
+        assert_eq!(serde_json::from_str::<PostContent>(&json).unwrap(), first);
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let content = crate::parser::parse_content(
+            "[b]Bold[/b] and [del]gone[/del], see [url=http://x]here[/url] \
+[@alice]<br/>[quote]Quoted<br/>lines[/quote][img]./a.jpg[/img]\
+[code]let x = 1;[/code][collapse=Spoiler]hidden[/collapse]",
+        );
+        assert_eq!(
+            content.to_markdown(),
+            "**Bold** and ~~gone~~, see [here](http://x) @alice\n\
+\n> Quoted\n> lines\n\n![](./a.jpg)\n```\nlet x = 1;\n```\n**Spoiler**\nhidden"
+        );
+    }
+
+    #[test]
+    fn test_word_count_english() {
+        let content = crate::parser::parse_content(
+            "It's a [b]well-known[/b] fact.[s:ac:goodjob]<br/>[quote]Quoted words here[/quote]",
+        );
+        assert_eq!(content.word_count(), 7);
+        assert_eq!(content.word_count_excluding_quotes(), 4);
+        assert_eq!(
+            content.reading_time(200),
+            Duration::from_secs_f64(7.0 * 60.0 / 200.0)
+        );
+    }
+
+    #[test]
+    fn test_summary_word_boundary() {
+        let content = PostContent::plain("The quick brown fox\njumps over the lazy dog");
+        assert_eq!(content.summary(22), "The quick brown fox…");
+        assert_eq!(
+            content.summary(100),
+            "The quick brown fox jumps over the lazy dog"
+        );
+        assert_eq!(content.summary(19), "The quick brown fox…");
+
+        let content = PostContent::plain("First sentence. Second sentence here.");
+        assert_eq!(content.summary(25), "First sentence.…");
+
+        let content = PostContent::plain("Supercalifragilistic");
+        assert_eq!(content.summary(5), "Super…");
+    }
+
+    #[test]
+    fn test_summary_cjk() {
+        let content = PostContent::plain("这是一个很长的句子没有空格");
+        assert_eq!(content.summary(5), "这是一个很…");
+
+        let content = PostContent::plain("第一句话。第二句话很长很长");
+        assert_eq!(content.summary(8), "第一句话。…");
+
+        let content = PostContent::plain("短文");
+        assert_eq!(content.summary(5), "短文");
+        assert_eq!(content.summary(0), "…");
+    }
+
+    #[test]
+    fn test_word_count_mixed_cjk() {
+        let content = crate::parser::parse_content("今天玩了 World of Warcraft，很好玩！");
+        // 4 characters, 3 words, 3 characters.
+        assert_eq!(content.word_count(), 10);
+        assert_eq!(content.reading_time(600), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_plain_text_with_stickers() {
+        let content = crate::parser::parse_content("Nice[s:ac:goodjob] [b]work[s:pg:心][/b]");
+        assert_eq!(content.to_plain_text(), "Nice work");
+        assert_eq!(
+            content.to_plain_text_with_stickers(),
+            "Nice[ac:goodjob] work[pg:心]"
+        );
+    }
+
     #[test]
     fn test_span_tagged() {
         let span = Span::tagged("b", vec![], vec![], vec![Span::plain("bold")]);
@@ -202,6 +795,20 @@ mod tests {
         assert_eq!(span.to_plain_text(), "bold");
     }
 
+    #[test]
+    fn test_quoted_post_ids() {
+        let content = crate::parser::parse_content(
+            "[quote][pid=111,9,1]Reply[/pid] [b]Post by x[/b][/quote]\
+             [b]Reply to [pid=222,9,2]Reply[/pid][/b] again [pid=111,9,1]Reply[/pid]",
+        );
+        assert_eq!(
+            content.quoted_post_ids(),
+            vec![PostId::new("111"), PostId::new("222")]
+        );
+
+        assert!(PostContent::plain("no quotes").quoted_post_ids().is_empty());
+    }
+
     #[test]
     fn test_subject() {
         let subject = Subject::new(vec!["News".into()], "Hello".into());
@@ -1,9 +1,13 @@
 //! Forum and category models.
 
+use std::fmt;
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
 use super::ForumId;
 use crate::client::FORUM_ICON_PATH;
+use crate::error::Error;
 
 /// Forum identifier that can be either a fid or stid.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -52,6 +56,37 @@ impl ForumIdKind {
     }
 }
 
+impl fmt::Display for ForumIdKind {
+    /// Format as `fid:123` or `stid:456`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.param_name(), self.id())
+    }
+}
+
+impl FromStr for ForumIdKind {
+    type Err = Error;
+
+    /// Parse `fid:123`, `stid:456`, or a bare `789` as a fid.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (kind, id) = match s.split_once(':') {
+            Some((kind, id)) => (kind.trim().to_lowercase(), id.trim()),
+            None => ("fid".to_owned(), s),
+        };
+
+        let digits = id.strip_prefix('-').unwrap_or(id);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::InvalidArgument(format!("Invalid forum ID: {}", s)));
+        }
+
+        match kind.as_str() {
+            "fid" => Ok(ForumIdKind::fid(id)),
+            "stid" => Ok(ForumIdKind::stid(id)),
+            _ => Err(Error::InvalidArgument(format!("Invalid forum ID: {}", s))),
+        }
+    }
+}
+
 impl From<ForumId> for ForumIdKind {
     fn from(id: ForumId) -> Self {
         ForumIdKind::Fid(id.0)
@@ -71,6 +106,12 @@ pub struct Forum {
     pub icon_url: String,
     /// Pinned topic ID if any.
     pub topped_topic_id: String,
+    /// Number of topics, when the listing reports it.
+    #[serde(default)]
+    pub topic_count: Option<i64>,
+    /// Number of posts today, when the listing reports it.
+    #[serde(default)]
+    pub today_count: Option<i64>,
 }
 
 impl Forum {
@@ -85,6 +126,8 @@ impl Forum {
             info: String::new(),
             icon_url,
             topped_topic_id: String::new(),
+            topic_count: None,
+            today_count: None,
         }
     }
 
@@ -92,6 +135,29 @@ impl Forum {
     pub fn id_str(&self) -> Option<&str> {
         self.id.as_ref().map(|id| id.id())
     }
+
+    /// Web URL of the forum's topic list on the site at `base_url`, if it
+    /// has an ID.
+    pub fn url(&self, base_url: &str) -> Option<String> {
+        self.id
+            .as_ref()
+            .map(|id| format!("{}thread.php?{}={}", base_url, id.param_name(), id.id()))
+    }
+}
+
+/// Full metadata of a single forum.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ForumDetail {
+    /// Basic forum information.
+    pub forum: Forum,
+    /// Number of topics in the forum.
+    pub topic_count: i64,
+    /// Number of posts in the forum.
+    pub post_count: i64,
+    /// Moderator usernames.
+    pub moderators: Vec<String>,
+    /// Announcement/rules text.
+    pub announcement: Option<String>,
 }
 
 /// A category of forums.
@@ -160,6 +226,32 @@ mod tests {
         assert_eq!(stid.param_name(), "stid");
     }
 
+    #[test]
+    fn test_forum_id_kind_from_str() {
+        assert_eq!(
+            "fid:123".parse::<ForumIdKind>().unwrap(),
+            ForumIdKind::fid("123")
+        );
+        assert_eq!(
+            "stid:456".parse::<ForumIdKind>().unwrap(),
+            ForumIdKind::stid("456")
+        );
+        assert_eq!(
+            "789".parse::<ForumIdKind>().unwrap(),
+            ForumIdKind::fid("789")
+        );
+        assert_eq!("-7".parse::<ForumIdKind>().unwrap(), ForumIdKind::fid("-7"));
+
+        assert!("tid:1".parse::<ForumIdKind>().is_err());
+        assert!("stid:".parse::<ForumIdKind>().is_err());
+        assert!("abc".parse::<ForumIdKind>().is_err());
+
+        for id in [ForumIdKind::fid("123"), ForumIdKind::stid("456")] {
+            assert_eq!(id.to_string().parse::<ForumIdKind>().unwrap(), id);
+        }
+        assert_eq!(ForumIdKind::stid("456").to_string(), "stid:456");
+    }
+
     #[test]
     fn test_forum_minimal() {
         let forum = Forum::minimal(ForumIdKind::fid("123"), "Test Forum");
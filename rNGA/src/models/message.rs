@@ -9,7 +9,7 @@ use super::{PostContent, UserId};
 pub struct ShortMessage {
     /// Conversation ID.
     pub id: String,
-    /// The other participant's user ID.
+    /// The other participant's user ID; the first one in group conversations.
     pub other_user_id: UserId,
     /// The other participant's username.
     pub other_username: String,
@@ -19,8 +19,14 @@ pub struct ShortMessage {
     pub last_time: i64,
     /// Whether there are unread messages.
     pub is_unread: bool,
+    /// Number of unread messages, if the server reported one.
+    #[serde(default)]
+    pub unread_count: Option<i32>,
     /// Number of messages in conversation.
     pub message_count: i32,
+    /// Everyone in the conversation, including the current user.
+    #[serde(default)]
+    pub participants: Vec<(UserId, String)>,
 }
 
 /// A single message in a conversation.
@@ -40,6 +46,26 @@ pub struct ShortMessagePost {
     pub is_mine: bool,
 }
 
+impl ShortMessage {
+    /// Whether more than two users take part in the conversation.
+    pub fn is_group(&self) -> bool {
+        self.participants.len() > 2
+    }
+
+    /// Whether the subject or the other participant contains `keyword`, ignoring case.
+    ///
+    /// The participant matches by username, or by user ID when `keyword` is exactly it.
+    pub fn matches(&self, keyword: &str) -> bool {
+        let keyword = keyword.trim().to_lowercase();
+        if keyword.is_empty() {
+            return true;
+        }
+        self.subject.to_lowercase().contains(&keyword)
+            || self.other_username.to_lowercase().contains(&keyword)
+            || self.other_user_id.as_str() == keyword
+    }
+}
+
 impl ShortMessagePost {
     /// Mark this message as from the current user.
     pub fn mark_as_mine(mut self) -> Self {
@@ -52,6 +78,41 @@ impl ShortMessagePost {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_short_message_matches() {
+        let conversations = [
+            ShortMessage {
+                id: "1".into(),
+                other_user_id: "100".into(),
+                other_username: "Alice".into(),
+                subject: "Trade offer".into(),
+                ..Default::default()
+            },
+            ShortMessage {
+                id: "2".into(),
+                other_user_id: "200".into(),
+                other_username: "鲍勃".into(),
+                subject: "组队副本".into(),
+                ..Default::default()
+            },
+        ];
+        let search = |keyword: &str| -> Vec<&str> {
+            conversations
+                .iter()
+                .filter(|c| c.matches(keyword))
+                .map(|c| c.id.as_str())
+                .collect()
+        };
+
+        assert_eq!(search("trade"), ["1"]);
+        assert_eq!(search("ALICE"), ["1"]);
+        assert_eq!(search("副本"), ["2"]);
+        assert_eq!(search("鲍勃"), ["2"]);
+        assert_eq!(search("200"), ["2"]);
+        assert_eq!(search("20"), Vec::<&str>::new());
+        assert_eq!(search(" "), ["1", "2"]);
+    }
+
     #[test]
     fn test_short_message_post() {
         let msg = ShortMessagePost {
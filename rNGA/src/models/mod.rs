@@ -10,12 +10,25 @@ mod topic;
 mod user;
 
 pub use content::{PostContent, Span, SpanKind, Subject};
-pub use forum::{Category, FavoriteForumOp, Forum, ForumIdKind, SubforumFilterOp};
+pub use forum::{Category, FavoriteForumOp, Forum, ForumDetail, ForumIdKind, SubforumFilterOp};
 pub use ids::{ForumId, PostId, TopicId, UserId};
 pub use message::{ShortMessage, ShortMessagePost};
 pub use notification::{Notification, NotificationCounts, NotificationType};
-pub use post::{Attachment, AttachmentKind, LightPost, Post, Vote, VoteState};
+pub use post::{image_url, Attachment, AttachmentKind, LightPost, Post, Vote, VoteState};
 pub use topic::{
-    FavoriteFolder, FavoriteTopicOp, SearchTimeRange, Topic, TopicOrder, TopicSnapshot, TopicType,
+    Debate, DebateSide, FavoriteFolder, FavoriteTopicOp, ReadOptions, SearchTimeRange, Topic,
+    TopicOrder, TopicSnapshot, TopicType, ToplistScope,
 };
-pub use user::{User, UserName};
+pub use user::{MyProfile, OnlineStatus, User, UserName, UserNameKind};
+
+pub(crate) use user::anon_context;
+
+use chrono::{DateTime, TimeZone, Utc};
+
+/// Convert a Unix timestamp to a UTC datetime, treating zero as unset.
+fn utc_datetime(timestamp: i64) -> Option<DateTime<Utc>> {
+    if timestamp == 0 {
+        return None;
+    }
+    Utc.timestamp_opt(timestamp, 0).single()
+}
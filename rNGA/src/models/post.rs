@@ -1,8 +1,10 @@
 //! Post and reply models.
 
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{PostContent, PostId, TopicId, User};
+use super::{utc_datetime, PostContent, PostId, Topic, TopicId, User, UserNameKind};
+use crate::client::ATTACHMENT_BASE_URL;
 
 /// A post/reply in a topic.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -33,8 +35,11 @@ pub struct Post {
     pub is_hidden: bool,
     /// Device/client used to post.
     pub from_device: Option<String>,
-    /// Signature line.
+    /// Signature line, as raw BBCode.
     pub signature: Option<String>,
+    /// Signature parsed like the post content.
+    #[serde(default)]
+    pub signature_content: Option<PostContent>,
     /// Hot replies under this post.
     pub hot_replies: Vec<LightPost>,
     /// Comments under this post.
@@ -48,6 +53,126 @@ impl Post {
     pub fn is_main(&self) -> bool {
         self.floor == 0
     }
+
+    /// Check if the topic's original poster wrote this post.
+    ///
+    /// Anonymous authors match only by their per-topic anonymous context;
+    /// masked negative IDs are never compared directly.
+    pub fn is_op(&self, topic: &Topic) -> bool {
+        let (author, op) = (&self.author, &topic.author);
+        match (&author.anon_context, &op.anon_context) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => !op.id.is_empty() && !op.is_negative_id() && author.id == op.id,
+            _ => false,
+        }
+    }
+
+    /// Post time as a UTC datetime.
+    pub fn posted_at(&self) -> Option<DateTime<Utc>> {
+        utc_datetime(self.post_date)
+    }
+
+    /// Human-readable name of the client used to post.
+    pub fn device_label(&self) -> Option<&str> {
+        self.from_device
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(device_label)
+    }
+
+    /// Build a `[quote]` block for replying to this post, in the format
+    /// `fetch_quote_content` returns, without the round trip.
+    ///
+    /// The content is reduced to plain text and nested quotes are dropped.
+    pub fn quote_for_reply(&self) -> String {
+        let link = if self.is_main() {
+            format!("[tid={}]Topic[/tid]", self.topic_id)
+        } else {
+            let page = self.floor.max(0) / POSTS_PER_PAGE + 1;
+            format!("[pid={},{},{}]Reply[/pid]", self.id, self.topic_id, page)
+        };
+
+        let author = match (self.author.name.kind(), &self.author.anon_context) {
+            (UserNameKind::Anonymous, Some(context)) => format!("[uid]#anon_{}[/uid]", context),
+            _ => format!(
+                "[uid={}]{}[/uid]",
+                self.author.id,
+                self.author
+                    .name
+                    .primary()
+                    .unwrap_or(self.author.name.display())
+            ),
+        };
+
+        // NGA timestamps quotes in Beijing time.
+        let time = FixedOffset::east_opt(8 * 3600)
+            .zip(self.posted_at())
+            .map(|(tz, at)| at.with_timezone(&tz).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+
+        format!(
+            "[quote]{} [b]Post by {} ({}):[/b]\n\n{}[/quote]\n",
+            link,
+            author,
+            time,
+            self.content.text_without_quotes().trim()
+        )
+    }
+
+    /// Absolute URLs of every image in the post: `[img]` tags in the content
+    /// followed by image attachments, without duplicates.
+    pub fn all_image_urls(&self) -> Vec<String> {
+        let attachments = self
+            .attachments
+            .iter()
+            .filter(|a| a.kind == AttachmentKind::Image)
+            .map(|a| a.url.clone());
+
+        let mut urls: Vec<String> = Vec::new();
+        for url in self.content.image_urls().into_iter().chain(attachments) {
+            let url = image_url(&url);
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        urls
+    }
+}
+
+/// Number of posts on a topic page.
+const POSTS_PER_PAGE: i32 = 20;
+
+/// Resolve an image source or attachment path to an absolute URL.
+///
+/// Relative paths such as `./mon_202401/a.jpg` are resolved against
+/// [`ATTACHMENT_BASE_URL`]; absolute and scheme-relative URLs are kept.
+pub fn image_url(src: &str) -> String {
+    let src = src.trim();
+    if src.starts_with("http://") || src.starts_with("https://") {
+        src.to_owned()
+    } else if let Some(rest) = src.strip_prefix("//") {
+        format!("https://{}", rest)
+    } else {
+        let path = src.trim_start_matches("./").trim_start_matches('/');
+        format!("{}{}", ATTACHMENT_BASE_URL, path)
+    }
+}
+
+/// Translate an NGA client identifier into a friendly label.
+///
+/// The raw value is either a bare name (`ios`, `app`) or a numeric client code
+/// followed by a description (`8 Android`). Unknown values are returned as is.
+pub fn device_label(raw: &str) -> &str {
+    let code = raw.split_whitespace().next().unwrap_or_default();
+    match code.to_lowercase().as_str() {
+        "7" | "ios" | "iphone" | "ipad" => "iOS App",
+        "8" | "android" => "Android App",
+        "app" => "Mobile App",
+        "wp" | "windowsphone" => "Windows Phone App",
+        "web" | "pc" | "desktop" => "Web",
+        _ => raw,
+    }
 }
 
 /// A light-weight post representation.
@@ -61,12 +186,14 @@ pub struct LightPost {
     pub content: PostContent,
     /// Post time.
     pub post_date: i64,
-    /// Post score.
+    /// Net post score.
     pub score: i32,
+    /// Separate upvote and downvote counts, when the response carries them.
+    pub vote: Option<VoteState>,
 }
 
 /// Attachment on a post.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Attachment {
     /// Attachment URL.
     pub url: String,
@@ -83,7 +210,7 @@ pub struct Attachment {
 }
 
 /// Attachment type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum AttachmentKind {
     /// Image attachment.
     Image,
@@ -92,6 +219,7 @@ pub enum AttachmentKind {
     /// Audio attachment.
     Audio,
     /// Other file type.
+    #[default]
     File,
 }
 
@@ -106,11 +234,29 @@ impl AttachmentKind {
             _ => AttachmentKind::File,
         }
     }
-}
 
-impl Default for AttachmentKind {
-    fn default() -> Self {
-        AttachmentKind::File
+    /// Determine attachment kind from a MIME type such as `image/webp`, or one
+    /// of NGA's short types like `img` (optionally followed by `:WxH`).
+    ///
+    /// Returns `None` if the value doesn't identify a kind, so the caller can
+    /// fall back to [`AttachmentKind::from_ext`].
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        let mime = mime
+            .split([';', ':'])
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_lowercase();
+        let (kind, subtype) = mime.split_once('/').unwrap_or((&mime, ""));
+        match kind {
+            "image" | "img" => Some(AttachmentKind::Image),
+            "video" => Some(AttachmentKind::Video),
+            "audio" => Some(AttachmentKind::Audio),
+            "application" | "text" if !subtype.is_empty() && subtype != "octet-stream" => {
+                Some(AttachmentKind::File)
+            }
+            _ => None,
+        }
     }
 }
 
@@ -176,6 +322,77 @@ pub enum PostAction {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::UserName;
+
+    fn user(id: &str) -> User {
+        User {
+            id: id.into(),
+            name: UserName::regular(format!("user{}", id)),
+            ..Default::default()
+        }
+    }
+
+    fn topic_by(author: User) -> Topic {
+        Topic {
+            author,
+            ..Default::default()
+        }
+    }
+
+    fn post_by(author: User) -> Post {
+        Post {
+            author,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_op() {
+        let topic = topic_by(user("42"));
+        assert!(post_by(user("42")).is_op(&topic));
+        assert!(!post_by(user("7")).is_op(&topic));
+        assert!(!post_by(user("0")).is_op(&topic_by(user("0"))));
+
+        let anon = |context: Option<&str>| User {
+            anon_context: context.map(str::to_owned),
+            ..User::anonymous("-1")
+        };
+        let topic = topic_by(anon(Some("abc")));
+        assert!(post_by(anon(Some("abc"))).is_op(&topic));
+        assert!(!post_by(anon(Some("def"))).is_op(&topic));
+        assert!(!post_by(anon(None)).is_op(&topic_by(anon(None))));
+        assert!(!post_by(user("42")).is_op(&topic));
+    }
+
+    #[test]
+    fn test_all_image_urls() {
+        let attachment = |url: &str, kind| Attachment {
+            url: url.into(),
+            kind,
+            ..Default::default()
+        };
+        let post = Post {
+            content: crate::parser::parse_content(
+                "[img]./mon_202401/a.jpg[/img][img]//img.example.com/b.png[/img]",
+            ),
+            attachments: vec![
+                attachment("mon_202401/a.jpg", AttachmentKind::Image),
+                attachment("mon_202401/c.webp", AttachmentKind::Image),
+                attachment("mon_202401/d.mp4", AttachmentKind::Video),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            post.all_image_urls(),
+            vec![
+                "https://img.nga.178.com/attachments/mon_202401/a.jpg",
+                "https://img.example.com/b.png",
+                "https://img.nga.178.com/attachments/mon_202401/c.webp",
+            ]
+        );
+        assert!(Post::default().all_image_urls().is_empty());
+    }
 
     #[test]
     fn test_attachment_kind() {
@@ -185,6 +402,33 @@ mod tests {
         assert_eq!(AttachmentKind::from_ext("zip"), AttachmentKind::File);
     }
 
+    #[test]
+    fn test_attachment_kind_from_mime() {
+        assert_eq!(
+            AttachmentKind::from_mime("image/webp"),
+            Some(AttachmentKind::Image)
+        );
+        assert_eq!(
+            AttachmentKind::from_mime("Video/MP4; codecs=avc1"),
+            Some(AttachmentKind::Video)
+        );
+        assert_eq!(
+            AttachmentKind::from_mime("audio/mpeg"),
+            Some(AttachmentKind::Audio)
+        );
+        assert_eq!(
+            AttachmentKind::from_mime("img:800x600"),
+            Some(AttachmentKind::Image)
+        );
+        assert_eq!(
+            AttachmentKind::from_mime("application/zip"),
+            Some(AttachmentKind::File)
+        );
+        assert_eq!(AttachmentKind::from_mime("application/octet-stream"), None);
+        assert_eq!(AttachmentKind::from_mime("1920X1080"), None);
+        assert_eq!(AttachmentKind::from_mime(""), None);
+    }
+
     #[test]
     fn test_vote_state_net() {
         let state = VoteState {
@@ -195,6 +439,42 @@ mod tests {
         assert_eq!(state.net(), 7);
     }
 
+    #[test]
+    fn test_quote_for_reply() {
+        let post = Post {
+            id: "9001".into(),
+            topic_id: "100".into(),
+            floor: 45,
+            author: User {
+                id: "42".into(),
+                name: UserName::parse("RealName(Nick)"),
+                ..Default::default()
+            },
+            content: crate::parser::parse_content(
+                "[quote]older text[/quote]I [b]agree[/b].<br/>Second line",
+            ),
+            post_date: 1_704_067_200,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            post.quote_for_reply(),
+            "[quote][pid=9001,100,3]Reply[/pid] [b]Post by [uid=42]RealName[/uid] \
+(2024-01-01 08:00):[/b]\n\nI agree.\nSecond line[/quote]\n"
+        );
+
+        let main = Post {
+            topic_id: "100".into(),
+            author: User {
+                anon_context: Some("abc".into()),
+                ..User::anonymous("-1")
+            },
+            ..Default::default()
+        };
+        let quote = main.quote_for_reply();
+        assert!(quote.starts_with("[quote][tid=100]Topic[/tid] [b]Post by [uid]#anon_abc[/uid]"));
+    }
+
     #[test]
     fn test_post_is_main() {
         let main = Post {
@@ -209,4 +489,32 @@ mod tests {
         };
         assert!(!reply.is_main());
     }
+
+    #[test]
+    fn test_device_label() {
+        assert_eq!(device_label("ios"), "iOS App");
+        assert_eq!(device_label("7 iPhone14,2"), "iOS App");
+        assert_eq!(device_label("8 Android"), "Android App");
+        assert_eq!(device_label("app"), "Mobile App");
+        assert_eq!(device_label("WP"), "Windows Phone App");
+        assert_eq!(device_label("web"), "Web");
+        assert_eq!(device_label("103 Something"), "103 Something");
+
+        let post = Post {
+            from_device: Some("android".into()),
+            ..Default::default()
+        };
+        assert_eq!(post.device_label(), Some("Android App"));
+        assert_eq!(Post::default().device_label(), None);
+    }
+
+    #[test]
+    fn test_post_posted_at() {
+        let post = Post {
+            post_date: 1_700_000_000,
+            ..Default::default()
+        };
+        assert_eq!(post.posted_at().unwrap().timestamp(), 1_700_000_000);
+        assert!(Post::default().posted_at().is_none());
+    }
 }
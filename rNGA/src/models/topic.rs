@@ -1,8 +1,10 @@
 //! Topic models.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::ops::{BitOr, BitOrAssign};
 
-use super::{ForumIdKind, Subject, TopicId, User};
+use super::{utc_datetime, ForumIdKind, Subject, TopicId, User};
 
 /// A topic/thread on NGA.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -37,6 +39,9 @@ pub struct Topic {
     pub parent_id: Option<TopicId>,
     /// Recommendation score.
     pub recommend: i32,
+    /// Against (bury) count, in forums that track it.
+    #[serde(default)]
+    pub against: i32,
 }
 
 impl Topic {
@@ -47,6 +52,32 @@ impl Topic {
             ..Default::default()
         }
     }
+
+    /// Subject text without its leading tags, which are shown separately.
+    pub fn short_title(&self) -> String {
+        self.subject.content_without_tags()
+    }
+
+    /// Post date as a UTC datetime.
+    pub fn posted_at(&self) -> Option<DateTime<Utc>> {
+        utc_datetime(self.post_date)
+    }
+
+    /// Last post date as a UTC datetime.
+    pub fn last_post_at(&self) -> Option<DateTime<Utc>> {
+        utc_datetime(self.last_post_date)
+    }
+
+    /// Recommendations minus againsts.
+    pub fn net_recommend(&self) -> i32 {
+        self.recommend - self.against
+    }
+
+    /// Get the web URL of this topic on the site at `base_url`, such as
+    /// [`NGAClient::base_url`](crate::NGAClient::base_url).
+    pub fn url(&self, base_url: &str) -> String {
+        format!("{}read.php?tid={}", base_url, self.id)
+    }
 }
 
 /// Topic type enumeration.
@@ -74,6 +105,45 @@ impl From<i32> for TopicType {
     }
 }
 
+/// Vote tallies of a debate topic.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Debate {
+    /// Votes for the affirmative side.
+    pub pro_count: u32,
+    /// Votes for the negative side.
+    pub con_count: u32,
+    /// Side the current user voted for.
+    pub user_side: Option<DebateSide>,
+}
+
+/// Side of a debate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DebateSide {
+    /// Affirmative side.
+    Pro,
+    /// Negative side.
+    Con,
+}
+
+impl DebateSide {
+    /// Get the API parameter value.
+    pub fn param(&self) -> &'static str {
+        match self {
+            DebateSide::Pro => "1",
+            DebateSide::Con => "2",
+        }
+    }
+
+    /// Parse an API side value, where anything else means no vote.
+    pub fn from_param(value: &str) -> Option<Self> {
+        match value.trim() {
+            "1" => Some(DebateSide::Pro),
+            "2" => Some(DebateSide::Con),
+            _ => None,
+        }
+    }
+}
+
 /// A snapshot of a topic for history purposes.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TopicSnapshot {
@@ -122,6 +192,75 @@ impl TopicOrder {
     }
 }
 
+/// Flags for the `opt` parameter of a topic page request, combined with `|`.
+///
+/// The empty set is NGA's default of showing every post, anonymous ones
+/// included.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ReadOptions(u32);
+
+impl ReadOptions {
+    /// Only show anonymous posts.
+    pub const ANONYMOUS_ONLY: Self = Self(512);
+
+    /// No flags: show all posts.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Flags from a raw `opt` value, for options without a named constant.
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// The raw `opt` value.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Whether no flags are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether all flags in `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Set or clear the flags in `other`.
+    pub fn set(&mut self, other: Self, value: bool) {
+        if value {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+
+    /// Get the API parameter value, empty when no flags are set.
+    pub fn param(&self) -> String {
+        if self.is_empty() {
+            String::new()
+        } else {
+            self.0.to_string()
+        }
+    }
+}
+
+impl BitOr for ReadOptions {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ReadOptions {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
 /// Topic search time range.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum SearchTimeRange {
@@ -151,6 +290,18 @@ impl SearchTimeRange {
     }
 }
 
+/// Scope of the hot topics toplist.
+///
+/// Experimental, like `TopicApi::toplist`.
+#[doc(hidden)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToplistScope {
+    /// Hot topics across all forums.
+    Global,
+    /// Hot topics in one forum.
+    Forum(ForumIdKind),
+}
+
 /// Favorite folder for topics.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FavoriteFolder {
@@ -185,6 +336,24 @@ impl FavoriteTopicOp {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_read_options() {
+        let mut options = ReadOptions::empty();
+        assert_eq!(options.param(), "");
+
+        options.set(ReadOptions::ANONYMOUS_ONLY, true);
+        options |= ReadOptions::from_bits(2);
+        assert!(options.contains(ReadOptions::ANONYMOUS_ONLY));
+        assert_eq!(options.param(), "514");
+        assert_eq!(
+            options,
+            ReadOptions::ANONYMOUS_ONLY | ReadOptions::from_bits(2)
+        );
+
+        options.set(ReadOptions::ANONYMOUS_ONLY, false);
+        assert_eq!(options.bits(), 2);
+    }
+
     #[test]
     fn test_topic_type_from_i32() {
         assert_eq!(TopicType::from(0), TopicType::Normal);
@@ -193,6 +362,27 @@ mod tests {
         assert_eq!(TopicType::from(4), TopicType::Assembly);
     }
 
+    #[test]
+    fn test_debate_side_param() {
+        for side in [DebateSide::Pro, DebateSide::Con] {
+            assert_eq!(DebateSide::from_param(side.param()), Some(side));
+        }
+        assert_eq!(DebateSide::from_param("0"), None);
+    }
+
+    #[test]
+    fn test_topic_datetimes() {
+        let topic = Topic {
+            post_date: 1_700_000_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            topic.posted_at().unwrap().to_rfc3339(),
+            "2023-11-14T22:13:20+00:00"
+        );
+        assert!(topic.last_post_at().is_none());
+    }
+
     #[test]
     fn test_topic_order_param() {
         assert_eq!(TopicOrder::LastPost.param(), "");
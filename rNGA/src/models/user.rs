@@ -29,6 +29,9 @@ pub struct User {
     pub is_muted: bool,
     /// User honor/medal.
     pub honor: Option<String>,
+    /// Per-topic hash identifying an anonymous poster.
+    #[serde(default)]
+    pub anon_context: Option<String>,
 }
 
 impl User {
@@ -36,14 +39,14 @@ impl User {
     pub fn anonymous(id: impl Into<UserId>) -> Self {
         Self {
             id: id.into(),
-            name: UserName::Anonymous,
+            name: UserName::anonymous(),
             ..Default::default()
         }
     }
 
     /// Check if this is an anonymous user.
     pub fn is_anonymous(&self) -> bool {
-        matches!(self.name, UserName::Anonymous)
+        self.name.is_anonymous()
     }
 
     /// Check if this user ID is negative.
@@ -59,81 +62,204 @@ impl User {
             None
         }
     }
+
+    /// Stable display handle for an anonymous poster, such as `Anon-3F9A1C`.
+    ///
+    /// Posts by the same anonymous user in a topic share a context hash and
+    /// therefore the same label. Returns `None` for regular users and for
+    /// anonymous users without a known context.
+    pub fn anon_label(&self) -> Option<String> {
+        if !self.is_anonymous() {
+            return None;
+        }
+        let context = self.anon_context.as_deref()?;
+
+        // FNV-1a, so labels stay the same across builds and platforms.
+        let hash = context.bytes().fold(0x811c_9dc5_u32, |hash, b| {
+            (hash ^ b as u32).wrapping_mul(0x0100_0193)
+        });
+        Some(format!("Anon-{:06X}", hash & 0xff_ffff))
+    }
+}
+
+/// The logged-in user's own profile, with fields only visible to them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MyProfile {
+    /// Public profile fields.
+    pub user: User,
+    /// Account email, usually partially masked.
+    pub email: Option<String>,
+    /// Whether the account email has been verified.
+    pub email_verified: bool,
+    /// Coin balance, in copper.
+    pub money: i64,
+    /// Credit balance.
+    pub credit: i64,
+    /// Number of users this account follows.
+    pub following_count: i32,
+    /// Number of users following this account.
+    pub follower_count: i32,
+}
+
+/// Whether a user is currently around, from their profile's activity fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OnlineStatus {
+    /// Active within the last few minutes.
+    Online {
+        /// Last activity time.
+        last_active: Option<i64>,
+    },
+    /// Not active recently.
+    Offline {
+        /// Last activity time.
+        last_active: Option<i64>,
+    },
+    /// The profile doesn't disclose the user's activity.
+    Hidden,
+}
+
+impl OnlineStatus {
+    /// Whether the user is online.
+    pub fn is_online(&self) -> bool {
+        matches!(self, OnlineStatus::Online { .. })
+    }
+
+    /// Last activity time, when disclosed.
+    pub fn last_active(&self) -> Option<i64> {
+        match self {
+            OnlineStatus::Online { last_active } | OnlineStatus::Offline { last_active } => {
+                *last_active
+            }
+            OnlineStatus::Hidden => None,
+        }
+    }
+}
+
+/// Anonymous context hash for a user, from either the `-USERID,CONTEXT_HASH`
+/// ID or the `#anon_HASH` username.
+pub(crate) fn anon_context(id: &str, username: Option<&str>) -> Option<String> {
+    parse_anon_id(id)
+        .map(|(_, context)| context)
+        .or_else(|| username.and_then(|name| name.trim().strip_prefix("#anon_")))
+        .filter(|context| !context.is_empty())
+        .map(str::to_owned)
 }
 
 /// User display name handling.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum UserName {
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UserName {
+    kind: UserNameKind,
+    /// The string the server sent, when parsed from it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    raw: Option<String>,
+}
+
+/// The forms a user name takes.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub enum UserNameKind {
     /// Regular username.
     Regular(String),
     /// Anonymous posting.
+    #[default]
     Anonymous,
     /// Username with nickname in parentheses.
     WithNickname { name: String, nickname: String },
 }
 
-impl Default for UserName {
-    fn default() -> Self {
-        UserName::Anonymous
+impl From<UserNameKind> for UserName {
+    fn from(kind: UserNameKind) -> Self {
+        Self { kind, raw: None }
     }
 }
 
 impl UserName {
     /// Create a regular username.
     pub fn regular(name: impl Into<String>) -> Self {
-        UserName::Regular(name.into())
+        UserNameKind::Regular(name.into()).into()
     }
 
     /// Create a username with nickname.
     pub fn with_nickname(name: impl Into<String>, nickname: impl Into<String>) -> Self {
-        UserName::WithNickname {
+        UserNameKind::WithNickname {
             name: name.into(),
             nickname: nickname.into(),
         }
+        .into()
     }
 
-    /// Parse username from NGA format.
-    pub fn parse(raw: &str) -> Self {
-        let raw = raw.trim();
+    /// Create an anonymous name.
+    pub fn anonymous() -> Self {
+        UserNameKind::Anonymous.into()
+    }
 
-        if raw.starts_with("#anon_") || raw.is_empty() {
-            return UserName::Anonymous;
+    /// Parse username from NGA format.
+    pub fn parse(original: &str) -> Self {
+        Self {
+            kind: UserNameKind::parse(original),
+            raw: Some(original.to_owned()),
         }
+    }
 
-        if let Some(paren_start) = raw.find('(') {
-            if raw.ends_with(')') {
-                let name = raw[..paren_start].trim().to_owned();
-                let nickname = raw[paren_start + 1..raw.len() - 1].trim().to_owned();
-                return UserName::WithNickname { name, nickname };
-            }
-        }
+    /// Which form the name takes.
+    pub fn kind(&self) -> &UserNameKind {
+        &self.kind
+    }
 
-        UserName::Regular(raw.to_owned())
+    /// Check if this is an anonymous name.
+    pub fn is_anonymous(&self) -> bool {
+        matches!(self.kind, UserNameKind::Anonymous)
     }
 
     /// Get the display string.
     pub fn display(&self) -> &str {
-        match self {
-            UserName::Regular(name) => name,
-            UserName::Anonymous => "Anonymous",
-            UserName::WithNickname { nickname, .. } => nickname,
+        match &self.kind {
+            UserNameKind::Regular(name) => name,
+            UserNameKind::Anonymous => "Anonymous",
+            UserNameKind::WithNickname { nickname, .. } => nickname,
         }
     }
 
     /// Get the primary/real username.
     pub fn primary(&self) -> Option<&str> {
-        match self {
-            UserName::Regular(name) => Some(name),
-            UserName::Anonymous => None,
-            UserName::WithNickname { name, .. } => Some(name),
+        match &self.kind {
+            UserNameKind::Regular(name) => Some(name),
+            UserNameKind::Anonymous => None,
+            UserNameKind::WithNickname { name, .. } => Some(name),
+        }
+    }
+
+    /// Get the name exactly as the server sent it, untrimmed.
+    ///
+    /// Names built with the constructors rather than [`UserName::parse`]
+    /// have no raw form.
+    pub fn raw(&self) -> Option<&str> {
+        self.raw.as_deref()
+    }
+}
+
+impl UserNameKind {
+    fn parse(original: &str) -> Self {
+        let raw = original.trim();
+
+        if raw.starts_with("#anon_") || raw.is_empty() {
+            return UserNameKind::Anonymous;
+        }
+
+        if let Some(paren_start) = raw.find('(') {
+            if raw.ends_with(')') {
+                let name = raw[..paren_start].trim().to_owned();
+                let nickname = raw[paren_start + 1..raw.len() - 1].trim().to_owned();
+                return UserNameKind::WithNickname { name, nickname };
+            }
         }
+
+        UserNameKind::Regular(raw.to_owned())
     }
 }
 
 /// Parse anonymous ID format used by NGA.
 /// Format: "-USERID,CONTEXT_HASH" where USERID is the masked user ID.
 /// Returns tuple of negative_user_id and context_hash.
-#[allow(dead_code)]
 pub fn parse_anon_id(raw: &str) -> Option<(i64, &str)> {
     if !raw.starts_with('-') {
         return None;
@@ -157,14 +283,14 @@ mod tests {
     #[test]
     fn test_username_parse() {
         let regular = UserName::parse("TestUser");
-        assert!(matches!(regular, UserName::Regular(ref n) if n == "TestUser"));
+        assert!(matches!(regular.kind(), UserNameKind::Regular(n) if n == "TestUser"));
 
         let anon = UserName::parse("#anon_abc123");
-        assert!(matches!(anon, UserName::Anonymous));
+        assert!(anon.is_anonymous());
 
         let nickname = UserName::parse("RealName(DisplayName)");
-        match nickname {
-            UserName::WithNickname { name, nickname } => {
+        match nickname.kind() {
+            UserNameKind::WithNickname { name, nickname } => {
                 assert_eq!(name, "RealName");
                 assert_eq!(nickname, "DisplayName");
             }
@@ -172,6 +298,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_username_raw() {
+        let nickname = UserName::parse("RealName (Display Name)");
+        assert_eq!(nickname.raw(), Some("RealName (Display Name)"));
+        assert_eq!(nickname.display(), "Display Name");
+        assert_eq!(nickname.primary(), Some("RealName"));
+
+        let json = serde_json::to_string(&nickname).unwrap();
+        let restored: UserName = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.raw(), Some("RealName (Display Name)"));
+
+        assert_eq!(UserName::parse(" TestUser ").raw(), Some(" TestUser "));
+        assert_eq!(UserName::parse("#anon_abc123").raw(), Some("#anon_abc123"));
+        assert_eq!(UserName::regular("a").raw(), None);
+        assert_eq!(UserName::with_nickname("a", "b").raw(), None);
+    }
+
     #[test]
     fn test_anon_id_parse() {
         let result = parse_anon_id("-12345,abc123");
@@ -181,6 +324,41 @@ mod tests {
         assert!(parse_anon_id("-12345").is_none());
     }
 
+    #[test]
+    fn test_anon_context() {
+        assert_eq!(
+            anon_context("-12345,abc123", None).as_deref(),
+            Some("abc123")
+        );
+        assert_eq!(
+            anon_context("-1", Some("#anon_def456")).as_deref(),
+            Some("def456")
+        );
+        assert_eq!(anon_context("12345", Some("TestUser")), None);
+    }
+
+    #[test]
+    fn test_anon_label() {
+        let mut anon = User::anonymous("-12345,abc123");
+        anon.anon_context = Some("abc123".into());
+        let label = anon.anon_label().unwrap();
+        assert!(label.starts_with("Anon-"));
+        assert_eq!(label.len(), "Anon-".len() + 6);
+
+        let mut other = User::anonymous("-1");
+        other.anon_context = Some("def456".into());
+        assert_ne!(other.anon_label().unwrap(), label);
+
+        assert!(User::anonymous("-1").anon_label().is_none());
+
+        let regular = User {
+            name: UserName::regular("TestUser"),
+            anon_context: Some("abc123".into()),
+            ..Default::default()
+        };
+        assert!(regular.anon_label().is_none());
+    }
+
     #[test]
     fn test_user_is_anonymous() {
         let anon = User::anonymous("-12345");
@@ -1,13 +1,47 @@
 //! BBCode parser for post content.
 
-use crate::models::{PostContent, Span};
+use std::collections::HashMap;
+
+use crate::models::{PostContent, Span, SpanKind};
+
+/// Default maximum tag nesting depth for `parse_content`.
+pub const DEFAULT_MAX_DEPTH: usize = 32;
 
 /// Parse BBCode content into structured spans.
 pub fn parse_content(text: &str) -> PostContent {
+    parse_content_with_depth(text, DEFAULT_MAX_DEPTH)
+}
+
+/// Parse BBCode content, opening at most `max_depth` nested tags.
+///
+/// Tags nested deeper are kept as plain text and recorded in `parse_error`.
+pub fn parse_content_with_depth(text: &str, max_depth: usize) -> PostContent {
+    parse(text, max_depth, false)
+}
+
+/// Parse BBCode content, recording each span's byte range in `raw`.
+///
+/// Ranges are stored in `Span::source` and index the normalized `raw`
+/// string, where newlines have been replaced by `<br/>`. Useful for mapping
+/// matches in the rendered text back to the original content.
+pub fn parse_content_with_spans(text: &str) -> PostContent {
+    parse(text, DEFAULT_MAX_DEPTH, true)
+}
+
+fn parse(text: &str, max_depth: usize, record_sources: bool) -> PostContent {
     let text = unescape_html(text);
     let text = text.replace('\n', "<br/>");
 
-    let (spans, error) = match Parser::new(&text).parse() {
+    let mut parser = Parser::new(&text, max_depth);
+    parser.record_sources = record_sources;
+    let (spans, error) = match parser.parse() {
+        Ok(spans) if parser.depth_exceeded => (
+            spans,
+            Some(format!(
+                "Tags nested deeper than {} kept as text",
+                max_depth
+            )),
+        ),
         Ok(spans) => (spans, None),
         Err(e) => {
             let fallback = vec![Span::plain(text.replace("<br/>", "\n"))];
@@ -27,10 +61,10 @@ pub fn parse_subject(text: &str) -> (Vec<String>, String) {
     let text = unescape_html(text);
 
     let mut tags = Vec::new();
-    let mut remaining = text.as_str();
+    let mut remaining = text.trim();
 
     while let Some(rest) = remaining.strip_prefix('[') {
-        if let Some(end) = rest.find(']') {
+        if let Some(end) = closing_bracket(rest) {
             let tag = rest[..end].trim();
             if !tag.is_empty() {
                 tags.push(tag.to_owned());
@@ -51,6 +85,159 @@ pub fn parse_subject(text: &str) -> (Vec<String>, String) {
     (tags, content)
 }
 
+/// Byte offset of the `]` closing a `[` just before `text`, skipping nested
+/// bracket pairs.
+fn closing_bracket(text: &str) -> Option<usize> {
+    let mut depth = 1;
+    for (i, c) in text.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Title of a `[collapse]` or `[collapse=Title]` opening tag.
+///
+/// Returns `None` if `tag_content` isn't a collapse tag, and `Some(None)`
+/// for an untitled one.
+fn collapse_title(tag_content: &str) -> Option<Option<String>> {
+    let rest = tag_content
+        .get(..8)
+        .filter(|name| name.eq_ignore_ascii_case("collapse"))
+        .map(|_| &tag_content[8..])?;
+
+    if rest.is_empty() {
+        return Some(None);
+    }
+    let title = rest.strip_prefix('=')?.trim();
+    Some(Some(title.to_owned()).filter(|t| !t.is_empty()))
+}
+
+/// Default reason for tags marking content the server withholds, or `None`
+/// for other tags.
+fn hidden_reason(tag_name: &str) -> Option<&'static str> {
+    match tag_name {
+        "hide" => Some("Reply to view"),
+        "randomblock" => Some("Random content"),
+        _ => None,
+    }
+}
+
+/// Phrases the server puts in place of content the viewer can't see yet.
+const HIDDEN_PLACEHOLDERS: &[&str] = &["回复后可见", "回复可见", "需要回复", "reply to view"];
+
+/// Reason shown for withheld content, or `None` if the server sent it.
+///
+/// Content counts as withheld when the tag's body is empty or just a known
+/// placeholder. The reason is the tag's attribute, else the placeholder,
+/// else `default`.
+fn hidden_content_reason(attrs: &[String], spans: &[Span], default: &str) -> Option<String> {
+    let text_only = spans
+        .iter()
+        .all(|span| matches!(span.kind, SpanKind::Plain { .. } | SpanKind::LineBreak));
+    if !text_only {
+        return None;
+    }
+
+    let placeholder: String = spans.iter().map(Span::to_plain_text).collect();
+    let placeholder = placeholder.trim();
+    let lowercase = placeholder.to_lowercase();
+    if !placeholder.is_empty()
+        && !HIDDEN_PLACEHOLDERS
+            .iter()
+            .any(|phrase| lowercase.contains(phrase))
+    {
+        return None;
+    }
+
+    let reason = attrs
+        .first()
+        .map(|attr| attr.trim())
+        .filter(|attr| !attr.is_empty())
+        .or(Some(placeholder).filter(|text| !text.is_empty()))
+        .unwrap_or(default);
+    Some(reason.to_owned())
+}
+
+/// Tags that never take content or a closing tag.
+const VOID_TAGS: &[&str] = &["hr", "*"];
+
+/// Whether `text` starts with a `[/tag]` closing tag, ignoring ASCII case.
+fn starts_with_close_tag(text: &str, tag: &str) -> bool {
+    text.strip_prefix("[/")
+        .and_then(|rest| Some((rest.get(..tag.len())?, &rest[tag.len()..])))
+        .is_some_and(|(name, rest)| name.eq_ignore_ascii_case(tag) && rest.starts_with(']'))
+}
+
+/// Split the content of an opening tag into its lowercase name, `=`
+/// attributes and space-separated attributes.
+fn parse_tag_parts(content: &str) -> (String, Vec<String>, Vec<String>) {
+    let content = content.trim();
+
+    if let Some(space_pos) = content.find(' ') {
+        let tag_name = content[..space_pos].to_lowercase();
+        let rest = &content[space_pos + 1..];
+        let complex_attrs: Vec<String> = rest
+            .split(' ')
+            .map(|s| s.trim().to_owned())
+            .filter(|s| !s.is_empty())
+            .collect();
+        return (tag_name, Vec::new(), complex_attrs);
+    }
+
+    if let Some(eq_pos) = content.find('=') {
+        let tag_name = content[..eq_pos].to_lowercase();
+        let attrs_str = &content[eq_pos + 1..];
+        let attrs: Vec<String> = attrs_str.split(',').map(|s| s.trim().to_owned()).collect();
+        return (tag_name, attrs, Vec::new());
+    }
+
+    (content.to_lowercase(), Vec::new(), Vec::new())
+}
+
+/// Name of the tag opened by `[tag_content]`, or `None` if it takes no
+/// closing tag.
+fn open_tag_name(tag_content: &str) -> Option<String> {
+    if collapse_title(tag_content).is_some() {
+        return Some("collapse".to_owned());
+    }
+    let (name, _, _) = parse_tag_parts(tag_content);
+    Some(name).filter(|name| !name.is_empty() && !VOID_TAGS.contains(&name.as_str()))
+}
+
+/// Pair each opening tag in `text` with its closing tag in one pass.
+///
+/// Maps the byte offset of an opening tag to the range of its closing tag.
+/// Same-name tags nest, so a `[/tag]` closes the innermost `[tag]` still
+/// open; opening tags left over are never closed.
+fn match_close_tags(text: &str) -> HashMap<usize, (usize, usize)> {
+    let mut open: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut closes = HashMap::new();
+
+    for (i, _) in text.match_indices('[') {
+        let Some(end) = text[i + 1..].find(']').map(|end| i + 1 + end) else {
+            break;
+        };
+        let tag_content = &text[i + 1..end];
+        if let Some(name) = tag_content.strip_prefix('/') {
+            if let Some(start) = open.get_mut(&name.to_lowercase()).and_then(Vec::pop) {
+                closes.insert(start, (i, end + 1));
+            }
+        } else if let Some(name) = open_tag_name(tag_content) {
+            open.entry(name).or_default().push(i);
+        }
+    }
+    closes
+}
+
 /// Unescape HTML entities.
 fn unescape_html(text: &str) -> String {
     let first = html_escape::decode_html_entities(text);
@@ -61,48 +248,60 @@ fn unescape_html(text: &str) -> String {
 struct Parser<'a> {
     input: &'a str,
     pos: usize,
+    /// Position of `input` within the full content, for nested parsers.
+    offset: usize,
+    record_sources: bool,
+    depth: usize,
+    max_depth: usize,
+    depth_exceeded: bool,
+    /// End of the content being parsed: the innermost open tag's closing
+    /// tag, or the end of `input`.
+    end: usize,
+    /// Closing tag range of each opening tag, by offset in `input`.
+    closes: HashMap<usize, (usize, usize)>,
 }
 
 impl<'a> Parser<'a> {
-    fn new(input: &'a str) -> Self {
-        Self { input, pos: 0 }
+    fn new(input: &'a str, max_depth: usize) -> Self {
+        Self {
+            input,
+            pos: 0,
+            offset: 0,
+            record_sources: false,
+            depth: 0,
+            max_depth,
+            depth_exceeded: false,
+            end: input.len(),
+            closes: match_close_tags(input),
+        }
     }
 
     fn parse(&mut self) -> Result<Vec<Span>, String> {
-        self.parse_spans(None)
+        self.parse_spans()
     }
 
-    fn parse_spans(&mut self, close_tag: Option<&str>) -> Result<Vec<Span>, String> {
+    /// Parse spans up to `self.end`.
+    fn parse_spans(&mut self) -> Result<Vec<Span>, String> {
         let mut spans = Vec::new();
         let mut plain_start = self.pos;
 
-        while self.pos < self.input.len() {
-            if let Some(tag) = close_tag {
-                if self.looking_at_close_tag(tag) {
-                    if plain_start < self.pos {
-                        spans.push(Span::plain(&self.input[plain_start..self.pos]));
-                    }
-                    self.skip_close_tag(tag);
-                    return Ok(spans);
-                }
-            }
-
+        while self.pos < self.end {
             let start_pos = self.pos;
 
             if let Some(span) = self.try_parse_br() {
                 if plain_start < start_pos {
-                    spans.push(Span::plain(&self.input[plain_start..start_pos]));
+                    spans.push(self.plain_span(plain_start, start_pos));
                 }
-                spans.push(span);
+                spans.push(self.with_source(span, start_pos, self.pos));
                 plain_start = self.pos;
                 continue;
             }
 
             if let Some(span) = self.try_parse_divider() {
                 if plain_start < start_pos {
-                    spans.push(Span::plain(&self.input[plain_start..start_pos]));
+                    spans.push(self.plain_span(plain_start, start_pos));
                 }
-                spans.push(span);
+                spans.push(self.with_source(span, start_pos, self.pos));
                 plain_start = self.pos;
                 continue;
             }
@@ -110,27 +309,27 @@ impl<'a> Parser<'a> {
             if self.current_char() == Some('[') {
                 if let Some(span) = self.try_parse_sticker() {
                     if plain_start < start_pos {
-                        spans.push(Span::plain(&self.input[plain_start..start_pos]));
+                        spans.push(self.plain_span(plain_start, start_pos));
                     }
-                    spans.push(span);
+                    spans.push(self.with_source(span, start_pos, self.pos));
                     plain_start = self.pos;
                     continue;
                 }
 
                 if let Some(span) = self.try_parse_at_mention() {
                     if plain_start < start_pos {
-                        spans.push(Span::plain(&self.input[plain_start..start_pos]));
+                        spans.push(self.plain_span(plain_start, start_pos));
                     }
-                    spans.push(span);
+                    spans.push(self.with_source(span, start_pos, self.pos));
                     plain_start = self.pos;
                     continue;
                 }
 
                 if let Some(span) = self.try_parse_tag()? {
                     if plain_start < start_pos {
-                        spans.push(Span::plain(&self.input[plain_start..start_pos]));
+                        spans.push(self.plain_span(plain_start, start_pos));
                     }
-                    spans.push(span);
+                    spans.push(self.with_source(span, start_pos, self.pos));
                     plain_start = self.pos;
                     continue;
                 }
@@ -140,14 +339,27 @@ impl<'a> Parser<'a> {
         }
 
         if plain_start < self.pos {
-            spans.push(Span::plain(&self.input[plain_start..self.pos]));
+            spans.push(self.plain_span(plain_start, self.pos));
         }
 
         Ok(spans)
     }
 
+    /// Plain span of `input[start..end]`.
+    fn plain_span(&self, start: usize, end: usize) -> Span {
+        self.with_source(Span::plain(&self.input[start..end]), start, end)
+    }
+
+    /// Record that `span` was parsed from `input[start..end]`, if enabled.
+    fn with_source(&self, mut span: Span, start: usize, end: usize) -> Span {
+        if self.record_sources {
+            span.source = Some((self.offset + start, self.offset + end));
+        }
+        span
+    }
+
     fn current_char(&self) -> Option<char> {
-        self.input[self.pos..].chars().next()
+        self.remaining().chars().next()
     }
 
     fn advance(&mut self) {
@@ -157,7 +369,7 @@ impl<'a> Parser<'a> {
     }
 
     fn remaining(&self) -> &'a str {
-        &self.input[self.pos..]
+        &self.input[self.pos..self.end]
     }
 
     fn try_parse_br(&mut self) -> Option<Span> {
@@ -183,7 +395,7 @@ impl<'a> Parser<'a> {
             || !remaining[eq_count..]
                 .chars()
                 .next()
-                .map_or(false, |c| c != '=' && c != '<' && c != '[')
+                .is_some_and(|c| c != '=' && c != '<' && c != '[')
         {
             self.pos += eq_count;
             return Some(Span::divider(Vec::new()));
@@ -197,7 +409,12 @@ impl<'a> Parser<'a> {
                 .take_while(|&c| c == '=')
                 .count();
 
-            let inner_spans = Parser::new(content).parse().unwrap_or_default();
+            let mut inner = Parser::new(content, self.max_depth);
+            inner.depth = self.depth;
+            inner.offset = self.offset + self.pos + eq_count;
+            inner.record_sources = self.record_sources;
+            let inner_spans = inner.parse().unwrap_or_default();
+            self.depth_exceeded |= inner.depth_exceeded;
 
             self.pos += eq_count + end_pos + end_eq_count;
             return Some(Span::divider(inner_spans));
@@ -241,18 +458,33 @@ impl<'a> Parser<'a> {
     }
 
     fn looking_at_close_tag(&self, tag: &str) -> bool {
-        let remaining = self.remaining();
-        if !remaining.starts_with("[/") {
-            return false;
-        }
+        starts_with_close_tag(self.remaining(), tag)
+    }
 
-        let after_slash = &remaining[2..];
-        if !after_slash.to_lowercase().starts_with(&tag.to_lowercase()) {
-            return false;
-        }
+    /// Range of the closing tag of the tag opened at the current position,
+    /// if it closes within the innermost open tag.
+    fn close_of_current(&self) -> Option<(usize, usize)> {
+        self.closes
+            .get(&self.pos)
+            .copied()
+            .filter(|&(_, close_end)| close_end <= self.end)
+    }
 
-        let after_tag = &after_slash[tag.len()..];
-        after_tag.starts_with(']')
+    /// Parse the content of an opening tag `open_len` bytes long, up to and
+    /// past its closing tag at `close`.
+    fn parse_tag_content(
+        &mut self,
+        open_len: usize,
+        (close_start, close_end): (usize, usize),
+    ) -> Result<Vec<Span>, String> {
+        self.depth += 1;
+        self.pos += open_len;
+        let end = std::mem::replace(&mut self.end, close_start);
+        let spans = self.parse_spans();
+        self.end = end;
+        self.pos = close_end;
+        self.depth -= 1;
+        spans
     }
 
     fn skip_close_tag(&mut self, tag: &str) {
@@ -277,15 +509,58 @@ impl<'a> Parser<'a> {
 
         let tag_content = &remaining[1..end];
 
-        let (tag_name, attrs, complex_attrs) = self.parse_tag_parts(tag_content);
+        // Collapse titles are free text, so they can't go through `parse_tag_parts`.
+        if let Some(title) = collapse_title(tag_content) {
+            let Some(close) = self.close_of_current() else {
+                return Ok(None);
+            };
+            if self.depth >= self.max_depth {
+                self.depth_exceeded = true;
+                return Ok(None);
+            }
+
+            let inner_spans = self.parse_tag_content(end + 1, close)?;
+
+            return Ok(Some(Span::collapse(title, inner_spans)));
+        }
+
+        let (tag_name, attrs, complex_attrs) = parse_tag_parts(tag_content);
 
         if tag_name.is_empty() {
             return Ok(None);
         }
 
-        self.pos += end + 1;
+        if VOID_TAGS.contains(&tag_name.as_str()) {
+            self.pos += end + 1;
+            if self.looking_at_close_tag(&tag_name) {
+                self.skip_close_tag(&tag_name);
+            }
+            return Ok(Some(Span::tagged(
+                &tag_name,
+                attrs,
+                complex_attrs,
+                Vec::new(),
+            )));
+        }
+
+        // An open tag that is never closed is literal text, not the start of
+        // a span swallowing the rest of the post.
+        let Some(close) = self.close_of_current() else {
+            return Ok(None);
+        };
+
+        if self.depth >= self.max_depth {
+            self.depth_exceeded = true;
+            return Ok(None);
+        }
+
+        let inner_spans = self.parse_tag_content(end + 1, close)?;
 
-        let inner_spans = self.parse_spans(Some(&tag_name))?;
+        if let Some(reason) = hidden_reason(&tag_name)
+            .and_then(|default| hidden_content_reason(&attrs, &inner_spans, default))
+        {
+            return Ok(Some(Span::hidden(reason)));
+        }
 
         Ok(Some(Span::tagged(
             &tag_name,
@@ -294,36 +569,11 @@ impl<'a> Parser<'a> {
             inner_spans,
         )))
     }
-
-    fn parse_tag_parts(&self, content: &str) -> (String, Vec<String>, Vec<String>) {
-        let content = content.trim();
-
-        if let Some(space_pos) = content.find(' ') {
-            let tag_name = content[..space_pos].to_lowercase();
-            let rest = &content[space_pos + 1..];
-            let complex_attrs: Vec<String> = rest
-                .split(' ')
-                .map(|s| s.trim().to_owned())
-                .filter(|s| !s.is_empty())
-                .collect();
-            return (tag_name, Vec::new(), complex_attrs);
-        }
-
-        if let Some(eq_pos) = content.find('=') {
-            let tag_name = content[..eq_pos].to_lowercase();
-            let attrs_str = &content[eq_pos + 1..];
-            let attrs: Vec<String> = attrs_str.split(',').map(|s| s.trim().to_owned()).collect();
-            return (tag_name, attrs, Vec::new());
-        }
-
-        (content.to_lowercase(), Vec::new(), Vec::new())
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::SpanKind;
 
     #[test]
     fn test_plain_text() {
@@ -416,6 +666,198 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_collapse_titled() {
+        let content = parse_content("Ending: [collapse=Spoiler, read after]He was dead[/collapse]");
+        assert_eq!(content.spans.len(), 2);
+        if let SpanKind::Collapse { title, spans } = &content.spans[1].kind {
+            assert_eq!(title.as_deref(), Some("Spoiler, read after"));
+            assert_eq!(spans.len(), 1);
+        } else {
+            panic!("Expected collapse span");
+        }
+
+        assert_eq!(content.to_plain_text(), "Ending: [Spoiler, read after]");
+        assert_eq!(
+            content.to_plain_text_expanded(),
+            "Ending: Spoiler, read after\nHe was dead"
+        );
+    }
+
+    #[test]
+    fn test_hidden_content() {
+        let content = parse_content(
+            "Answer: [hide]回复后可见[/hide] / [hide=回复本帖][/hide] / [randomblock][/randomblock] end",
+        );
+
+        let reasons: Vec<&str> = content
+            .spans
+            .iter()
+            .filter_map(|span| match &span.kind {
+                SpanKind::Hidden { reason } => Some(reason.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(reasons, vec!["回复后可见", "回复本帖", "Random content"]);
+        assert_eq!(
+            content.to_plain_text(),
+            "Answer: [回复后可见] / [回复本帖] / [Random content] end"
+        );
+        assert!(content.to_markdown().contains("*回复后可见*"));
+    }
+
+    #[test]
+    fn test_revealed_hidden_content() {
+        let content = parse_content(
+            "[randomblock][img]./mon_202401/a.jpg[/img][/randomblock][hide]答案是 [b]42[/b][/hide]",
+        );
+        assert_eq!(content.spans.len(), 2);
+
+        if let SpanKind::Tagged { tag, spans, .. } = &content.spans[0].kind {
+            assert_eq!(tag, "randomblock");
+            assert!(matches!(&spans[0].kind, SpanKind::Tagged { tag, .. } if tag == "img"));
+        } else {
+            panic!("Expected tagged span");
+        }
+        assert!(matches!(&content.spans[1].kind, SpanKind::Tagged { tag, .. } if tag == "hide"));
+        assert!(content.to_plain_text().ends_with("答案是 42"));
+    }
+
+    #[test]
+    fn test_collapse_untitled() {
+        let content = parse_content("[COLLAPSE][b]hidden[/b][/collapse] shown");
+        if let SpanKind::Collapse { title, spans } = &content.spans[0].kind {
+            assert_eq!(title, &None);
+            assert!(spans[0].is_tag("b"));
+        } else {
+            panic!("Expected collapse span");
+        }
+
+        assert_eq!(content.to_plain_text(), "[...] shown");
+        assert_eq!(content.to_plain_text_expanded(), "hidden shown");
+        assert_eq!(content.summary(100), "[...] shown");
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let text = format!("{}deep{}", "[b]".repeat(1000), "[/b]".repeat(1000));
+        let content = parse_content(&text);
+        assert!(content.parse_error.is_some());
+        assert!(content.to_plain_text().contains("[b]deep"));
+
+        let mut depth = 0;
+        let mut spans = &content.spans;
+        while let Some(SpanKind::Tagged { spans: inner, .. }) = spans.first().map(|s| &s.kind) {
+            depth += 1;
+            spans = inner;
+        }
+        assert_eq!(depth, DEFAULT_MAX_DEPTH);
+
+        let shallow = parse_content_with_depth("[b][i]x[/i][/b]", 2);
+        assert!(shallow.parse_error.is_none());
+        let limited = parse_content_with_depth("[b][i]x[/i][/b]", 1);
+        assert!(limited.parse_error.is_some());
+        assert_eq!(limited.to_plain_text(), "[i]x[/i]");
+    }
+
+    #[test]
+    fn test_span_sources() {
+        let text = "你好 [b]粗体 [i]x[/i][/b][s:ac:blink]\n======分割 [u]x[/u]======[collapse=剧透]y[/collapse]";
+        let content = parse_content_with_spans(text);
+        let raw = content.raw.as_str();
+        let source = |span: &Span| {
+            let (start, end) = span.source.unwrap();
+            &raw[start..end]
+        };
+
+        let rebuilt: String = content.spans.iter().map(source).collect();
+        assert_eq!(rebuilt, raw);
+
+        assert_eq!(source(&content.spans[0]), "你好 ");
+        assert_eq!(source(&content.spans[1]), "[b]粗体 [i]x[/i][/b]");
+        assert_eq!(source(&content.spans[2]), "[s:ac:blink]");
+        assert_eq!(source(&content.spans[3]), "<br/>");
+
+        if let SpanKind::Tagged { spans, .. } = &content.spans[1].kind {
+            assert_eq!(source(&spans[0]), "粗体 ");
+            assert_eq!(source(&spans[1]), "[i]x[/i]");
+        } else {
+            panic!("Expected tagged span");
+        }
+        if let SpanKind::Divider { spans } = &content.spans[4].kind {
+            assert_eq!(source(&spans[0]), "分割 ");
+            assert_eq!(source(&spans[1]), "[u]x[/u]");
+        } else {
+            panic!("Expected divider span");
+        }
+
+        assert!(parse_content(text).spans.iter().all(|s| s.source.is_none()));
+    }
+
+    #[test]
+    fn test_unmatched_open_tag() {
+        let content = parse_content("[b]not bold<br/>[i]italic[/i] [1] footnote");
+        assert_eq!(content.to_plain_text(), "[b]not bold\nitalic [1] footnote");
+        assert!(content
+            .spans
+            .iter()
+            .any(|s| matches!(&s.kind, SpanKind::Tagged { tag, .. } if tag == "i")));
+        assert!(!content
+            .spans
+            .iter()
+            .any(|s| matches!(&s.kind, SpanKind::Tagged { tag, .. } if tag == "b")));
+    }
+
+    #[test]
+    fn test_open_tag_closed_outside_parent() {
+        let content = parse_content("[quote][b]x[/quote] [b]y[/b]");
+        assert_eq!(content.spans.len(), 3);
+        if let SpanKind::Tagged { tag, spans, .. } = &content.spans[0].kind {
+            assert_eq!(tag, "quote");
+            assert!(matches!(&spans[..], [span] if span.to_plain_text() == "[b]x"));
+        } else {
+            panic!("Expected tagged span");
+        }
+        assert!(matches!(&content.spans[2].kind, SpanKind::Tagged { tag, .. } if tag == "b"));
+
+        let content = parse_content("[B]nested [b]bold[/b][/B]");
+        assert_eq!(content.to_plain_text(), "nested bold");
+    }
+
+    #[test]
+    fn test_unclosed_outer_same_name_tag() {
+        let content = parse_content("[b]a [b]b[/b] c");
+        assert_eq!(content.to_plain_text(), "[b]a b c");
+        assert_eq!(content.spans.len(), 3);
+        assert!(matches!(
+            &content.spans[1].kind,
+            SpanKind::Tagged { tag, spans, .. } if tag == "b" && spans[0].to_plain_text() == "b"
+        ));
+
+        let content = parse_content("[quote][b]x[b]y[/b][/quote]");
+        if let SpanKind::Tagged { tag, spans, .. } = &content.spans[0].kind {
+            assert_eq!(tag, "quote");
+            assert_eq!(spans.len(), 2);
+            assert_eq!(spans[0].to_plain_text(), "[b]x");
+        } else {
+            panic!("Expected tagged span");
+        }
+    }
+
+    #[test]
+    fn test_void_tag() {
+        let content = parse_content("above[hr]below [b]bold[/b]");
+        assert_eq!(content.spans.len(), 4);
+        assert!(matches!(
+            &content.spans[1].kind,
+            SpanKind::Tagged { tag, spans, .. } if tag == "hr" && spans.is_empty()
+        ));
+        assert!(matches!(&content.spans[3].kind, SpanKind::Tagged { tag, .. } if tag == "b"));
+
+        let content = parse_content("[hr][/hr]after");
+        assert_eq!(content.spans.len(), 2);
+    }
+
     #[test]
     fn test_subject_parsing() {
         let (tags, content) = parse_subject("[News][Important] Hello World");
@@ -423,6 +865,21 @@ mod tests {
         assert_eq!(content, "Hello World");
     }
 
+    #[test]
+    fn test_subject_full_width_spaces() {
+        let (tags, content) =
+            parse_subject("\u{3000}[讨论]\u{3000}[攻略]\u{3000}新版本配装\u{3000}");
+        assert_eq!(tags, vec!["讨论", "攻略"]);
+        assert_eq!(content, "新版本配装");
+    }
+
+    #[test]
+    fn test_subject_nested_brackets() {
+        let (tags, content) = parse_subject("[[x]] [News] Hello [world]");
+        assert_eq!(tags, vec!["[x]", "News"]);
+        assert_eq!(content, "Hello [world]");
+    }
+
     #[test]
     fn test_subject_only_tag() {
         let (tags, content) = parse_subject("[SingleTag]");
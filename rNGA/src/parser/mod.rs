@@ -3,5 +3,10 @@
 pub mod bbcode;
 pub mod xml;
 
-pub use bbcode::{parse_content, parse_subject};
-pub use xml::{extract_kv, parse_timestamp, XmlDocument, XmlNode};
+pub use bbcode::{
+    parse_content, parse_content_with_depth, parse_content_with_spans, parse_subject,
+};
+pub use xml::{
+    extract_kv, find_element, find_element_path, pages_for, parse_timestamp, ElementSpan,
+    XmlDocument, XmlNode,
+};
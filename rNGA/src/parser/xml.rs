@@ -2,6 +2,7 @@
 
 use crate::error::{Error, Result};
 use std::collections::HashMap;
+use std::ops::Range;
 use sxd_document::parser;
 use sxd_xpath::{nodeset::Node, Context, Factory, Value};
 
@@ -19,6 +20,13 @@ impl XmlDocument {
         Ok(Self { package })
     }
 
+    /// Parse a fragment of a response, such as a single list item, without
+    /// checking it for NGA error markers.
+    pub(crate) fn parse_fragment(xml: &str) -> Result<Self> {
+        let package = parser::parse(xml).map_err(|e| Error::Xml(e.to_string()))?;
+        Ok(Self { package })
+    }
+
     /// Get the root document.
     fn doc(&self) -> sxd_document::dom::Document<'_> {
         self.package.as_document()
@@ -76,6 +84,116 @@ impl XmlDocument {
     pub fn int_or(&self, expr: &str, default: i64) -> i64 {
         self.int(expr).unwrap_or(default)
     }
+
+    /// Number of pages of a paginated response.
+    ///
+    /// Uses the total row count in `__ROWS` and the per-page field at
+    /// `per_page_path`, falling back to `default_per_page` when it is absent.
+    /// Endpoints name the field differently, and thread.php carries both
+    /// `__T__ROWS_PAGE` and `__R__ROWS_PAGE`, so each caller picks its own.
+    pub fn total_pages(&self, per_page_path: &str, default_per_page: u32) -> u32 {
+        let count = |expr: &str| {
+            self.string_opt(expr)
+                .and_then(|s| s.trim().parse::<u32>().ok())
+        };
+        let per_page = count(per_page_path)
+            .filter(|&n| n > 0)
+            .unwrap_or(default_per_page);
+
+        pages_for(count("/root/__ROWS").unwrap_or(0), per_page)
+    }
+}
+
+/// Number of pages needed to hold `total_rows` rows, at least one.
+pub fn pages_for(total_rows: u32, per_page: u32) -> u32 {
+    if per_page == 0 {
+        return 1;
+    }
+    total_rows.div_ceil(per_page).max(1)
+}
+
+/// Byte ranges of an element within raw XML text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementSpan {
+    /// The whole element, including its tags.
+    pub outer: Range<usize>,
+    /// The content between the tags; empty for `<name/>`.
+    pub inner: Range<usize>,
+}
+
+/// Find the next top-level element named `name` in `xml[range]` by scanning
+/// the text, without building a DOM.
+///
+/// CDATA sections, comments and processing instructions are skipped, so markup
+/// inside them is never mistaken for an element.
+pub fn find_element(xml: &str, range: Range<usize>, name: &str) -> Option<ElementSpan> {
+    let mut pos = range.start;
+    let mut depth = 0usize;
+    let mut open = None;
+
+    while let Some(offset) = xml[pos..range.end].find('<') {
+        let start = pos + offset;
+        let rest = &xml[start..range.end];
+        let skip_past = |marker: &str| rest.find(marker).map(|i| start + i + marker.len());
+
+        if rest.starts_with("<![CDATA[") {
+            pos = skip_past("]]>")?;
+            continue;
+        }
+        if rest.starts_with("<!--") {
+            pos = skip_past("-->")?;
+            continue;
+        }
+        if rest.starts_with("<?") || rest.starts_with("<!") {
+            pos = skip_past(">")?;
+            continue;
+        }
+
+        let tag_end = skip_past(">")?;
+        let tag = &xml[start + 1..tag_end - 1];
+        if tag.starts_with('/') {
+            depth = depth.checked_sub(1)?;
+            if let (0, Some((outer_start, inner_start))) = (depth, open) {
+                return Some(ElementSpan {
+                    outer: outer_start..tag_end,
+                    inner: inner_start..start,
+                });
+            }
+        } else {
+            let self_closing = tag.ends_with('/');
+            let tag_name = tag
+                .trim_end_matches('/')
+                .split(char::is_whitespace)
+                .next()
+                .unwrap_or("");
+            if depth == 0 && tag_name == name {
+                if self_closing {
+                    return Some(ElementSpan {
+                        outer: start..tag_end,
+                        inner: tag_end..tag_end,
+                    });
+                }
+                open = Some((start, tag_end));
+            }
+            if !self_closing {
+                depth += 1;
+            }
+        }
+        pos = tag_end;
+    }
+
+    None
+}
+
+/// Follow a path of element names from the top of `xml`, as in
+/// `["root", "__R"]` for `/root/__R`.
+pub fn find_element_path(xml: &str, path: &[&str]) -> Option<ElementSpan> {
+    let (first, rest) = path.split_first()?;
+    let mut span = find_element(xml, 0..xml.len(), first)?;
+    for name in rest {
+        span = find_element(xml, span.inner.clone(), name)?;
+    }
+    Some(span)
 }
 
 /// XPath evaluation result.
@@ -155,7 +273,7 @@ impl<'a> XmlNode<'a> {
         map
     }
 
-    /// Get text content.
+    /// Get text content, including CDATA sections.
     pub fn text(&self) -> String {
         match self.0 {
             Node::Text(t) => t.text().to_owned(),
@@ -246,8 +364,31 @@ fn parse_int(s: &str) -> Result<i64> {
         .map_err(|_| Error::parse(format!("Invalid integer: {}", s)))
 }
 
+/// Check if a response is an HTML maintenance or verification page.
+pub(crate) fn check_html_page(text: &str) -> Result<()> {
+    const MAINTENANCE_MARKERS: &[&str] = &["服务器维护", "系统维护", "维护中", "under maintenance"];
+    const VERIFICATION_MARKERS: &[&str] = &["验证码", "人机验证", "captcha", "/verify"];
+
+    let head = text.trim_start();
+    let head: String = head.chars().take(4096).collect::<String>().to_lowercase();
+    if !head.starts_with("<!doctype html") && !head.starts_with("<html") {
+        return Ok(());
+    }
+
+    if MAINTENANCE_MARKERS.iter().any(|m| head.contains(m)) {
+        return Err(Error::Maintenance);
+    }
+    if VERIFICATION_MARKERS.iter().any(|m| head.contains(m)) {
+        return Err(Error::VerificationRequired);
+    }
+
+    Ok(())
+}
+
 /// Check if XML response contains an NGA error.
 fn check_nga_error(xml: &str) -> Result<()> {
+    check_html_page(xml)?;
+
     if !xml.contains("__error") && !xml.contains("error code=") {
         return Ok(());
     }
@@ -269,7 +410,7 @@ fn check_nga_error(xml: &str) -> Result<()> {
 
             let message = html_escape::decode_html_entities(message);
 
-            return Err(Error::nga(code, message.as_ref()));
+            return Err(Error::from_nga(code, message.as_ref()));
         }
     }
 
@@ -307,6 +448,82 @@ pub fn parse_timestamp(s: &str) -> Option<i64> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_find_element() {
+        let xml = "<?xml version=\"1.0\"?><root><__U/><__R>\
+<item><pid>1</pid><attachs><item>a.png</item></attachs></item>\
+<!-- <item>comment</item> -->\
+<item><content><![CDATA[</item><item>]]></content></item>\
+<item/></__R></root>";
+
+        let r = find_element_path(xml, &["root", "__R"]).unwrap();
+        assert!(xml[r.outer.clone()].starts_with("<__R>"));
+        assert!(xml[r.outer.clone()].ends_with("</__R>"));
+
+        let mut items = Vec::new();
+        let mut pos = r.inner.start;
+        while let Some(item) = find_element(xml, pos..r.inner.end, "item") {
+            pos = item.outer.end;
+            items.push(&xml[item.inner]);
+        }
+        assert_eq!(
+            items,
+            vec![
+                "<pid>1</pid><attachs><item>a.png</item></attachs>",
+                "<content><![CDATA[</item><item>]]></content>",
+                "",
+            ]
+        );
+
+        assert!(find_element_path(xml, &["root", "__T"]).is_none());
+        let u = find_element_path(xml, &["root", "__U"]).unwrap();
+        assert!(u.inner.is_empty());
+    }
+
+    #[test]
+    fn test_pages_for() {
+        assert_eq!(pages_for(0, 20), 1);
+        assert_eq!(pages_for(1, 20), 1);
+        assert_eq!(pages_for(20, 20), 1);
+        assert_eq!(pages_for(21, 20), 2);
+        assert_eq!(pages_for(70, 35), 2);
+        assert_eq!(pages_for(71, 35), 3);
+        assert_eq!(pages_for(100, 25), 4);
+        assert_eq!(pages_for(10, 0), 1);
+    }
+
+    #[test]
+    fn test_total_pages_uses_response_page_size() {
+        let doc = XmlDocument::parse(
+            "<root><__ROWS>100</__ROWS><__T__ROWS_PAGE>25</__T__ROWS_PAGE></root>",
+        )
+        .unwrap();
+        assert_eq!(doc.total_pages("/root/__T__ROWS_PAGE", 35), 4);
+
+        let doc = XmlDocument::parse("<root><__ROWS>40</__ROWS></root>").unwrap();
+        assert_eq!(doc.total_pages("/root/__ROWS_PAGE", 20), 2);
+        assert_eq!(doc.total_pages("/root/__ROWS_PAGE", 35), 2);
+
+        let doc =
+            XmlDocument::parse("<root><__ROWS>40</__ROWS><__ROWS_PAGE>0</__ROWS_PAGE></root>")
+                .unwrap();
+        assert_eq!(doc.total_pages("/root/__ROWS_PAGE", 20), 2);
+
+        let doc = XmlDocument::parse("<root><data/></root>").unwrap();
+        assert_eq!(doc.total_pages("/root/__ROWS_PAGE", 20), 1);
+    }
+
+    #[test]
+    fn test_total_pages_picks_named_field() {
+        let doc = XmlDocument::parse(
+            "<root><__ROWS>70</__ROWS><__R__ROWS_PAGE>20</__R__ROWS_PAGE>\
+             <__T__ROWS_PAGE>35</__T__ROWS_PAGE></root>",
+        )
+        .unwrap();
+        assert_eq!(doc.total_pages("/root/__T__ROWS_PAGE", 35), 2);
+        assert_eq!(doc.total_pages("/root/__R__ROWS_PAGE", 20), 4);
+    }
+
     #[test]
     fn test_parse_int() {
         assert_eq!(parse_int("123").unwrap(), 123);
@@ -336,6 +553,32 @@ mod tests {
         assert_eq!(items[0].attr("name"), Some("test".to_owned()));
     }
 
+    #[test]
+    fn test_flood_control_error() {
+        let xml = r#"<error code="15" message="你发帖太快了，请 15 秒后再试"/>"#;
+        assert!(matches!(
+            check_nga_error(xml),
+            Err(Error::FloodControl { retry_after }) if retry_after.as_secs() == 15
+        ));
+    }
+
+    #[test]
+    fn test_cdata_text() {
+        let xml = r#"<root><item><content><![CDATA[[quote]<b>a > b && c</b>[/quote]]]></content><tail>x<![CDATA[<y>]]>z</tail></item></root>"#;
+        let doc = XmlDocument::parse(xml).unwrap();
+
+        let content = doc.select_one("/root/item/content").unwrap().unwrap();
+        assert_eq!(content.text(), "[quote]<b>a > b && c</b>[/quote]");
+
+        let attrs = doc.select_one("/root/item").unwrap().unwrap().attrs();
+        assert_eq!(attrs["content"], "[quote]<b>a > b && c</b>[/quote]");
+        assert_eq!(attrs["tail"], "x<y>z");
+        assert_eq!(
+            doc.string_opt("/root/item/content").as_deref(),
+            Some("[quote]<b>a > b && c</b>[/quote]")
+        );
+    }
+
     #[test]
     fn test_nga_error_detection() {
         let error_xml = r#"<error code="1" message="Not logged in"/>"#;
@@ -345,4 +588,30 @@ mod tests {
         let ok_xml = r#"<data><item id="1"/></data>"#;
         assert!(check_nga_error(ok_xml).is_ok());
     }
+
+    #[test]
+    fn test_maintenance_page_detection() {
+        let html = r#"<!DOCTYPE html>
+<html><head><meta charset="gbk"><title>NGA</title></head>
+<body><div class="notice">服务器维护中，预计 30 分钟后恢复</div></body></html>"#;
+        assert!(matches!(check_html_page(html), Err(Error::Maintenance)));
+        assert!(matches!(XmlDocument::parse(html), Err(Error::Maintenance)));
+    }
+
+    #[test]
+    fn test_verification_page_detection() {
+        let html = r#"<html><head><script>
+window.location.href = "https://bbs.nga.cn/nuke.php?__lib=login&__act=captcha";
+</script></head><body>请输入验证码</body></html>"#;
+        assert!(matches!(
+            check_html_page(html),
+            Err(Error::VerificationRequired)
+        ));
+    }
+
+    #[test]
+    fn test_html_page_ignores_xml_content() {
+        let xml = r#"<root><subject>服务器维护中的验证码问题</subject></root>"#;
+        assert!(check_html_page(xml).is_ok());
+    }
 }
@@ -0,0 +1,93 @@
+//! Sticker name resolution.
+
+/// NGA sticker CDN path.
+pub const STICKER_BASE_URL: &str = "https://img4.nga.178.com/ngabbs/post/smile/";
+
+/// Known sticker images by pack, as `(name, file)` pairs.
+///
+/// Only commonly used stickers are listed; others resolve to `None`.
+const PACKS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "ac",
+        &[
+            ("blink", "ac0.png"),
+            ("goodjob", "ac1.png"),
+            ("上", "ac2.png"),
+            ("中枪", "ac3.png"),
+            ("偷笑", "ac4.png"),
+            ("冷", "ac5.png"),
+            ("凌乱", "ac6.png"),
+            ("吓", "ac8.png"),
+            ("吻", "ac9.png"),
+            ("呆", "ac10.png"),
+            ("咦", "ac11.png"),
+            ("哦", "ac12.png"),
+            ("哭", "ac13.png"),
+            ("哭1", "ac14.png"),
+            ("哭笑", "ac15.png"),
+            ("喘", "ac17.png"),
+        ],
+    ),
+    (
+        "pg",
+        &[
+            ("战斗力", "pg01.png"),
+            ("哈啤", "pg02.png"),
+            ("满分", "pg03.png"),
+            ("衰", "pg04.png"),
+            ("拒绝", "pg05.png"),
+            ("心", "pg06.png"),
+            ("严肃", "pg07.png"),
+            ("吃瓜", "pg08.png"),
+        ],
+    ),
+];
+
+/// Textual fallbacks for stickers that have a close emoji.
+const ALIASES: &[(&str, &str)] = &[
+    ("ac:goodjob", "👍"),
+    ("ac:偷笑", "🤭"),
+    ("ac:哭", "😭"),
+    ("ac:吻", "😘"),
+    ("pg:心", "❤"),
+    ("pg:吃瓜", "🍉"),
+];
+
+/// Resolve a `pack:name` sticker to its image URL.
+pub fn sticker_url(name: &str) -> Option<String> {
+    let (pack, sticker) = name.split_once(':')?;
+    let (_, files) = PACKS.iter().find(|(p, _)| *p == pack)?;
+    let (_, file) = files.iter().find(|(n, _)| *n == sticker)?;
+    Some(format!("{}{}", STICKER_BASE_URL, file))
+}
+
+/// Get a textual alias for a sticker, if one is defined.
+pub fn sticker_alias(name: &str) -> Option<&'static str> {
+    ALIASES.iter().find(|(n, _)| *n == name).map(|(_, a)| *a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sticker_url() {
+        assert_eq!(
+            sticker_url("ac:blink").as_deref(),
+            Some("https://img4.nga.178.com/ngabbs/post/smile/ac0.png")
+        );
+        assert_eq!(
+            sticker_url("pg:吃瓜").as_deref(),
+            Some("https://img4.nga.178.com/ngabbs/post/smile/pg08.png")
+        );
+        assert_eq!(sticker_url("ac:unknown"), None);
+        assert_eq!(sticker_url("zz:blink"), None);
+        assert_eq!(sticker_url("blink"), None);
+    }
+
+    #[test]
+    fn test_sticker_alias() {
+        assert_eq!(sticker_alias("ac:goodjob"), Some("👍"));
+        assert_eq!(sticker_alias("ac:blink"), None);
+    }
+}
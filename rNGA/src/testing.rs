@@ -0,0 +1,101 @@
+//! Local HTTP fixtures for tests.
+//!
+//! Available to other crates with the `test-util` feature.
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+/// How a [`serve`] fixture picks the body of each response.
+#[derive(Debug, Clone)]
+pub enum Routes {
+    /// Answer one request per body, in order.
+    InOrder(Vec<&'static str>),
+    /// Answer one request per route, picking the body routed to the value of
+    /// the named query parameter, or an empty body if none matches.
+    ByParam(&'static str, Vec<(String, &'static str)>),
+}
+
+impl Routes {
+    fn len(&self) -> usize {
+        match self {
+            Routes::InOrder(bodies) => bodies.len(),
+            Routes::ByParam(_, routes) => routes.len(),
+        }
+    }
+
+    /// Body for the `n`th request, whose first line is `request_line`.
+    fn body(&self, n: usize, request_line: &str) -> &'static str {
+        match self {
+            Routes::InOrder(bodies) => bodies[n],
+            Routes::ByParam(param, routes) => {
+                let prefix = format!("{}=", param);
+                let value = request_line
+                    .split(['?', '&', ' '])
+                    .find_map(|p| p.strip_prefix(prefix.as_str()))
+                    .unwrap_or_default();
+                routes
+                    .iter()
+                    .find(|(v, _)| v == value)
+                    .map_or("", |(_, body)| *body)
+            }
+        }
+    }
+}
+
+/// Serve canned responses on a local port and return its base URL.
+///
+/// Each response has the status line `status`, such as `"200 OK"`, and the
+/// extra `headers`, each ending in `\r\n`. The server stops once every route
+/// has been answered.
+pub fn serve(routes: Routes, status: &'static str, headers: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for n in 0..routes.len() {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let len = stream.read(&mut buf).unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..len]);
+            let body = routes.body(n, request.lines().next().unwrap_or_default());
+            let response = format!(
+                "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                headers,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    base_url
+}
+
+/// Serve `bodies` in order with `200 OK`, one per request.
+pub fn serve_bodies(bodies: Vec<&'static str>) -> String {
+    serve(Routes::InOrder(bodies), "200 OK", "")
+}
+
+/// Accept one request and never answer it.
+pub fn serve_silent() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let _ = stream.read(&mut [0u8; 4096]);
+        std::thread::sleep(Duration::from_secs(5));
+    });
+
+    base_url
+}
+
+/// Base URL of a local port with nothing listening on it.
+pub fn closed_base_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}/", listener.local_addr().unwrap());
+    drop(listener);
+    base_url
+}